@@ -3,22 +3,59 @@
 //! This module provides read-only access to DataForge's shared data and
 //! implements computation functions that can be run on the data.
 
+use crate::compute::alignment::DepthAlignment;
+use crate::compute::batch::{BatchRequest, BatchTarget, CurveBinding, WellRun};
+use crate::compute::cache::{CachedResult, ResultCache};
 use crate::compute::context::{CancellationToken, ProgressState};
-use crate::compute::data_loader::DataForgeCurveLoader;
-use crate::compute::engine::ExecutionEngine;
+use crate::compute::data_loader::{load_well_markers, DataForgeCurveLoader};
+use crate::compute::engine::{ExecutionEngine, ExecutionOptions, WellMarker};
+use crate::compute::error::UdfError;
+use crate::compute::interval::DepthInterval;
+use crate::compute::lineage::{lineage_graph, LineageDirection, LineageGraph};
+use crate::compute::mnemonics::{MatchKind, MnemonicDictionary, MnemonicRule};
+use crate::compute::output_writer::{OutputWriter, RegisteredOutput};
 use crate::compute::parameters::ParameterValue;
+use crate::compute::prov::{export_prov, ProvExportScope};
 use crate::compute::providers::register_builtin_providers;
 use crate::compute::registry::{ProviderInfo, UdfInfo, UdfRegistry};
+use crate::compute::staleness::{
+    check_staleness, has_column, load_source_execution, recompute_target, DerivedCurveStatus,
+    SourceExecution, StalenessScope,
+};
+use crate::compute::taxonomy::CurveTaxonomy;
+use crate::compute::types::{
+    CurveDataType, ExecutionRecord, ExecutionStatus, InputReference, NamedOutput,
+    OutputCurveData, OutputReference, UdfOutput,
+};
+use crate::compute::units::{ParameterConversion, UnitConversion};
+use crate::compute::verify::{self, VerificationReport};
+use crate::compute::workflow::{remap_saved_inputs, NodeRun, WorkflowDefinition, WorkflowNode};
+use crate::local_db::{ExecutionHistoryFilter, ExecutionHistoryPage, LocalDatabase};
 use duckdb::Connection as DuckDbConnection;
 use log::info;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use uuid::Uuid;
 
+/// Event emitted when an execution's progress or status message changes.
+pub const EXECUTION_PROGRESS_EVENT: &str = "udf-execution-progress";
+/// Event emitted for each warning produced by an execution.
+pub const EXECUTION_WARNING_EVENT: &str = "udf-execution-warning";
+/// Event emitted once an execution has finished (completed, failed or cancelled).
+pub const EXECUTION_COMPLETED_EVENT: &str = "udf-execution-completed";
+/// Event emitted as each well of a batch finishes.
+pub const BATCH_WELL_COMPLETED_EVENT: &str = "udf-batch-well-completed";
+/// Event emitted once every well of a batch has finished.
+pub const BATCH_COMPLETED_EVENT: &str = "udf-batch-completed";
+/// Event emitted as each node of a workflow run finishes.
+pub const WORKFLOW_NODE_COMPLETED_EVENT: &str = "udf-workflow-node-completed";
+/// Event emitted once every node of a workflow run has finished.
+pub const WORKFLOW_COMPLETED_EVENT: &str = "udf-workflow-completed";
+
 /// Active execution tracking for progress and cancellation.
 #[derive(Default)]
 pub struct ActiveExecutions {
@@ -26,6 +63,33 @@ pub struct ActiveExecutions {
     executions: RwLock<HashMap<String, (Arc<CancellationToken>, Arc<ProgressState>)>>,
 }
 
+impl ActiveExecutions {
+    /// Track an execution so it can be queried and cancelled
+    fn register(
+        &self,
+        execution_id: &str,
+        cancel_token: &Arc<CancellationToken>,
+        progress_state: &Arc<ProgressState>,
+    ) -> Result<(), String> {
+        let mut executions = self
+            .executions
+            .write()
+            .map_err(|e| format!("Failed to lock executions: {}", e))?;
+        executions.insert(
+            execution_id.to_string(),
+            (cancel_token.clone(), progress_state.clone()),
+        );
+        Ok(())
+    }
+
+    /// Stop tracking a finished execution
+    fn unregister(&self, execution_id: &str) {
+        if let Ok(mut executions) = self.executions.write() {
+            executions.remove(execution_id);
+        }
+    }
+}
+
 /// State for the Compute application
 pub struct ComputeState {
     /// Path to DataForge's app data directory
@@ -34,8 +98,8 @@ pub struct ComputeState {
     pub db: Option<Connection>,
     /// UDF Registry
     pub registry: Option<Arc<UdfRegistry>>,
-    /// Execution Engine
-    pub engine: Option<ExecutionEngine>,
+    /// Execution Engine (shared with background executions)
+    pub engine: Option<Arc<ExecutionEngine>>,
 }
 
 impl Default for ComputeState {
@@ -97,7 +161,7 @@ impl ComputeState {
         );
 
         self.registry = Some(registry);
-        self.engine = Some(engine);
+        self.engine = Some(Arc::new(engine));
 
         Ok(())
     }
@@ -127,6 +191,11 @@ impl ComputeState {
         }
     }
 
+    /// Get the path to DataForge's SQLite database
+    pub fn db_path(&self) -> Option<PathBuf> {
+        self.dataforge_data_dir.as_ref().map(|d| d.join("dataforge.db"))
+    }
+
     /// Get the blobs directory path
     pub fn blobs_dir(&self) -> Option<PathBuf> {
         self.dataforge_data_dir.as_ref().map(|d| d.join("blobs"))
@@ -178,6 +247,7 @@ pub struct CurveInfo {
     pub unit: Option<String>,
     pub description: Option<String>,
     pub main_curve_type: Option<String>,
+    pub property_id: Option<String>,
     pub min_depth: Option<f64>,
     pub max_depth: Option<f64>,
     pub row_count: i64,
@@ -239,16 +309,34 @@ pub struct MovingAverageResult {
     pub data: Vec<CurveDataPoint>,
 }
 
-#[derive(Debug, Serialize)]
+/// One named output curve of an execution
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecuteUdfOutput {
+    pub name: String,
+    pub mnemonic: String,
+    pub unit: String,
+    pub curve_type: CurveDataType,
+    pub curve_id: Option<String>,
+    pub data: Vec<CurveDataPoint>,
+}
+
+/// Result of an execution.
+///
+/// `output_mnemonic`, `output_curve_id` and `output_data` describe the first
+/// output; `outputs` lists all of them.
+#[derive(Debug, Clone, Serialize)]
 pub struct ExecuteUdfResult {
     pub success: bool,
     pub execution_id: String,
     pub output_mnemonic: Option<String>,
     pub output_curve_id: Option<String>,
     pub output_data: Option<Vec<CurveDataPoint>>,
+    pub outputs: Vec<ExecuteUdfOutput>,
     pub warnings: Vec<String>,
     pub error: Option<String>,
     pub saved: bool,
+    /// Execution whose cached output was reused, if the UDF did not run
+    pub cached_from: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -259,6 +347,17 @@ pub struct ExecuteUdfRequest {
     pub parameters: HashMap<String, serde_json::Value>,
     #[serde(default)]
     pub save_result: bool,
+    /// Run the UDF even if a cached result exists
+    #[serde(default)]
+    pub force_recompute: bool,
+    /// Derived curves to save outputs into as new versions, by output name
+    /// (set by `recompute_derived_curve`)
+    #[serde(skip)]
+    pub replace_curves: HashMap<String, Uuid>,
+    /// Recorded intervals of depth range and zone parameters (set by
+    /// `recompute_derived_curve`)
+    #[serde(skip)]
+    pub depth_intervals: BTreeMap<String, DepthInterval>,
 }
 
 // ==== Tauri Commands ====
@@ -376,7 +475,8 @@ pub fn list_curves(
 
     // Join with curve_properties to get the canonical curve type
     // The property_id maps to curve_properties.id (e.g., 'gamma_ray', 'bulk_density')
-    // We convert this to the MainCurveType format expected by UDFs (e.g., 'GR', 'RHOB')
+    // The taxonomy maps it to the MainCurveType code expected by UDFs (e.g., 'GR', 'RHOB')
+    let taxonomy = CurveTaxonomy::load(db).map_err(|e| e.to_string())?;
     let mut stmt = db
         .prepare(
             r#"SELECT c.id, c.mnemonic, c.unit, c.description,
@@ -395,7 +495,7 @@ pub fn list_curves(
         .query_map([&well_id], |row| {
             let property_id: Option<String> = row.get(4)?;
             // Map property_id to MainCurveType code
-            let main_curve_type = property_id.map(|pid| property_id_to_curve_type(&pid));
+            let main_curve_type = property_id.as_deref().map(|pid| taxonomy.type_code(pid));
 
             Ok(CurveInfo {
                 id: row.get(0)?,
@@ -403,6 +503,7 @@ pub fn list_curves(
                 unit: row.get(2)?,
                 description: row.get(3)?,
                 main_curve_type,
+                property_id,
                 min_depth: row.get(5)?,
                 max_depth: row.get(6)?,
                 row_count: row.get(7)?,
@@ -423,6 +524,7 @@ pub struct CurveInfoWithWell {
     pub unit: Option<String>,
     pub description: Option<String>,
     pub main_curve_type: Option<String>,
+    pub property_id: Option<String>,
     pub min_depth: Option<f64>,
     pub max_depth: Option<f64>,
     pub row_count: i64,
@@ -440,6 +542,7 @@ pub fn list_all_curves_for_workspace(
     let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
 
     // Join curves with wells and curve_properties to get all curve info
+    let taxonomy = CurveTaxonomy::load(db).map_err(|e| e.to_string())?;
     let mut stmt = db
         .prepare(
             r#"SELECT c.id, c.mnemonic, c.unit, c.description,
@@ -459,7 +562,7 @@ pub fn list_all_curves_for_workspace(
     let curves = stmt
         .query_map([&workspace_id], |row| {
             let property_id: Option<String> = row.get(4)?;
-            let main_curve_type = property_id.map(|pid| property_id_to_curve_type(&pid));
+            let main_curve_type = property_id.as_deref().map(|pid| taxonomy.type_code(pid));
 
             Ok(CurveInfoWithWell {
                 id: row.get(0)?,
@@ -467,6 +570,7 @@ pub fn list_all_curves_for_workspace(
                 unit: row.get(2)?,
                 description: row.get(3)?,
                 main_curve_type,
+                property_id,
                 min_depth: row.get(5)?,
                 max_depth: row.get(6)?,
                 row_count: row.get(7)?,
@@ -481,20 +585,17 @@ pub fn list_all_curves_for_workspace(
     Ok(curves)
 }
 
-/// Convert DataForge property_id to MainCurveType code
-fn property_id_to_curve_type(property_id: &str) -> String {
-    match property_id {
-        "gamma_ray" => "GR".to_string(),
-        "bulk_density" => "RHOB".to_string(),
-        "neutron_porosity" => "NPHI".to_string(),
-        "deep_resistivity" | "medium_resistivity" | "shallow_resistivity" => "RT".to_string(),
-        "caliper" => "CALI".to_string(),
-        "compressional_slowness" | "shear_slowness" => "DT".to_string(),
-        "spontaneous_potential" => "SP".to_string(),
-        "photoelectric" => "PE".to_string(),
-        "depth" => "DEPTH".to_string(),
-        _ => "OTHER".to_string(),
-    }
+/// List the well tops (formation markers) of a well, shallowest first
+#[tauri::command]
+pub fn get_well_markers(
+    well_id: String,
+    state: State<'_, Mutex<ComputeState>>,
+) -> Result<Vec<WellMarker>, String> {
+    let state = state.lock().expect("Failed to lock state");
+    let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+    let well_uuid = Uuid::parse_str(&well_id).map_err(|e| format!("Invalid well ID: {}", e))?;
+
+    load_well_markers(db, well_uuid).map_err(|e| e.to_string())
 }
 
 /// Get curve data by reading the parquet blob
@@ -782,65 +883,136 @@ pub fn get_udf_parameters(
         .map_err(|e| e.to_string())
 }
 
-/// Execute a UDF
+/// Execute a UDF in the background
+///
+/// Returns the execution ID immediately. The computation runs on a worker
+/// thread without holding the compute state lock; progress, warnings and
+/// the final result are pushed to the frontend as Tauri events.
 #[tauri::command]
 pub fn execute_udf(
+    app: AppHandle,
     request: ExecuteUdfRequest,
     state: State<'_, Mutex<ComputeState>>,
     active_executions: State<'_, ActiveExecutions>,
-) -> Result<ExecuteUdfResult, String> {
-    // Generate execution ID upfront for tracking
-    let execution_id = Uuid::new_v4().to_string();
+) -> Result<String, String> {
+    // Grab what the worker needs, then release the state lock
+    let (engine, db_path, blobs_dir) = {
+        let state = state.lock().expect("Failed to lock state");
+        let engine = state.engine.clone().ok_or("Engine not initialized")?;
+        let db_path = state.db_path().ok_or("Not connected to DataForge")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        (engine, db_path, blobs_dir)
+    };
+
+    spawn_execution(app, request, engine, db_path, blobs_dir, &active_executions)
+}
 
-    // Create cancellation token and progress state for this execution
+/// Start an execution on a worker thread, returning its execution ID.
+///
+/// Completion is announced with the execution-completed event.
+fn spawn_execution(
+    app: AppHandle,
+    request: ExecuteUdfRequest,
+    engine: Arc<ExecutionEngine>,
+    db_path: PathBuf,
+    blobs_dir: PathBuf,
+    active_executions: &ActiveExecutions,
+) -> Result<String, String> {
+    // Generate execution ID upfront for tracking; the engine records the
+    // run under the same ID so history and provenance lookups match
+    let execution_uuid = Uuid::new_v4();
+    let execution_id = execution_uuid.to_string();
+
+    // Create cancellation token and progress state for this execution.
+    // Progress changes are pushed to the frontend as events.
     let cancel_token = Arc::new(CancellationToken::new());
-    let progress_state = Arc::new(ProgressState::new());
+    let progress_state = emitting_progress_state(&app, &execution_id, &cancel_token);
 
     // Register this execution for progress tracking
-    {
-        let mut executions = active_executions
-            .executions
-            .write()
-            .map_err(|e| format!("Failed to lock executions: {}", e))?;
-        executions.insert(
-            execution_id.clone(),
-            (cancel_token.clone(), progress_state.clone()),
-        );
-    }
+    active_executions.register(&execution_id, &cancel_token, &progress_state)?;
+
+    let task_execution_id = execution_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let execution_id = task_execution_id;
+        let result = execute_udf_inner(
+            &app,
+            execution_uuid,
+            request,
+            &engine,
+            &db_path,
+            blobs_dir,
+            cancel_token,
+            progress_state,
+        )
+        .unwrap_or_else(|error| ExecuteUdfResult {
+            success: false,
+            execution_id: execution_id.clone(),
+            output_mnemonic: None,
+            output_curve_id: None,
+            output_data: None,
+            outputs: Vec::new(),
+            warnings: Vec::new(),
+            error: Some(error),
+            saved: false,
+            cached_from: None,
+        });
 
-    // Execute and clean up on exit
-    let result = execute_udf_inner(
-        &execution_id,
-        request,
-        state,
-        cancel_token,
-        progress_state,
-    );
+        for warning in &result.warnings {
+            let _ = app.emit(
+                EXECUTION_WARNING_EVENT,
+                ExecutionWarning {
+                    execution_id: execution_id.clone(),
+                    warning: warning.clone(),
+                },
+            );
+        }
 
-    // Unregister execution when done
-    {
-        if let Ok(mut executions) = active_executions.executions.write() {
-            executions.remove(&execution_id);
+        // Unregister execution before announcing completion
+        app.state::<ActiveExecutions>().unregister(&execution_id);
+
+        if let Err(e) = app.emit(EXECUTION_COMPLETED_EVENT, result) {
+            log::error!("❌ Failed to emit completion for {}: {}", execution_id, e);
         }
-    }
+    });
 
-    result
+    Ok(execution_id)
 }
 
-/// Inner execution logic (separated for cleanup handling)
-fn execute_udf_inner(
+/// Create a progress state that emits progress events for an execution.
+fn emitting_progress_state(
+    app: &AppHandle,
     execution_id: &str,
+    cancel_token: &Arc<CancellationToken>,
+) -> Arc<ProgressState> {
+    let app = app.clone();
+    let execution_id = execution_id.to_string();
+    let cancel_token = cancel_token.clone();
+    Arc::new(ProgressState::with_listener(Box::new(
+        move |progress: f64, message: Option<&str>| {
+            let _ = app.emit(
+                EXECUTION_PROGRESS_EVENT,
+                ExecutionProgress {
+                    execution_id: execution_id.clone(),
+                    progress: progress as u8,
+                    message: message.map(str::to_string),
+                    is_cancelled: cancel_token.is_cancelled(),
+                },
+            );
+        },
+    )))
+}
+
+/// Inner execution logic (runs on a worker thread)
+fn execute_udf_inner(
+    app: &AppHandle,
+    execution_id: Uuid,
     request: ExecuteUdfRequest,
-    state: State<'_, Mutex<ComputeState>>,
-    _cancel_token: Arc<CancellationToken>,
-    _progress_state: Arc<ProgressState>,
+    engine: &ExecutionEngine,
+    db_path: &Path,
+    blobs_dir: PathBuf,
+    cancel_token: Arc<CancellationToken>,
+    progress_state: Arc<ProgressState>,
 ) -> Result<ExecuteUdfResult, String> {
-    let state = state.lock().expect("Failed to lock state");
-
-    let engine = state.engine.as_ref().ok_or("Engine not initialized")?;
-    let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
-    let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
-
     // Parse UUIDs
     let well_id = Uuid::parse_str(&request.well_id)
         .map_err(|e| format!("Invalid well ID: {}", e))?;
@@ -857,78 +1029,803 @@ fn execute_udf_inner(
         })
         .collect();
 
-    // Create curve loader
-    let loader = DataForgeCurveLoader::new(db, blobs_dir.clone());
-
-    // TODO: Pass cancel_token and progress_state to engine.execute
-    // when we add async execution support
+    // Create curve loader with its own connection
+    let writer_blobs_dir = blobs_dir.clone();
+    let loader = DataForgeCurveLoader::open(db_path, blobs_dir)
+        .map_err(|e| e.to_string())?
+        .with_mnemonics(workspace_mnemonics(app, &request.workspace_id));
+
+    // Share the registered handles so cancel_execution and
+    // get_execution_progress affect this run
+    let options = ExecutionOptions::new()
+        .with_cancellation_token(cancel_token)
+        .with_progress_state(progress_state)
+        .with_execution_id(execution_id)
+        .with_result_cache(LocalResultCache::shared(app))
+        .with_force_recompute(request.force_recompute)
+        .with_depth_intervals(request.depth_intervals.clone());
 
     // Execute
     let result = engine
-        .execute(&request.udf_id, well_id, workspace_id, parameters, &loader)
+        .execute(&request.udf_id, well_id, workspace_id, parameters, &loader, &options)
         .map_err(|e| e.to_string())?;
 
+    let mut record = result.record;
+
     // Build response
-    if let Some(output) = result.output {
-        let output_data: Vec<CurveDataPoint> = output
-            .curve_data
-            .depths
+    let response = if let Some(output) = result.output {
+        let mut outputs: Vec<ExecuteUdfOutput> = output
+            .outputs
             .iter()
-            .zip(output.curve_data.values.iter())
-            .map(|(d, v)| CurveDataPoint {
-                depth: *d,
-                value: *v,
+            .map(|named| ExecuteUdfOutput {
+                name: named.name.clone(),
+                mnemonic: named.curve_data.mnemonic.clone(),
+                unit: named.curve_data.unit.clone(),
+                curve_type: named.curve_data.curve_type,
+                curve_id: None,
+                data: curve_data_points(&named.curve_data),
             })
             .collect();
+        let mut warnings = output.warnings.clone();
+
+        // Optionally commit the result back to DataForge
+        let mut saved = false;
+        if request.save_result {
+            info!("💾 Saving {} output curve(s)", output.outputs.len());
+            match commit_output(
+                db_path,
+                writer_blobs_dir,
+                well_id,
+                &output.outputs,
+                &request.replace_curves,
+                &mut record,
+            ) {
+                Ok(registered) => {
+                    for (entry, registered) in outputs.iter_mut().zip(registered) {
+                        entry.curve_id = Some(registered.curve_id.to_string());
+                    }
+                    saved = true;
+                }
+                Err(e) => {
+                    log::error!("❌ Failed to save output of {}: {}", execution_id, e);
+                    warnings.push(format!("Output was not saved: {}", e));
+                }
+            }
+        }
 
-        let mnemonic = output.curve_data.mnemonic.clone();
-        let warnings = output.warnings.clone();
-
-        // Optionally save the result back to DataForge
-        let (saved, output_curve_id) = if request.save_result {
-            // Note: For saving, we would need a read-write connection
-            // For now, we log that saving was requested but not performed
-            // because the DB is opened read-only
-            info!("💾 Save requested for output curve: {}", mnemonic);
-
-            // In a full implementation, we would:
-            // 1. Open a read-write connection to the DB
-            // 2. Use OutputWriter to write the blob and register the curve
-            // For MVP, we indicate save was requested
-            (false, None)
-        } else {
-            (false, None)
-        };
-
-        Ok(ExecuteUdfResult {
+        let primary = outputs.first();
+        ExecuteUdfResult {
             success: true,
             execution_id: execution_id.to_string(),
-            output_mnemonic: Some(mnemonic),
-            output_curve_id,
-            output_data: Some(output_data),
+            output_mnemonic: primary.map(|o| o.mnemonic.clone()),
+            output_curve_id: primary.and_then(|o| o.curve_id.clone()),
+            output_data: primary.map(|o| o.data.clone()),
+            outputs,
             warnings,
             error: None,
             saved,
-        })
+            cached_from: record.cached_from.map(|id| id.to_string()),
+        }
     } else {
-        Ok(ExecuteUdfResult {
+        ExecuteUdfResult {
             success: false,
             execution_id: execution_id.to_string(),
             output_mnemonic: None,
             output_curve_id: None,
             output_data: None,
+            outputs: Vec::new(),
             warnings: Vec::new(),
-            error: result.record.error_message,
+            error: record.error_message.clone(),
             saved: false,
+            cached_from: None,
+        }
+    };
+
+    // Every run is recorded, whether it completed, failed or was cancelled
+    record_execution_history(app, &record);
+
+    Ok(response)
+}
+
+/// Commit execution outputs to DataForge over a read-write connection.
+///
+/// The blobs, curves and execution record are written atomically; on
+/// success the record carries the saved outputs. Outputs named in `replace`
+/// are saved as a new version of that derived curve.
+fn commit_output(
+    db_path: &Path,
+    blobs_dir: PathBuf,
+    well_id: Uuid,
+    outputs: &[NamedOutput],
+    replace: &HashMap<String, Uuid>,
+    record: &mut ExecutionRecord,
+) -> Result<Vec<RegisteredOutput>, String> {
+    let mut db = Connection::open(db_path)
+        .map_err(|e| format!("Failed to open database for writing: {}", e))?;
+
+    let registered = OutputWriter::new(blobs_dir)
+        .commit_execution_replacing(&mut db, well_id, outputs, replace, record)
+        .map_err(|e| e.to_string())?;
+
+    for (output, registered) in outputs.iter().zip(&registered) {
+        info!(
+            "💾 Saved derived curve {} ({}) with {} points",
+            registered.curve_id,
+            output.curve_data.mnemonic,
+            output.curve_data.depths.len()
+        );
+    }
+
+    Ok(registered)
+}
+
+/// Convert output curve data to depth/value points for the frontend
+fn curve_data_points(curve: &OutputCurveData) -> Vec<CurveDataPoint> {
+    curve
+        .depths
+        .iter()
+        .zip(curve.values.iter())
+        .map(|(d, v)| CurveDataPoint {
+            depth: *d,
+            value: *v,
         })
+        .collect()
+}
+
+/// Persist an execution record to the local execution history.
+///
+/// History is best-effort: failing to record a run is logged but does not
+/// fail the execution itself.
+fn record_execution_history(app: &AppHandle, record: &ExecutionRecord) {
+    let Some(local_db) = app.try_state::<LocalDbState>() else {
+        log::warn!(
+            "⚠️ Local database not available, execution {} not recorded",
+            record.id
+        );
+        return;
+    };
+
+    let result = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))
+        .and_then(|db| db.save_execution_record(record).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        log::error!("❌ Failed to record execution {}: {}", record.id, e);
+    }
+}
+
+/// Result cache backed by the local database.
+///
+/// Like the execution history it is best-effort: the engine logs cache
+/// failures and runs the UDF.
+struct LocalResultCache {
+    app: AppHandle,
+}
+
+impl LocalResultCache {
+    fn shared(app: &AppHandle) -> Arc<dyn ResultCache> {
+        Arc::new(Self { app: app.clone() })
+    }
+
+    fn with_db<T>(
+        &self,
+        f: impl FnOnce(&LocalDatabase) -> anyhow::Result<T>,
+    ) -> Result<T, UdfError> {
+        let local_db = self
+            .app
+            .try_state::<LocalDbState>()
+            .ok_or_else(|| UdfError::DatabaseError("Local database not available".to_string()))?;
+        let db = local_db.db.lock().map_err(|e| {
+            UdfError::DatabaseError(format!("Failed to lock local database: {}", e))
+        })?;
+        f(&db).map_err(|e| UdfError::DatabaseError(e.to_string()))
+    }
+}
+
+impl ResultCache for LocalResultCache {
+    fn lookup(&self, key: &str) -> Result<Option<CachedResult>, UdfError> {
+        self.with_db(|db| db.get_cached_result(key))
+    }
+
+    fn store(
+        &self,
+        key: &str,
+        record: &ExecutionRecord,
+        output: &UdfOutput,
+    ) -> Result<(), UdfError> {
+        self.with_db(|db| db.save_cached_result(key, record, output))
+    }
+}
+
+/// Remove all cached execution results
+///
+/// Returns the number of removed entries. Executions run afterwards
+/// recompute their outputs.
+#[tauri::command]
+pub fn clear_result_cache(local_db: State<'_, LocalDbState>) -> Result<usize, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    let removed = db
+        .clear_result_cache()
+        .map_err(|e| format!("Failed to clear result cache: {}", e))?;
+
+    info!("🗑️ Cleared {} cached result(s)", removed);
+    Ok(removed)
+}
+
+// ==== Batch Execution ====
+
+/// Request to run a UDF over many wells
+#[derive(Debug, Deserialize)]
+pub struct ExecuteBatchRequest {
+    pub udf_id: String,
+    pub workspace_id: String,
+    /// Wells to run over; every well in the workspace when omitted
+    #[serde(default)]
+    pub well_ids: Option<Vec<String>>,
+    /// Non-curve parameter values shared by all wells
+    pub parameters: HashMap<String, serde_json::Value>,
+    /// How each curve parameter is bound in every well
+    pub bindings: HashMap<String, CurveBinding>,
+    #[serde(default)]
+    pub save_results: bool,
+    /// Run the UDF for every well even if cached results exist
+    #[serde(default)]
+    pub force_recompute: bool,
+}
+
+/// Outcome of one well of a batch (payload of the well-completed event)
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchWellResult {
+    pub batch_id: String,
+    pub well_id: String,
+    pub status: ExecutionStatus,
+    /// Execution record ID, if the UDF ran for this well
+    pub execution_id: Option<String>,
+    /// Curve bound to each curve parameter
+    pub bound_curves: HashMap<String, String>,
+    /// Saved output curve IDs (empty unless saved)
+    pub output_curve_ids: Vec<String>,
+    /// Execution whose cached output was reused, if the UDF did not run
+    pub cached_from: Option<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Outcome of a whole batch (payload of the batch-completed event)
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchExecutionResult {
+    pub batch_id: String,
+    /// False if the batch could not start (e.g., unknown UDF)
+    pub success: bool,
+    pub wells: Vec<BatchWellResult>,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub error: Option<String>,
+}
+
+/// Execute a UDF over many wells in the background
+///
+/// Returns the batch ID immediately. Progress events carry the batch ID as
+/// their execution ID and report progress over all wells; cancelling the
+/// batch ID stops the running well and skips the rest. Each well's result
+/// is emitted as it finishes and recorded in the execution history.
+#[tauri::command]
+pub fn execute_udf_batch(
+    app: AppHandle,
+    request: ExecuteBatchRequest,
+    state: State<'_, Mutex<ComputeState>>,
+    active_executions: State<'_, ActiveExecutions>,
+) -> Result<String, String> {
+    let (engine, db_path, blobs_dir) = {
+        let state = state.lock().expect("Failed to lock state");
+        let engine = state.engine.clone().ok_or("Engine not initialized")?;
+        let db_path = state.db_path().ok_or("Not connected to DataForge")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        (engine, db_path, blobs_dir)
+    };
+
+    let batch_request = BatchRequest {
+        udf_id: request.udf_id,
+        workspace_id: Uuid::parse_str(&request.workspace_id)
+            .map_err(|e| format!("Invalid workspace ID: {}", e))?,
+        target: match request.well_ids {
+            Some(well_ids) => BatchTarget::Wells(
+                well_ids
+                    .iter()
+                    .map(|id| Uuid::parse_str(id).map_err(|e| format!("Invalid well ID: {}", e)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            None => BatchTarget::Workspace,
+        },
+        parameters: request
+            .parameters
+            .into_iter()
+            .map(|(k, v)| (k, json_to_parameter_value(v)))
+            .collect(),
+        bindings: request.bindings,
+    };
+    let save_results = request.save_results;
+    let force_recompute = request.force_recompute;
+
+    let batch_id = Uuid::new_v4().to_string();
+    let cancel_token = Arc::new(CancellationToken::new());
+    let progress_state = emitting_progress_state(&app, &batch_id, &cancel_token);
+    active_executions.register(&batch_id, &cancel_token, &progress_state)?;
+
+    let task_batch_id = batch_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let batch_id = task_batch_id;
+        let options = ExecutionOptions::new()
+            .with_cancellation_token(cancel_token)
+            .with_progress_state(progress_state)
+            .with_result_cache(LocalResultCache::shared(&app))
+            .with_force_recompute(force_recompute);
+
+        let mnemonics = workspace_mnemonics(&app, &batch_request.workspace_id.to_string());
+        let mut wells = Vec::new();
+        let outcome = DataForgeCurveLoader::open(&db_path, blobs_dir.clone())
+            .map_err(|e| e.to_string())
+            .map(|loader| loader.with_mnemonics(mnemonics))
+            .and_then(|loader| {
+                engine
+                    .execute_batch(&batch_request, &loader, &options, |run| {
+                        let well = finish_batch_well(
+                            &app,
+                            &batch_id,
+                            run,
+                            save_results,
+                            &db_path,
+                            &blobs_dir,
+                        );
+                        let _ = app.emit(BATCH_WELL_COMPLETED_EVENT, well.clone());
+                        wells.push(well);
+                    })
+                    .map_err(|e| e.to_string())
+            });
+
+        let count =
+            |status: ExecutionStatus| wells.iter().filter(|w| w.status == status).count();
+        let result = BatchExecutionResult {
+            batch_id: batch_id.clone(),
+            success: outcome.is_ok(),
+            completed: count(ExecutionStatus::Completed),
+            failed: count(ExecutionStatus::Failed),
+            cancelled: count(ExecutionStatus::Cancelled),
+            error: outcome.err(),
+            wells,
+        };
+
+        info!(
+            "📊 Batch {} finished: {} completed, {} failed, {} cancelled",
+            batch_id, result.completed, result.failed, result.cancelled
+        );
+
+        app.state::<ActiveExecutions>().unregister(&batch_id);
+
+        if let Err(e) = app.emit(BATCH_COMPLETED_EVENT, result) {
+            log::error!("❌ Failed to emit completion for batch {}: {}", batch_id, e);
+        }
+    });
+
+    Ok(batch_id)
+}
+
+/// Save (if requested) and record one finished well of a batch.
+fn finish_batch_well(
+    app: &AppHandle,
+    batch_id: &str,
+    run: &mut WellRun,
+    save_results: bool,
+    db_path: &Path,
+    blobs_dir: &Path,
+) -> BatchWellResult {
+    let mut output_curve_ids = Vec::new();
+
+    if let Some(result) = run.result.as_mut() {
+        if let (true, Some(output)) = (save_results, &result.output) {
+            match commit_output(
+                db_path,
+                blobs_dir.to_path_buf(),
+                run.well_id,
+                &output.outputs,
+                &HashMap::new(),
+                &mut result.record,
+            ) {
+                Ok(registered) => {
+                    output_curve_ids = registered
+                        .iter()
+                        .map(|r| r.curve_id.to_string())
+                        .collect();
+                }
+                Err(e) => run.warnings.push(format!("Output was not saved: {}", e)),
+            }
+        }
+
+        record_execution_history(app, &result.record);
+    }
+
+    BatchWellResult {
+        batch_id: batch_id.to_string(),
+        well_id: run.well_id.to_string(),
+        status: run.status,
+        execution_id: run.result.as_ref().map(|r| r.record.id.to_string()),
+        bound_curves: run
+            .bound_curves
+            .iter()
+            .map(|(name, id)| (name.clone(), id.to_string()))
+            .collect(),
+        output_curve_ids,
+        cached_from: run
+            .result
+            .as_ref()
+            .and_then(|r| r.record.cached_from)
+            .map(|id| id.to_string()),
+        warnings: run.warnings.clone(),
+        error: run.error.clone(),
+    }
+}
+
+// ==== Workflows ====
+
+/// Request to create or update a workflow definition
+#[derive(Debug, Deserialize)]
+pub struct SaveWorkflowRequest {
+    /// Existing workflow to update; a new workflow is created when omitted
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub nodes: Vec<WorkflowNode>,
+}
+
+/// Request to run a saved workflow against one well
+#[derive(Debug, Deserialize)]
+pub struct ExecuteWorkflowRequest {
+    pub workflow_id: String,
+    pub well_id: String,
+    pub workspace_id: String,
+    #[serde(default)]
+    pub save_results: bool,
+    /// Run every node even if cached results exist
+    #[serde(default)]
+    pub force_recompute: bool,
+}
+
+/// Outcome of one node of a workflow run (payload of the node-completed event)
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowNodeResult {
+    pub run_id: String,
+    pub node_id: String,
+    pub status: ExecutionStatus,
+    /// Execution record ID, if the UDF ran for this node
+    pub execution_id: Option<String>,
+    /// Output curves; `curve_id` is set once saved
+    pub outputs: Vec<ExecuteUdfOutput>,
+    /// Execution whose cached output was reused, if the UDF did not run
+    pub cached_from: Option<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Outcome of a whole workflow run (payload of the workflow-completed event)
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowExecutionResult {
+    pub run_id: String,
+    pub workflow_id: String,
+    /// False if the workflow could not start (e.g., invalid definition)
+    pub success: bool,
+    pub nodes: Vec<WorkflowNodeResult>,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub error: Option<String>,
+}
+
+/// Create or update a workflow definition
+///
+/// The definition is validated (known UDFs, valid upstream references, no
+/// cycles) before it is stored in the local database.
+#[tauri::command]
+pub fn save_workflow(
+    request: SaveWorkflowRequest,
+    state: State<'_, Mutex<ComputeState>>,
+    local_db: State<'_, LocalDbState>,
+) -> Result<WorkflowDefinition, String> {
+    let engine = {
+        let state = state.lock().expect("Failed to lock state");
+        state.engine.clone().ok_or("Engine not initialized")?
+    };
+
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    let now = chrono::Utc::now();
+    let (id, created_at) = match &request.id {
+        Some(id) => {
+            let existing = db
+                .get_workflow(id)
+                .map_err(|e| format!("Failed to read workflow: {}", e))?
+                .ok_or_else(|| format!("Workflow {} not found", id))?;
+            (existing.id, existing.created_at)
+        }
+        None => (Uuid::new_v4(), now),
+    };
+
+    let workflow = WorkflowDefinition {
+        id,
+        name: request.name,
+        description: request.description,
+        nodes: request.nodes,
+        created_at,
+        updated_at: now,
+    };
+
+    engine
+        .validate_workflow(&workflow)
+        .map_err(|e| e.to_string())?;
+
+    db.save_workflow(&workflow)
+        .map_err(|e| format!("Failed to save workflow: {}", e))?;
+
+    info!("💾 Saved workflow {} ({})", workflow.id, workflow.name);
+    Ok(workflow)
+}
+
+/// List all saved workflow definitions
+#[tauri::command]
+pub fn list_workflows(
+    local_db: State<'_, LocalDbState>,
+) -> Result<Vec<WorkflowDefinition>, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.list_workflows()
+        .map_err(|e| format!("Failed to list workflows: {}", e))
+}
+
+/// Get a saved workflow definition
+#[tauri::command]
+pub fn get_workflow(
+    workflow_id: String,
+    local_db: State<'_, LocalDbState>,
+) -> Result<Option<WorkflowDefinition>, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.get_workflow(&workflow_id)
+        .map_err(|e| format!("Failed to get workflow: {}", e))
+}
+
+/// Delete a saved workflow definition
+#[tauri::command]
+pub fn delete_workflow(
+    workflow_id: String,
+    local_db: State<'_, LocalDbState>,
+) -> Result<bool, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.delete_workflow(&workflow_id)
+        .map_err(|e| format!("Failed to delete workflow: {}", e))
+}
+
+/// Run a saved workflow against one well in the background
+///
+/// Returns the run ID immediately. Progress events carry the run ID as
+/// their execution ID and report progress over all nodes; cancelling the
+/// run ID stops the running node and skips the rest. Each node's result is
+/// emitted as it finishes and recorded in the execution history.
+#[tauri::command]
+pub fn execute_workflow(
+    app: AppHandle,
+    request: ExecuteWorkflowRequest,
+    state: State<'_, Mutex<ComputeState>>,
+    local_db: State<'_, LocalDbState>,
+    active_executions: State<'_, ActiveExecutions>,
+) -> Result<String, String> {
+    let (engine, db_path, blobs_dir) = {
+        let state = state.lock().expect("Failed to lock state");
+        let engine = state.engine.clone().ok_or("Engine not initialized")?;
+        let db_path = state.db_path().ok_or("Not connected to DataForge")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        (engine, db_path, blobs_dir)
+    };
+
+    let workflow = {
+        let db = local_db
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock local database: {}", e))?;
+        db.get_workflow(&request.workflow_id)
+            .map_err(|e| format!("Failed to read workflow: {}", e))?
+            .ok_or_else(|| format!("Workflow {} not found", request.workflow_id))?
+    };
+
+    let well_id = Uuid::parse_str(&request.well_id)
+        .map_err(|e| format!("Invalid well ID: {}", e))?;
+    let workspace_id = Uuid::parse_str(&request.workspace_id)
+        .map_err(|e| format!("Invalid workspace ID: {}", e))?;
+    let save_results = request.save_results;
+    let force_recompute = request.force_recompute;
+
+    let run_uuid = Uuid::new_v4();
+    let run_id = run_uuid.to_string();
+    let cancel_token = Arc::new(CancellationToken::new());
+    let progress_state = emitting_progress_state(&app, &run_id, &cancel_token);
+    active_executions.register(&run_id, &cancel_token, &progress_state)?;
+
+    let task_run_id = run_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let run_id = task_run_id;
+        let options = ExecutionOptions::new()
+            .with_cancellation_token(cancel_token)
+            .with_progress_state(progress_state)
+            .with_execution_id(run_uuid)
+            .with_result_cache(LocalResultCache::shared(&app))
+            .with_force_recompute(force_recompute);
+
+        // In-memory output curve IDs mapped to the IDs they were saved under
+        let mut saved_curves = HashMap::new();
+        let mut nodes = Vec::new();
+        let mnemonics = workspace_mnemonics(&app, &workspace_id.to_string());
+        let outcome = DataForgeCurveLoader::open(&db_path, blobs_dir.clone())
+            .map_err(|e| e.to_string())
+            .map(|loader| loader.with_mnemonics(mnemonics))
+            .and_then(|loader| {
+                engine
+                    .execute_workflow(&workflow, well_id, workspace_id, &loader, &options, |run| {
+                        let node = finish_workflow_node(
+                            &app,
+                            &run_id,
+                            run,
+                            well_id,
+                            save_results,
+                            &mut saved_curves,
+                            &db_path,
+                            &blobs_dir,
+                        );
+                        let _ = app.emit(WORKFLOW_NODE_COMPLETED_EVENT, node.clone());
+                        nodes.push(node);
+                    })
+                    .map_err(|e| e.to_string())
+            });
+
+        let count = |status: ExecutionStatus| nodes.iter().filter(|n| n.status == status).count();
+        let result = WorkflowExecutionResult {
+            run_id: run_id.clone(),
+            workflow_id: workflow.id.to_string(),
+            success: outcome.is_ok(),
+            completed: count(ExecutionStatus::Completed),
+            failed: count(ExecutionStatus::Failed),
+            cancelled: count(ExecutionStatus::Cancelled),
+            error: outcome.err(),
+            nodes,
+        };
+
+        info!(
+            "📊 Workflow run {} finished: {} completed, {} failed, {} cancelled",
+            run_id, result.completed, result.failed, result.cancelled
+        );
+
+        app.state::<ActiveExecutions>().unregister(&run_id);
+
+        if let Err(e) = app.emit(WORKFLOW_COMPLETED_EVENT, result) {
+            log::error!(
+                "❌ Failed to emit completion for workflow run {}: {}",
+                run_id,
+                e
+            );
+        }
+    });
+
+    Ok(run_id)
+}
+
+/// Save (if requested) and record one finished node of a workflow run.
+///
+/// Nodes finish in topological order, so by the time a node is saved its
+/// upstream outputs have been saved too and its inputs can be pointed at
+/// the stored curves.
+#[allow(clippy::too_many_arguments)]
+fn finish_workflow_node(
+    app: &AppHandle,
+    run_id: &str,
+    run: &mut NodeRun,
+    well_id: Uuid,
+    save_results: bool,
+    saved_curves: &mut HashMap<Uuid, Uuid>,
+    db_path: &Path,
+    blobs_dir: &Path,
+) -> WorkflowNodeResult {
+    let mut outputs = Vec::new();
+    let mut warnings = Vec::new();
+
+    if let Some(result) = run.result.as_mut() {
+        remap_saved_inputs(&mut result.record, saved_curves);
+
+        if let Some(output) = &result.output {
+            warnings.extend(output.warnings.iter().cloned());
+            outputs = output
+                .outputs
+                .iter()
+                .map(|named| ExecuteUdfOutput {
+                    name: named.name.clone(),
+                    mnemonic: named.curve_data.mnemonic.clone(),
+                    unit: named.curve_data.unit.clone(),
+                    curve_type: named.curve_data.curve_type,
+                    curve_id: None,
+                    data: curve_data_points(&named.curve_data),
+                })
+                .collect();
+
+            if save_results {
+                match commit_output(
+                    db_path,
+                    blobs_dir.to_path_buf(),
+                    well_id,
+                    &output.outputs,
+                    &HashMap::new(),
+                    &mut result.record,
+                ) {
+                    Ok(registered) => {
+                        for (entry, registered) in outputs.iter_mut().zip(registered) {
+                            if let Some(in_memory) = run.output_curves.get(&registered.name) {
+                                saved_curves.insert(*in_memory, registered.curve_id);
+                            }
+                            entry.curve_id = Some(registered.curve_id.to_string());
+                        }
+                    }
+                    Err(e) => warnings.push(format!("Output was not saved: {}", e)),
+                }
+            }
+        }
+
+        record_execution_history(app, &result.record);
+    }
+
+    WorkflowNodeResult {
+        run_id: run_id.to_string(),
+        node_id: run.node_id.clone(),
+        status: run.status,
+        execution_id: run.result.as_ref().map(|r| r.record.id.to_string()),
+        outputs,
+        cached_from: run
+            .result
+            .as_ref()
+            .and_then(|r| r.record.cached_from)
+            .map(|id| id.to_string()),
+        warnings,
+        error: run.error.clone(),
     }
 }
 
 /// Validate UDF parameters without executing
+///
+/// When connected to DataForge, the selected curves are checked against
+/// their parameters from curve metadata as well (preflight). Curves are
+/// typed with the workspace's mnemonic aliases when a workspace is given.
 #[tauri::command]
 pub fn validate_udf_parameters(
+    app: AppHandle,
     udf_id: String,
     parameters: HashMap<String, serde_json::Value>,
+    workspace_id: Option<String>,
     state: State<'_, Mutex<ComputeState>>,
 ) -> Result<Vec<serde_json::Value>, String> {
     let state = state.lock().expect("Failed to lock state");
@@ -939,9 +1836,18 @@ pub fn validate_udf_parameters(
         .map(|(k, v)| (k, json_to_parameter_value(v)))
         .collect();
 
-    let errors = engine
-        .validate_only(&udf_id, &params)
-        .map_err(|e| e.to_string())?;
+    let errors = match (&state.db, state.db_path(), state.blobs_dir()) {
+        (Some(_), Some(db_path), Some(blobs_dir)) => {
+            let mut loader =
+                DataForgeCurveLoader::open(&db_path, blobs_dir).map_err(|e| e.to_string())?;
+            if let Some(workspace_id) = &workspace_id {
+                loader = loader.with_mnemonics(workspace_mnemonics(&app, workspace_id));
+            }
+            engine.preflight(&udf_id, &params, &loader)
+        }
+        _ => engine.validate_only(&udf_id, &params),
+    }
+    .map_err(|e| e.to_string())?;
 
     Ok(errors
         .into_iter()
@@ -977,10 +1883,124 @@ fn json_to_parameter_value(v: serde_json::Value) -> ParameterValue {
                 ParameterValue::String(s)
             }
         }
+        // Curve lists, depth ranges, zones and quantities
+        v @ (serde_json::Value::Array(_) | serde_json::Value::Object(_)) => {
+            serde_json::from_value(v).unwrap_or(ParameterValue::Null)
+        }
         _ => ParameterValue::Null,
     }
 }
 
+// ==== Mnemonic Aliases ====
+
+/// A rule of a workspace's effective mnemonic dictionary
+#[derive(Debug, Clone, Serialize)]
+pub struct MnemonicAliasInfo {
+    #[serde(flatten)]
+    pub rule: MnemonicRule,
+    /// Whether the rule is a workspace override rather than a default
+    pub workspace: bool,
+}
+
+/// Mnemonic dictionary of a workspace: the defaults with the workspace's
+/// alias rules from the local database.
+///
+/// Like the execution history it is best-effort: if the rules cannot be
+/// read the failure is logged and the defaults apply.
+fn workspace_mnemonics(app: &AppHandle, workspace_id: &str) -> MnemonicDictionary {
+    let Some(local_db) = app.try_state::<LocalDbState>() else {
+        return MnemonicDictionary::defaults();
+    };
+
+    let result = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))
+        .and_then(|db| {
+            db.list_mnemonic_aliases(workspace_id)
+                .map_err(|e| e.to_string())
+        })
+        .and_then(|rules| {
+            MnemonicDictionary::defaults()
+                .with_overrides(rules)
+                .map_err(|e| e.to_string())
+        });
+
+    result.unwrap_or_else(|e| {
+        log::error!(
+            "❌ Failed to load mnemonic aliases of workspace {}: {}",
+            workspace_id,
+            e
+        );
+        MnemonicDictionary::defaults()
+    })
+}
+
+/// Get the effective mnemonic dictionary of a workspace
+///
+/// Rules are listed in match order: the first rule matching a mnemonic
+/// decides the curve type of curves without a property.
+#[tauri::command]
+pub fn get_mnemonic_dictionary(
+    app: AppHandle,
+    workspace_id: String,
+) -> Result<Vec<MnemonicAliasInfo>, String> {
+    let dictionary = workspace_mnemonics(&app, &workspace_id);
+    Ok(dictionary
+        .rules()
+        .map(|(rule, workspace)| MnemonicAliasInfo {
+            rule: rule.clone(),
+            workspace,
+        })
+        .collect())
+}
+
+/// Create or update a workspace's mnemonic alias rule
+///
+/// Regex rules are compiled before they are stored, so invalid patterns
+/// are rejected here instead of being skipped at execution time.
+#[tauri::command]
+pub fn save_mnemonic_alias(
+    workspace_id: String,
+    rule: MnemonicRule,
+    local_db: State<'_, LocalDbState>,
+) -> Result<(), String> {
+    MnemonicDictionary::new(vec![rule.clone()]).map_err(|e| e.to_string())?;
+
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.save_mnemonic_alias(&workspace_id, &rule)
+        .map_err(|e| format!("Failed to save mnemonic alias: {}", e))?;
+
+    info!(
+        "💾 Saved mnemonic alias {} -> {} for workspace {}",
+        rule.pattern,
+        rule.curve_type.display_name(),
+        workspace_id
+    );
+    Ok(())
+}
+
+/// Delete a workspace's mnemonic alias rule
+#[tauri::command]
+pub fn delete_mnemonic_alias(
+    workspace_id: String,
+    kind: MatchKind,
+    pattern: String,
+    local_db: State<'_, LocalDbState>,
+) -> Result<bool, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.delete_mnemonic_alias(&workspace_id, kind, &pattern)
+        .map_err(|e| format!("Failed to delete mnemonic alias: {}", e))
+}
+
 // ==== Provenance Commands ====
 
 /// Response type for curve provenance query
@@ -991,8 +2011,12 @@ pub struct CurveProvenanceResponse {
     pub udf_version: String,
     pub inputs: Vec<InputReferenceResponse>,
     pub parameters: serde_json::Value,
+    /// Numeric parameter values converted to another unit before execution
+    pub parameter_conversions: Vec<ParameterConversion>,
     pub output_curve_id: Option<String>,
     pub output_parquet_hash: Option<String>,
+    /// All saved outputs of the execution
+    pub outputs: Vec<OutputReference>,
     pub started_at: String,
     pub completed_at: Option<String>,
     pub compute_app_version: String,
@@ -1006,13 +2030,19 @@ pub struct InputReferenceResponse {
     pub mnemonic: String,
     pub parquet_hash: String,
     pub version: i64,
+    pub unit_conversion: Option<UnitConversion>,
+    pub alignment: Option<DepthAlignment>,
 }
 
 /// Get provenance information for a derived curve
+///
+/// The execution record is taken from the local execution history, falling
+/// back to DataForge's execution_records table for curves saved elsewhere.
 #[tauri::command]
 pub fn get_curve_provenance(
     curve_id: String,
     state: State<'_, Mutex<ComputeState>>,
+    local_db: State<'_, LocalDbState>,
 ) -> Result<Option<CurveProvenanceResponse>, String> {
     let state = state.lock().expect("Failed to lock state");
     let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
@@ -1036,43 +2066,409 @@ pub fn get_curve_provenance(
     let execution_id = source_execution_id
         .ok_or("Derived curve has no execution ID")?;
 
-    // Query the execution record
-    let record: CurveProvenanceResponse = db
+    // Prefer the local execution history
+    let local_record = {
+        let local_db = local_db
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock local database: {}", e))?;
+        local_db
+            .get_execution_record(&execution_id)
+            .map_err(|e| format!("Failed to read execution history: {}", e))?
+    };
+
+    if let Some(record) = local_record {
+        return Ok(Some(CurveProvenanceResponse {
+            id: record.id.to_string(),
+            udf_id: record.udf_id,
+            udf_version: record.udf_version,
+            inputs: record
+                .inputs
+                .iter()
+                .map(|input| input_reference_response(db, input))
+                .collect(),
+            parameters: record.parameters,
+            parameter_conversions: record.parameter_conversions,
+            output_curve_id: record.output_curve_id.map(|id| id.to_string()),
+            output_parquet_hash: record.output_parquet_hash,
+            outputs: record.outputs,
+            started_at: record.started_at.to_rfc3339(),
+            completed_at: record.completed_at.map(|t| t.to_rfc3339()),
+            compute_app_version: record.compute_app_version,
+            status: record.status.as_str().to_string(),
+            error_message: record.error_message,
+        }));
+    }
+
+    // Fall back to the execution record stored in DataForge. Tables created
+    // before multi-output support have no outputs column, nor those created
+    // before numeric parameters were converted a parameter_conversions one.
+    let has_outputs = has_column(db, "execution_records", "outputs").unwrap_or(false);
+    let outputs_column = if has_outputs { "outputs" } else { "NULL" };
+    let has_conversions =
+        has_column(db, "execution_records", "parameter_conversions").unwrap_or(false);
+    let conversions_column = if has_conversions {
+        "parameter_conversions"
+    } else {
+        "NULL"
+    };
+
+    let (mut record, inputs_json, outputs_json, conversions_json): (
+        CurveProvenanceResponse,
+        String,
+        Option<String>,
+        Option<String>,
+    ) = db
         .query_row(
-            r#"
-            SELECT id, udf_id, udf_version, inputs, parameters,
-                   output_curve_id, output_parquet_hash,
-                   started_at, completed_at, compute_app_version,
-                   status, error_message
-            FROM execution_records
-            WHERE id = ?1
-            "#,
+            &format!(
+                r#"
+                SELECT id, udf_id, udf_version, inputs, parameters,
+                       output_curve_id, output_parquet_hash,
+                       started_at, completed_at, compute_app_version,
+                       status, error_message, {}, {}
+                FROM execution_records
+                WHERE id = ?1
+                "#,
+                outputs_column, conversions_column
+            ),
             [&execution_id],
             |row| {
-                let inputs_json: String = row.get(3)?;
                 let params_json: String = row.get(4)?;
 
-                Ok(CurveProvenanceResponse {
-                    id: row.get(0)?,
-                    udf_id: row.get(1)?,
-                    udf_version: row.get(2)?,
-                    inputs: serde_json::from_str(&inputs_json).unwrap_or_default(),
-                    parameters: serde_json::from_str(&params_json).unwrap_or(serde_json::json!({})),
-                    output_curve_id: row.get(5)?,
-                    output_parquet_hash: row.get(6)?,
-                    started_at: row.get(7)?,
-                    completed_at: row.get(8)?,
-                    compute_app_version: row.get(9)?,
-                    status: row.get(10)?,
-                    error_message: row.get(11)?,
-                })
+                Ok((
+                    CurveProvenanceResponse {
+                        id: row.get(0)?,
+                        udf_id: row.get(1)?,
+                        udf_version: row.get(2)?,
+                        inputs: Vec::new(),
+                        parameters: serde_json::from_str(&params_json)
+                            .unwrap_or(serde_json::json!({})),
+                        parameter_conversions: Vec::new(),
+                        output_curve_id: row.get(5)?,
+                        output_parquet_hash: row.get(6)?,
+                        outputs: Vec::new(),
+                        started_at: row.get(7)?,
+                        completed_at: row.get(8)?,
+                        compute_app_version: row.get(9)?,
+                        status: row.get(10)?,
+                        error_message: row.get(11)?,
+                    },
+                    row.get(3)?,
+                    row.get(12)?,
+                    row.get(13)?,
+                ))
             },
         )
         .map_err(|e| format!("Execution record not found: {}", e))?;
 
+    let inputs: Vec<InputReference> = serde_json::from_str(&inputs_json).unwrap_or_default();
+    record.inputs = inputs
+        .iter()
+        .map(|input| input_reference_response(db, input))
+        .collect();
+    record.outputs = outputs_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    record.parameter_conversions = conversions_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
     Ok(Some(record))
 }
 
+/// Resolve an input reference for display, looking up its mnemonic
+fn input_reference_response(db: &Connection, input: &InputReference) -> InputReferenceResponse {
+    let mnemonic: String = db
+        .query_row(
+            "SELECT mnemonic FROM curves WHERE id = ?1",
+            [input.curve_id.to_string()],
+            |row| row.get(0),
+        )
+        .unwrap_or_default();
+
+    InputReferenceResponse {
+        curve_id: input.curve_id.to_string(),
+        mnemonic,
+        parquet_hash: input.parquet_hash.clone(),
+        version: input.version,
+        unit_conversion: input.unit_conversion.clone(),
+        alignment: input.alignment.clone(),
+    }
+}
+
+/// Get the lineage graph of a curve
+///
+/// Upstream lineage follows each derived curve back through the execution
+/// that produced it to the curves loaded from files; downstream lineage
+/// collects every derived curve built, directly or indirectly, from the
+/// curve. `max_depth` limits how many executions away from the curve the
+/// graph reaches.
+#[tauri::command]
+pub fn get_curve_lineage(
+    curve_id: String,
+    direction: LineageDirection,
+    max_depth: Option<usize>,
+    state: State<'_, Mutex<ComputeState>>,
+) -> Result<LineageGraph, String> {
+    let curve_id = Uuid::parse_str(&curve_id).map_err(|e| format!("Invalid curve ID: {}", e))?;
+
+    let state = state.lock().expect("Failed to lock state");
+    let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+
+    let graph = lineage_graph(db, curve_id, direction, max_depth).map_err(|e| e.to_string())?;
+    info!(
+        "🧬 {:?} lineage of {}: {} curve(s), {} execution(s)",
+        direction,
+        curve_id,
+        graph.curves.len(),
+        graph.executions.len()
+    );
+    Ok(graph)
+}
+
+/// Export provenance as a W3C PROV-JSON document
+///
+/// Covers a single curve and the execution that produced it, the full
+/// lineage of a curve, or every curve in a workspace.
+#[tauri::command]
+pub fn export_provenance(
+    scope: ProvExportScope,
+    state: State<'_, Mutex<ComputeState>>,
+) -> Result<serde_json::Value, String> {
+    let state = state.lock().expect("Failed to lock state");
+    let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+
+    info!("📤 Exporting provenance for {:?}", scope);
+    export_prov(db, &scope).map_err(|e| e.to_string())
+}
+
+/// Check that an execution is reproducible
+///
+/// Re-runs the UDF on the exact input data recorded by the execution and
+/// compares the content hash of each saved output with the recorded one,
+/// summarizing per-sample differences where they differ. The record is
+/// looked up like in `get_curve_provenance`. Nothing is saved.
+#[tauri::command]
+pub async fn verify_execution(
+    app: AppHandle,
+    execution_id: String,
+    state: State<'_, Mutex<ComputeState>>,
+    local_db: State<'_, LocalDbState>,
+) -> Result<VerificationReport, String> {
+    Uuid::parse_str(&execution_id).map_err(|e| format!("Invalid execution ID: {}", e))?;
+
+    let (engine, db_path, blobs_dir, source, well_id, workspace_id) = {
+        let state = state.lock().expect("Failed to lock state");
+        let engine = state.engine.clone().ok_or("Engine not initialized")?;
+        let db_path = state.db_path().ok_or("Not connected to DataForge")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+
+        let local_record = local_db
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock local database: {}", e))?
+            .get_execution_record(&execution_id)
+            .map_err(|e| format!("Failed to read execution history: {}", e))?;
+
+        let (source, well_id, workspace_id) = match local_record {
+            Some(record) => (
+                SourceExecution::from_record(&record),
+                record.well_id,
+                record.workspace_id,
+            ),
+            None => {
+                let source = load_source_execution(db, &execution_id)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("Execution record {} not found", execution_id))?;
+
+                // DataForge does not record the well; take it from a saved output
+                let curve_id = source
+                    .outputs
+                    .first()
+                    .map(|o| o.curve_id)
+                    .or(source.output_curve_id)
+                    .ok_or_else(|| format!("Execution {} has no saved output", execution_id))?;
+                let (well_id, workspace_id): (String, String) = db
+                    .query_row(
+                        r#"SELECT c.well_id, w.workspace_id
+                           FROM curves c JOIN wells w ON c.well_id = w.id
+                           WHERE c.id = ?1"#,
+                        [curve_id.to_string()],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .map_err(|e| format!("Output curve not found: {}", e))?;
+                let parse = |id: &str| Uuid::parse_str(id).map_err(|e| e.to_string());
+                (source, parse(&well_id)?, parse(&workspace_id)?)
+            }
+        };
+        (engine, db_path, blobs_dir, source, well_id, workspace_id)
+    };
+
+    let parameters: HashMap<String, ParameterValue> = match &source.parameters {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.clone(), json_to_parameter_value(v.clone())))
+            .collect(),
+        _ => HashMap::new(),
+    };
+
+    info!(
+        "🔬 Verifying execution {} of {}",
+        source.execution_id, source.udf_id
+    );
+
+    let mnemonics = workspace_mnemonics(&app, &workspace_id.to_string());
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let loader = DataForgeCurveLoader::open(&db_path, blobs_dir)?.with_mnemonics(mnemonics);
+        verify::verify_execution(&engine, &source, well_id, workspace_id, parameters, &loader)
+    })
+    .await
+    .map_err(|e| format!("Verification task failed: {}", e))?
+    .map_err(|e| e.to_string())?;
+
+    if report.reproducible {
+        info!("✅ Execution {} is reproducible", report.execution_id);
+    } else {
+        log::warn!("⚠️ Execution {} did not reproduce", report.execution_id);
+    }
+    Ok(report)
+}
+
+// ==== Stale Derived Curves ====
+
+/// Check derived curves for inputs that changed since they were computed
+///
+/// Checks one well if `well_id` is given, otherwise every well of the
+/// workspace. All derived curves are returned; `is_stale` marks those whose
+/// recorded input versions or hashes no longer match the current curves.
+#[tauri::command]
+pub fn check_stale_curves(
+    workspace_id: String,
+    well_id: Option<String>,
+    state: State<'_, Mutex<ComputeState>>,
+) -> Result<Vec<DerivedCurveStatus>, String> {
+    let scope = match well_id {
+        Some(well_id) => StalenessScope::Well(
+            Uuid::parse_str(&well_id).map_err(|e| format!("Invalid well ID: {}", e))?,
+        ),
+        None => StalenessScope::Workspace(
+            Uuid::parse_str(&workspace_id).map_err(|e| format!("Invalid workspace ID: {}", e))?,
+        ),
+    };
+
+    let state = state.lock().expect("Failed to lock state");
+    let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+
+    let statuses = check_staleness(db, scope).map_err(|e| e.to_string())?;
+    info!(
+        "🔍 {} of {} derived curve(s) are stale",
+        statuses.iter().filter(|s| s.is_stale).count(),
+        statuses.len()
+    );
+    Ok(statuses)
+}
+
+/// Recompute a derived curve from its recorded execution
+///
+/// Re-runs the recorded UDF with the recorded parameters against the
+/// current versions of its input curves, in the background like
+/// `execute_udf`. Each output of the execution is saved as a new version of
+/// the derived curve it was saved as before, so curve IDs stay the same.
+/// Depth range and zone parameters keep the depths they were recorded with.
+/// Returns the new execution ID.
+#[tauri::command]
+pub fn recompute_derived_curve(
+    app: AppHandle,
+    curve_id: String,
+    state: State<'_, Mutex<ComputeState>>,
+    active_executions: State<'_, ActiveExecutions>,
+) -> Result<String, String> {
+    let curve_id = Uuid::parse_str(&curve_id).map_err(|e| format!("Invalid curve ID: {}", e))?;
+
+    let (engine, db_path, blobs_dir, target) = {
+        let state = state.lock().expect("Failed to lock state");
+        let engine = state.engine.clone().ok_or("Engine not initialized")?;
+        let db_path = state.db_path().ok_or("Not connected to DataForge")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        let db = state.db.as_ref().ok_or("Not connected to DataForge")?;
+        let target = recompute_target(db, curve_id).map_err(|e| e.to_string())?;
+        (engine, db_path, blobs_dir, target)
+    };
+
+    let source = target.source;
+    let mut replace_curves = target.output_curves;
+
+    // Records from before multi-output support only name the first output
+    if replace_curves.is_empty() {
+        if let Some(output_curve_id) = source.output_curve_id {
+            let metadata = engine
+                .get_udf_metadata(&source.udf_id)
+                .map_err(|e| e.to_string())?;
+            if let Some(primary) = metadata.outputs.first() {
+                replace_curves.insert(primary.name.clone(), output_curve_id);
+            }
+        }
+    }
+
+    let parameters = match source.parameters {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        _ => HashMap::new(),
+    };
+
+    info!(
+        "🔁 Recomputing derived curve {} from execution {}",
+        curve_id, source.execution_id
+    );
+
+    let request = ExecuteUdfRequest {
+        udf_id: source.udf_id,
+        well_id: target.well_id.to_string(),
+        workspace_id: target.workspace_id.to_string(),
+        parameters,
+        save_result: true,
+        force_recompute: false,
+        replace_curves,
+        depth_intervals: source.depth_intervals,
+    };
+    spawn_execution(app, request, engine, db_path, blobs_dir, &active_executions)
+}
+
+// ==== Execution History Commands ====
+
+/// Query the local execution history
+///
+/// Records are returned newest first and can be filtered by UDF, well,
+/// workspace, status and start date, and paged with limit/offset.
+#[tauri::command]
+pub fn list_execution_history(
+    filter: Option<ExecutionHistoryFilter>,
+    local_db: State<'_, LocalDbState>,
+) -> Result<ExecutionHistoryPage, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.list_execution_history(&filter.unwrap_or_default())
+        .map_err(|e| format!("Failed to list execution history: {}", e))
+}
+
+/// Get a single execution record from the local execution history
+#[tauri::command]
+pub fn get_execution_record(
+    execution_id: String,
+    local_db: State<'_, LocalDbState>,
+) -> Result<Option<ExecutionRecord>, String> {
+    let db = local_db
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock local database: {}", e))?;
+
+    db.get_execution_record(&execution_id)
+        .map_err(|e| format!("Failed to get execution record: {}", e))
+}
+
 // ==== Async Execution Commands ====
 
 /// Response for execution progress query
@@ -1084,6 +2480,13 @@ pub struct ExecutionProgress {
     pub is_cancelled: bool,
 }
 
+/// Payload of the execution warning event
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionWarning {
+    pub execution_id: String,
+    pub warning: String,
+}
+
 /// Get the progress of an active execution
 #[tauri::command]
 pub fn get_execution_progress(
@@ -1152,177 +2555,132 @@ pub fn list_active_executions(
 
 // ==== Save Output Curve Command ====
 
-/// Request to save an output curve to DataForge
+/// Request to save the output curves of an execution to DataForge
 #[derive(Debug, Clone, Deserialize)]
 pub struct SaveOutputCurveRequest {
     pub execution_id: String,
     pub well_id: String,
     pub workspace_id: String,
+    pub outputs: Vec<SaveOutputCurveItem>,
+}
+
+/// One output curve to save
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveOutputCurveItem {
+    /// Output name as declared by the UDF
+    pub name: String,
     pub mnemonic: Option<String>,
+    #[serde(default)]
+    pub unit: Option<String>,
     pub output_data: Vec<CurveDataPoint>,
 }
 
-/// Response from saving an output curve
+/// Response from saving output curves
+///
+/// `curve_id` is the first saved curve; `curve_ids` lists all of them in
+/// request order.
 #[derive(Debug, Clone, Serialize)]
 pub struct SaveOutputCurveResponse {
     pub success: bool,
     pub curve_id: Option<String>,
+    pub curve_ids: Vec<String>,
     pub error: Option<String>,
 }
 
-/// Save the output of a UDF execution as a new curve in DataForge
+/// Save the outputs of a UDF execution as new curves in DataForge
+///
+/// The execution is looked up in the local execution history, and the blobs,
+/// curves and execution record are committed to DataForge atomically.
 #[tauri::command]
 pub fn save_output_curve(
     request: SaveOutputCurveRequest,
     state: State<'_, Mutex<ComputeState>>,
+    local_db: State<'_, LocalDbState>,
 ) -> Result<SaveOutputCurveResponse, String> {
-    use arrow::array::Float64Array;
-    use arrow::datatypes::{DataType, Field, Schema};
-    use arrow::record_batch::RecordBatch;
-    use parquet::arrow::ArrowWriter;
-    use parquet::basic::Compression;
-    use parquet::file::properties::WriterProperties;
-    use sha2::{Digest, Sha256};
-    use std::fs;
-
-    let state = state.lock().expect("Failed to lock state");
-    let data_dir = state
-        .dataforge_data_dir
-        .as_ref()
-        .ok_or("DataForge data directory not set")?;
+    let (db_path, blobs_dir) = {
+        let state = state.lock().expect("Failed to lock state");
+        let db_path = state.db_path().ok_or("DataForge data directory not set")?;
+        let blobs_dir = state.blobs_dir().ok_or("Blobs directory not set")?;
+        (db_path, blobs_dir)
+    };
 
-    // We need to open a read-write connection for saving
-    let db_path = data_dir.join("dataforge.db");
-    let db = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open database for writing: {}", e))?;
+    let well_id = Uuid::parse_str(&request.well_id)
+        .map_err(|e| format!("Invalid well ID: {}", e))?;
 
-    // Ensure the curves table has the required columns for derived curves
-    crate::compute::output_writer::ensure_derived_curve_columns(&db)
-        .map_err(|e| format!("Failed to ensure derived curve columns: {}", e))?;
-
-    let mnemonic = request
-        .mnemonic
-        .unwrap_or_else(|| format!("DERIVED_{}", &request.execution_id[..8]));
-
-    // Generate curve ID
-    let curve_id = Uuid::new_v4();
-
-    // Create parquet data
-    let depths: Vec<f64> = request.output_data.iter().map(|p| p.depth).collect();
-    let values: Vec<Option<f64>> = request.output_data.iter().map(|p| p.value).collect();
-
-    // Build Arrow arrays
-    let depth_array = Float64Array::from(depths.clone());
-    let value_array = Float64Array::from(values.clone());
-
-    // Schema: DEPTH, {mnemonic}
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("DEPTH", DataType::Float64, false),
-        Field::new(&mnemonic, DataType::Float64, true),
-    ]));
-
-    let batch = RecordBatch::try_new(
-        schema.clone(),
-        vec![Arc::new(depth_array), Arc::new(value_array)],
-    )
-    .map_err(|e| format!("Failed to create record batch: {}", e))?;
-
-    // Write parquet to buffer
-    let mut buf = Vec::new();
-    let props = WriterProperties::builder()
-        .set_compression(Compression::SNAPPY)
-        .build();
-    let mut writer = ArrowWriter::try_new(&mut buf, schema, Some(props))
-        .map_err(|e| format!("Failed to create parquet writer: {}", e))?;
-    writer
-        .write(&batch)
-        .map_err(|e| format!("Failed to write parquet: {}", e))?;
-    writer
-        .close()
-        .map_err(|e| format!("Failed to close parquet writer: {}", e))?;
-
-    // Calculate hash
-    let mut hasher = Sha256::new();
-    hasher.update(&buf);
-    let hash = format!("{:x}", hasher.finalize());
-
-    // Store blob
-    let blobs_dir = data_dir.join("blobs");
-    let blob_path = blobs_dir
-        .join(&hash[0..2])
-        .join(&hash[2..4])
-        .join(format!("{}.parquet", &hash));
-
-    fs::create_dir_all(blob_path.parent().unwrap())
-        .map_err(|e| format!("Failed to create blob directory: {}", e))?;
-    fs::write(&blob_path, &buf).map_err(|e| format!("Failed to write blob: {}", e))?;
-
-    // Register blob
-    db.execute(
-        "INSERT OR IGNORE INTO blob_registry (hash, size_bytes) VALUES (?1, ?2)",
-        rusqlite::params![hash, buf.len() as i64],
-    )
-    .map_err(|e| format!("Failed to register blob: {}", e))?;
-
-    // Calculate statistics
-    let valid_values: Vec<f64> = values.iter().filter_map(|v| *v).collect();
-    let (min_val, max_val, mean_val) = if valid_values.is_empty() {
-        (None, None, None)
-    } else {
-        let min = valid_values.iter().cloned().fold(f64::INFINITY, f64::min);
-        let max = valid_values
-            .iter()
-            .cloned()
-            .fold(f64::NEG_INFINITY, f64::max);
-        let mean = valid_values.iter().sum::<f64>() / valid_values.len() as f64;
-        (Some(min), Some(max), Some(mean))
+    let mut record = {
+        let local_db = local_db
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock local database: {}", e))?;
+        local_db
+            .get_execution_record(&request.execution_id)
+            .map_err(|e| format!("Failed to read execution history: {}", e))?
+            .ok_or_else(|| format!("Execution {} not found in history", request.execution_id))?
     };
 
-    let null_count = values.iter().filter(|v| v.is_none()).count() as i64;
-    let min_depth = depths.iter().cloned().fold(f64::INFINITY, f64::min);
-    let max_depth = depths.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-
-    // Insert curve record
-    db.execute(
-        r#"
-        INSERT INTO curves (
-            id, well_id, mnemonic,
-            native_top_depth, native_bottom_depth, native_sample_count,
-            min_value, max_value, mean_value, null_count,
-            native_parquet_hash, quality_flag, is_derived, source_execution_id,
-            created_by
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
-        "#,
-        rusqlite::params![
-            curve_id.to_string(),
-            request.well_id,
-            mnemonic,
-            min_depth,
-            max_depth,
-            depths.len() as i64,
-            min_val,
-            max_val,
-            mean_val,
-            null_count,
-            hash,
-            "derived",
-            true,
-            request.execution_id,
-            "DataForge Compute"
-        ],
-    )
-    .map_err(|e| format!("Failed to insert curve: {}", e))?;
+    if record.status != ExecutionStatus::Completed {
+        return Err(format!(
+            "Execution {} did not complete and cannot be saved",
+            request.execution_id
+        ));
+    }
 
-    info!(
-        "💾 Saved derived curve {} ({}) with {} points",
-        curve_id,
-        mnemonic,
-        depths.len()
-    );
+    if request.outputs.is_empty() {
+        return Err("No outputs to save".to_string());
+    }
+
+    let outputs: Vec<NamedOutput> = request
+        .outputs
+        .iter()
+        .map(|item| NamedOutput {
+            name: item.name.clone(),
+            curve_data: OutputCurveData {
+                mnemonic: item.mnemonic.clone().unwrap_or_else(|| {
+                    format!("DERIVED_{}_{}", &request.execution_id[..8], item.name)
+                }),
+                curve_type: CurveDataType::Computed,
+                unit: item.unit.clone().unwrap_or_default(),
+                depths: item.output_data.iter().map(|p| p.depth).collect(),
+                values: item.output_data.iter().map(|p| p.value).collect(),
+                description: None,
+            },
+        })
+        .collect();
+
+    let curve_ids: Vec<String> = match commit_output(
+        &db_path,
+        blobs_dir,
+        well_id,
+        &outputs,
+        &HashMap::new(),
+        &mut record,
+    ) {
+        Ok(registered) => registered.iter().map(|r| r.curve_id.to_string()).collect(),
+        Err(e) => {
+            return Ok(SaveOutputCurveResponse {
+                success: false,
+                curve_id: None,
+                curve_ids: Vec::new(),
+                error: Some(e),
+            })
+        }
+    };
+
+    // Keep the local history in step with what was committed
+    {
+        let local_db = local_db
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock local database: {}", e))?;
+        if let Err(e) = local_db.save_execution_record(&record) {
+            log::error!("❌ Failed to update execution {}: {}", record.id, e);
+        }
+    }
 
     Ok(SaveOutputCurveResponse {
         success: true,
-        curve_id: Some(curve_id.to_string()),
+        curve_id: curve_ids.first().cloned(),
+        curve_ids,
         error: None,
     })
 }
@@ -0,0 +1,575 @@
+//! Depth alignment of input curves.
+//!
+//! UDFs combine their input curves sample by sample, so the curves must
+//! share one depth grid. UDFs that take more than one curve get parameters
+//! added by the engine that pick how curves on other grids are resampled:
+//!
+//! - `none`: the curves must already share their depths
+//! - `first`: curves are resampled to the depths of the first curve
+//! - `reference`: curves are resampled to the depths of a chosen curve
+//!   parameter
+//! - `grid`: curves are resampled to a regular grid covering all of them,
+//!   at `alignment_step` or the finest step of the curves
+//!
+//! Values between samples are interpolated linearly, taken from the
+//! nearest sample, or left null unless a sample is at the same depth
+//! (`none`). Resampled inputs record their alignment in the provenance of
+//! the execution.
+
+use crate::compute::error::UdfError;
+use crate::compute::nulls::CurveInput;
+use crate::compute::parameters::{
+    ChoiceParameter, NumericParameter, ParameterDefinition, ParameterValue,
+};
+use crate::compute::types::CurveData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Name of the alignment strategy parameter added to UDFs by the engine.
+pub const ALIGNMENT_PARAMETER: &str = "depth_alignment";
+
+/// Name of the parameter choosing the reference curve parameter.
+pub const REFERENCE_PARAMETER: &str = "alignment_reference";
+
+/// Name of the parameter setting the step of the regular grid.
+pub const STEP_PARAMETER: &str = "alignment_step";
+
+/// Name of the parameter choosing the interpolation between samples.
+pub const INTERPOLATION_PARAMETER: &str = "alignment_interpolation";
+
+/// Depth tolerance when matching samples.
+pub(crate) const DEPTH_TOLERANCE: f64 = 1e-6;
+
+/// Largest regular grid the engine resamples curves to.
+const MAX_GRID_SAMPLES: usize = 5_000_000;
+
+/// Depths input curves are resampled to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum AlignmentStrategy {
+    /// Curves must share their depths
+    #[default]
+    None,
+    /// Depths of the first curve
+    First,
+    /// Depths of the curve bound to a curve parameter (the first curve if
+    /// none is chosen)
+    Reference(Option<String>),
+    /// Regular grid covering all curves, at a step or the finest step of
+    /// the curves
+    Grid { step: Option<f64> },
+}
+
+impl AlignmentStrategy {
+    /// Strategy selected by the parameters of an execution.
+    pub fn from_parameters(parameters: &HashMap<String, ParameterValue>) -> Self {
+        match parameters.get(ALIGNMENT_PARAMETER).and_then(|v| v.as_str()) {
+            Some("first") => Self::First,
+            Some("reference") => Self::Reference(
+                parameters
+                    .get(REFERENCE_PARAMETER)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            ),
+            Some("grid") => Self::Grid {
+                step: parameters
+                    .get(STEP_PARAMETER)
+                    .and_then(|v| v.as_f64())
+                    .filter(|step| step.is_finite() && *step > 0.0),
+            },
+            _ => Self::None,
+        }
+    }
+
+    /// Parameter value of the strategy.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::First => "first",
+            Self::Reference(_) => "reference",
+            Self::Grid { .. } => "grid",
+        }
+    }
+}
+
+/// How values between the samples of a curve are obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// Linear between the neighbouring samples
+    #[default]
+    Linear,
+    /// Value of the nearest sample
+    Nearest,
+    /// Only samples at the same depth have a value
+    None,
+}
+
+impl Interpolation {
+    /// Interpolation by its parameter value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "linear" => Some(Self::Linear),
+            "nearest" => Some(Self::Nearest),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    /// Interpolation selected by the parameters of an execution.
+    pub fn from_parameters(parameters: &HashMap<String, ParameterValue>) -> Self {
+        parameters
+            .get(INTERPOLATION_PARAMETER)
+            .and_then(|v| v.as_str())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+}
+
+/// Resampling of an input curve onto the depths of an execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthAlignment {
+    /// Alignment strategy ("first", "reference" or "grid")
+    pub strategy: String,
+    /// Curve parameter whose depths the curve was resampled to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Step of the regular grid the curve was resampled to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+    /// Interpolation between samples
+    pub interpolation: Interpolation,
+    /// Number of samples of the curve
+    pub source_samples: usize,
+    /// Number of samples after resampling
+    pub samples: usize,
+}
+
+/// Add the engine's depth alignment parameters to a UDF's definitions.
+///
+/// Only UDFs that take more than one curve get them.
+pub fn with_alignment_parameters(
+    mut definitions: Vec<Box<dyn ParameterDefinition>>,
+) -> Vec<Box<dyn ParameterDefinition>> {
+    let curve_parameters: Vec<(String, String)> = definitions
+        .iter()
+        .filter(|d| matches!(d.param_type(), "curve" | "curve_list"))
+        .map(|d| (d.name().to_string(), d.label().to_string()))
+        .collect();
+    let takes_several =
+        curve_parameters.len() > 1 || definitions.iter().any(|d| d.param_type() == "curve_list");
+    let is_taken = definitions.iter().any(|d| {
+        [
+            ALIGNMENT_PARAMETER,
+            REFERENCE_PARAMETER,
+            STEP_PARAMETER,
+            INTERPOLATION_PARAMETER,
+        ]
+        .contains(&d.name())
+    });
+    if !takes_several || is_taken {
+        return definitions;
+    }
+
+    definitions.push(Box::new(
+        ChoiceParameter::optional(ALIGNMENT_PARAMETER, "Depth Alignment")
+            .with_description("How curves on different depth grids are resampled")
+            .with_option("none", "Require matching depths")
+            .with_option("first", "Resample to the first curve")
+            .with_option("reference", "Resample to the reference curve")
+            .with_option("grid", "Resample to a regular grid")
+            .with_default("none"),
+    ));
+    let reference = curve_parameters.iter().fold(
+        ChoiceParameter::optional(REFERENCE_PARAMETER, "Reference Curve")
+            .with_description("Curve whose depths the others are resampled to"),
+        |choice, (name, label)| choice.with_option(name, label),
+    );
+    definitions.push(Box::new(reference.with_default(&curve_parameters[0].0)));
+    definitions.push(Box::new(
+        NumericParameter::optional(STEP_PARAMETER, "Grid Step", f64::NAN)
+            .with_description("Step of the regular grid (leave empty to use the finest step)")
+            .with_min(0.0001)
+            .in_depth_unit(),
+    ));
+    definitions.push(Box::new(
+        ChoiceParameter::optional(INTERPOLATION_PARAMETER, "Interpolation")
+            .with_description("How values between the samples of a curve are obtained")
+            .with_option("linear", "Linear")
+            .with_option("nearest", "Nearest sample")
+            .with_option("none", "None (matching depths only)")
+            .with_default("linear"),
+    ));
+    definitions
+}
+
+/// Resample the input curves onto common depths, as the strategy picks.
+///
+/// Returns the alignment of each resampled curve by curve ID. Curves
+/// already on the target depths are left as they are.
+pub fn align(
+    inputs: &mut [CurveInput<'_>],
+    strategy: &AlignmentStrategy,
+    interpolation: Interpolation,
+) -> Result<Vec<(Uuid, DepthAlignment)>, UdfError> {
+    let first_depths = || {
+        inputs
+            .iter()
+            .find_map(|input| input.curves.first())
+            .map(|curve| curve.depths.clone())
+    };
+    let (target, reference, step) = match strategy {
+        AlignmentStrategy::None => return Ok(Vec::new()),
+        AlignmentStrategy::First | AlignmentStrategy::Reference(None) => {
+            (first_depths(), None, None)
+        }
+        AlignmentStrategy::Reference(Some(name)) => {
+            let curve = inputs
+                .iter()
+                .find(|input| input.def.name() == name)
+                .and_then(|input| input.curves.first())
+                .ok_or_else(|| UdfError::MissingCurve(name.clone()))?;
+            (Some(curve.depths.clone()), Some(name.clone()), None)
+        }
+        AlignmentStrategy::Grid { step } => match grid_step(inputs, *step) {
+            Some(step) => (
+                Some(Arc::new(regular_grid(inputs, step)?)),
+                None,
+                Some(step),
+            ),
+            None => (None, None, None),
+        },
+    };
+    let Some(target) = target else {
+        return Ok(Vec::new());
+    };
+
+    let mut alignments = Vec::new();
+    for input in inputs.iter_mut() {
+        for curve in &mut input.curves {
+            if same_depths(&curve.depths, &target) {
+                continue;
+            }
+
+            alignments.push((
+                curve.curve_id,
+                DepthAlignment {
+                    strategy: strategy.as_str().to_string(),
+                    reference: reference.clone(),
+                    step,
+                    interpolation,
+                    source_samples: curve.depths.len(),
+                    samples: target.len(),
+                },
+            ));
+            *curve = Arc::new(resample(curve, &target, interpolation));
+        }
+    }
+
+    Ok(alignments)
+}
+
+/// Resample a curve onto other depths.
+pub fn resample(
+    curve: &CurveData,
+    depths: &Arc<Vec<f64>>,
+    interpolation: Interpolation,
+) -> CurveData {
+    CurveData {
+        depths: depths.clone(),
+        values: depths
+            .iter()
+            .map(|&depth| interpolate(interpolation, depth, &curve.depths, &curve.values))
+            .collect(),
+        ..curve.clone()
+    }
+}
+
+/// Value of a curve at a depth.
+///
+/// Depths outside the samples of the curve have no value.
+pub fn interpolate(
+    interpolation: Interpolation,
+    target: f64,
+    depths: &[f64],
+    values: &[Option<f64>],
+) -> Option<f64> {
+    match interpolation {
+        Interpolation::Linear => interpolate_at_depth(target, depths, values),
+        Interpolation::Nearest => nearest_at_depth(target, depths, values),
+        Interpolation::None => value_at_depth(target, depths, values),
+    }
+}
+
+/// Linear interpolation at a target depth.
+pub fn interpolate_at_depth(target: f64, depths: &[f64], values: &[Option<f64>]) -> Option<f64> {
+    if depths.is_empty() {
+        return None;
+    }
+
+    // Check if outside range
+    if target < depths[0] || target > depths[depths.len() - 1] {
+        return None;
+    }
+
+    // Find bracketing indices using binary search
+    let idx = depths.partition_point(|&d| d < target);
+
+    if idx == 0 {
+        // Exactly at or before first point
+        return values[0];
+    }
+
+    if idx >= depths.len() {
+        // At or after last point
+        return values[depths.len() - 1];
+    }
+
+    // Check for exact match
+    if (depths[idx] - target).abs() < 1e-10 {
+        return values[idx];
+    }
+
+    // Linear interpolation between idx-1 and idx
+    let d0 = depths[idx - 1];
+    let d1 = depths[idx];
+    let v0 = values[idx - 1];
+    let v1 = values[idx];
+
+    match (v0, v1) {
+        (Some(val0), Some(val1)) => {
+            let t = (target - d0) / (d1 - d0);
+            Some(val0 + t * (val1 - val0))
+        }
+        (Some(val), None) | (None, Some(val)) => Some(val), // Use available value
+        (None, None) => None,
+    }
+}
+
+/// Value of the sample nearest to a target depth.
+fn nearest_at_depth(target: f64, depths: &[f64], values: &[Option<f64>]) -> Option<f64> {
+    let (first, last) = (*depths.first()?, *depths.last()?);
+    if target < first - DEPTH_TOLERANCE || target > last + DEPTH_TOLERANCE {
+        return None;
+    }
+
+    let idx = depths.partition_point(|&d| d < target);
+    let nearest = if idx == 0 {
+        0
+    } else if idx >= depths.len() || target - depths[idx - 1] <= depths[idx] - target {
+        idx - 1
+    } else {
+        idx
+    };
+    values[nearest]
+}
+
+/// Value of the sample at a target depth, if there is one.
+fn value_at_depth(target: f64, depths: &[f64], values: &[Option<f64>]) -> Option<f64> {
+    let idx = depths.partition_point(|&d| d < target - DEPTH_TOLERANCE);
+    match depths.get(idx) {
+        Some(depth) if (depth - target).abs() <= DEPTH_TOLERANCE => values[idx],
+        _ => None,
+    }
+}
+
+/// Whether two depth arrays hold the same depths.
+fn same_depths(a: &Arc<Vec<f64>>, b: &Arc<Vec<f64>>) -> bool {
+    Arc::ptr_eq(a, b)
+        || (a.len() == b.len()
+            && a.iter()
+                .zip(b.iter())
+                .all(|(d1, d2)| (d1 - d2).abs() <= DEPTH_TOLERANCE))
+}
+
+/// Step of the regular grid: the given step, or the finest mean step of
+/// the curves.
+fn grid_step(inputs: &[CurveInput<'_>], step: Option<f64>) -> Option<f64> {
+    step.or_else(|| {
+        inputs
+            .iter()
+            .flat_map(|input| &input.curves)
+            .filter(|curve| curve.depths.len() > 1)
+            .map(|curve| {
+                let depths = &curve.depths;
+                (depths[depths.len() - 1] - depths[0]) / (depths.len() - 1) as f64
+            })
+            .filter(|step| *step > 0.0)
+            .reduce(f64::min)
+    })
+}
+
+/// Regular grid from the shallowest to the deepest sample of the curves.
+fn regular_grid(inputs: &[CurveInput<'_>], step: f64) -> Result<Vec<f64>, UdfError> {
+    let extents = inputs
+        .iter()
+        .flat_map(|input| &input.curves)
+        .filter_map(|curve| Some((*curve.depths.first()?, *curve.depths.last()?)));
+    let (top, base) = extents.fold((f64::INFINITY, f64::NEG_INFINITY), |(top, base), (t, b)| {
+        (top.min(t), base.max(b))
+    });
+    if top > base {
+        return Ok(Vec::new());
+    }
+
+    let count = ((base - top) / step + DEPTH_TOLERANCE).floor() as usize + 1;
+    if count > MAX_GRID_SAMPLES {
+        return Err(UdfError::IncompatibleData(format!(
+            "A grid step of {} gives {} samples between {} and {}, more than {}",
+            step, count, top, base, MAX_GRID_SAMPLES
+        )));
+    }
+    Ok((0..count).map(|i| top + i as f64 * step).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::parameters::CurveParameter;
+    use crate::compute::test_support::curve;
+
+    #[test]
+    fn test_interpolation_methods() {
+        let depths = [100.0, 101.0, 102.0];
+        let values = [Some(10.0), Some(20.0), None];
+
+        assert_eq!(
+            interpolate(Interpolation::Linear, 100.25, &depths, &values),
+            Some(12.5)
+        );
+        assert_eq!(
+            interpolate(Interpolation::Nearest, 100.25, &depths, &values),
+            Some(10.0)
+        );
+        assert_eq!(
+            interpolate(Interpolation::Nearest, 100.75, &depths, &values),
+            Some(20.0)
+        );
+        assert_eq!(
+            interpolate(Interpolation::None, 100.25, &depths, &values),
+            None
+        );
+        assert_eq!(
+            interpolate(Interpolation::None, 101.0, &depths, &values),
+            Some(20.0)
+        );
+
+        // Null samples and depths outside the curve have no value
+        assert_eq!(
+            interpolate(Interpolation::Nearest, 101.75, &depths, &values),
+            None
+        );
+        assert_eq!(
+            interpolate(Interpolation::Linear, 103.0, &depths, &values),
+            None
+        );
+    }
+
+    #[test]
+    fn test_align_strategies() {
+        let rhob_param = CurveParameter::required("rhob", "Density");
+        let gr_param = CurveParameter::required("gr", "Gamma Ray");
+        let inputs = || {
+            vec![
+                CurveInput {
+                    def: &rhob_param,
+                    curves: vec![Arc::new(curve(
+                        "RHOB",
+                        "g/cm3",
+                        vec![100.0, 100.5, 101.0],
+                        vec![Some(2.0), Some(2.2), Some(2.4)],
+                    ))],
+                },
+                CurveInput {
+                    def: &gr_param,
+                    curves: vec![Arc::new(curve(
+                        "GR",
+                        "gAPI",
+                        vec![100.0, 101.0, 102.0],
+                        vec![Some(40.0), Some(60.0), Some(80.0)],
+                    ))],
+                },
+            ]
+        };
+
+        // Matching depths are required unless a strategy is chosen
+        let mut aligned = inputs();
+        let alignments = align(
+            &mut aligned,
+            &AlignmentStrategy::None,
+            Interpolation::Linear,
+        );
+        assert!(alignments.unwrap().is_empty());
+
+        // The GR is resampled to the density depths
+        let mut aligned = inputs();
+        let alignments = align(
+            &mut aligned,
+            &AlignmentStrategy::First,
+            Interpolation::Linear,
+        )
+        .unwrap();
+        assert_eq!(alignments.len(), 1);
+        assert_eq!(alignments[0].0, aligned[1].curves[0].curve_id);
+        assert_eq!(alignments[0].1.source_samples, 3);
+        assert_eq!(aligned[1].curves[0].depths, aligned[0].curves[0].depths);
+        assert_eq!(aligned[1].curves[0].values[1], Some(50.0));
+
+        // ... or the density to the GR depths
+        let mut aligned = inputs();
+        let strategy = AlignmentStrategy::Reference(Some("gr".to_string()));
+        let alignments = align(&mut aligned, &strategy, Interpolation::Nearest).unwrap();
+        assert_eq!(alignments[0].1.reference.as_deref(), Some("gr"));
+        assert_eq!(
+            aligned[0].curves[0].values,
+            vec![Some(2.0), Some(2.4), None]
+        );
+
+        // A grid at the finest step covers both curves
+        let mut aligned = inputs();
+        let strategy = AlignmentStrategy::Grid { step: None };
+        let alignments = align(&mut aligned, &strategy, Interpolation::Linear).unwrap();
+        assert_eq!(alignments.len(), 2);
+        assert_eq!(alignments[0].1.step, Some(0.5));
+        assert_eq!(aligned[0].curves[0].depths.len(), 5);
+        assert_eq!(aligned[1].curves[0].values[3], Some(70.0));
+
+        let strategy = AlignmentStrategy::Reference(Some("nphi".to_string()));
+        assert!(align(&mut inputs(), &strategy, Interpolation::Linear).is_err());
+    }
+
+    #[test]
+    fn test_alignment_parameters() {
+        let single: Vec<Box<dyn ParameterDefinition>> =
+            vec![Box::new(CurveParameter::required("gr", "Gamma Ray"))];
+        assert_eq!(with_alignment_parameters(single).len(), 1);
+
+        let pair: Vec<Box<dyn ParameterDefinition>> = vec![
+            Box::new(CurveParameter::required("rhob", "Density")),
+            Box::new(CurveParameter::required("gr", "Gamma Ray")),
+        ];
+        let definitions = with_alignment_parameters(pair);
+        let reference = definitions
+            .iter()
+            .find(|d| d.name() == REFERENCE_PARAMETER)
+            .unwrap();
+        assert_eq!(reference.to_json()["default"], "rhob");
+        assert_eq!(reference.to_json()["options"][1]["value"], "gr");
+
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            ALIGNMENT_PARAMETER.to_string(),
+            ParameterValue::String("grid".to_string()),
+        );
+        parameters.insert(STEP_PARAMETER.to_string(), ParameterValue::Number(0.25));
+        assert_eq!(
+            AlignmentStrategy::from_parameters(&parameters),
+            AlignmentStrategy::Grid { step: Some(0.25) }
+        );
+        assert_eq!(
+            Interpolation::from_parameters(&parameters),
+            Interpolation::Linear
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! Batch execution of a UDF across many wells.
+//!
+//! A batch runs one UDF with a shared parameter template over a list of
+//! wells, or every well in a workspace. Curve parameters are bound per well
+//! by mnemonic, `curve_properties` ID or curve type, so a single request
+//! works across wells whose curves have different IDs.
+//!
+//! Wells run one after another. Progress is reported as an aggregate over
+//! all wells, and cancelling the batch stops the running well and skips the
+//! remaining ones.
+
+use crate::compute::engine::{
+    CurveLoader, CurveMetadataInfo, ExecutionEngine, ExecutionOptions, ExecutionResult,
+};
+use crate::compute::error::UdfError;
+use crate::compute::parameters::ParameterValue;
+use crate::compute::types::{CurveDataType, ExecutionStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use uuid::Uuid;
+
+/// How a curve parameter is bound to a curve in each well.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "by", content = "value", rename_all = "snake_case")]
+pub enum CurveBinding {
+    /// Match curves by mnemonic (case-insensitive)
+    Mnemonic(String),
+    /// Match curves by DataForge `curve_properties` ID (e.g., "gamma_ray")
+    PropertyId(String),
+    /// Match curves by detected curve type (or a variety of it)
+    CurveType(CurveDataType),
+}
+
+impl CurveBinding {
+    /// Check whether a curve satisfies this binding
+    pub fn matches(&self, curve: &CurveMetadataInfo) -> bool {
+        match self {
+            CurveBinding::Mnemonic(mnemonic) => curve.mnemonic.eq_ignore_ascii_case(mnemonic),
+            CurveBinding::PropertyId(property_id) => {
+                curve.property_id.as_deref() == Some(property_id.as_str())
+            }
+            CurveBinding::CurveType(curve_type) => curve.curve_type.satisfies(*curve_type),
+        }
+    }
+}
+
+impl fmt::Display for CurveBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveBinding::Mnemonic(mnemonic) => write!(f, "mnemonic '{}'", mnemonic),
+            CurveBinding::PropertyId(property_id) => write!(f, "property '{}'", property_id),
+            CurveBinding::CurveType(curve_type) => {
+                write!(f, "curve type '{}'", curve_type.display_name())
+            }
+        }
+    }
+}
+
+/// Which wells a batch runs over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "well_ids", rename_all = "snake_case")]
+pub enum BatchTarget {
+    /// An explicit list of wells, run in the given order
+    Wells(Vec<Uuid>),
+    /// Every well in the workspace
+    Workspace,
+}
+
+/// A request to run one UDF over many wells.
+#[derive(Debug, Clone)]
+pub struct BatchRequest {
+    /// Full UDF identifier (provider:udf_id)
+    pub udf_id: String,
+    /// Workspace containing the wells
+    pub workspace_id: Uuid,
+    /// Wells to run over
+    pub target: BatchTarget,
+    /// Parameter values shared by all wells (non-curve parameters)
+    pub parameters: HashMap<String, ParameterValue>,
+    /// Curve parameter bindings, by parameter name
+    pub bindings: HashMap<String, CurveBinding>,
+}
+
+/// Outcome of one well in a batch.
+#[derive(Debug)]
+pub struct WellRun {
+    /// Well the UDF ran against
+    pub well_id: Uuid,
+    /// Status of this well's run
+    pub status: ExecutionStatus,
+    /// Curves bound for this well, by parameter name
+    pub bound_curves: HashMap<String, Uuid>,
+    /// Execution result, if the UDF ran
+    pub result: Option<ExecutionResult>,
+    /// Warnings from binding and execution
+    pub warnings: Vec<String>,
+    /// Error message if the well failed or was cancelled
+    pub error: Option<String>,
+}
+
+impl WellRun {
+    fn not_run(well_id: Uuid, status: ExecutionStatus, error: String) -> Self {
+        Self {
+            well_id,
+            status,
+            bound_curves: HashMap::new(),
+            result: None,
+            warnings: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Outcome of a batch execution, one entry per well in run order.
+#[derive(Debug, Default)]
+pub struct BatchResult {
+    pub runs: Vec<WellRun>,
+}
+
+impl BatchResult {
+    /// Number of wells with the given status
+    pub fn count(&self, status: ExecutionStatus) -> usize {
+        self.runs.iter().filter(|r| r.status == status).count()
+    }
+}
+
+impl ExecutionEngine {
+    /// Execute a UDF over many wells.
+    ///
+    /// Each well gets its own execution (and execution record). Wells whose
+    /// curves cannot be bound are recorded as failed without running. The
+    /// aggregate progress is reported through `options.progress_state`, and
+    /// cancelling `options.cancellation_token` stops the running well and
+    /// marks the remaining wells as cancelled.
+    ///
+    /// `on_well` is called as each well finishes, so callers can persist or
+    /// report results without waiting for the whole batch.
+    pub fn execute_batch(
+        &self,
+        request: &BatchRequest,
+        curve_loader: &dyn CurveLoader,
+        options: &ExecutionOptions,
+        mut on_well: impl FnMut(&mut WellRun),
+    ) -> Result<BatchResult, UdfError> {
+        // Fail fast on an unknown UDF rather than once per well
+        self.get_parameter_definitions(&request.udf_id)?;
+
+        let well_ids = match &request.target {
+            BatchTarget::Wells(well_ids) => well_ids.clone(),
+            BatchTarget::Workspace => curve_loader.list_wells(request.workspace_id)?,
+        };
+
+        let total = well_ids.len();
+        let mut result = BatchResult::default();
+
+        for (index, well_id) in well_ids.into_iter().enumerate() {
+            let mut run = if options.cancellation_token.is_cancelled() {
+                WellRun::not_run(
+                    well_id,
+                    ExecutionStatus::Cancelled,
+                    "Batch cancelled before this well ran".to_string(),
+                )
+            } else {
+                options.progress_state.set_progress_with_message(
+                    index as f64 / total as f64 * 100.0,
+                    format!("Well {} of {}", index + 1, total),
+                );
+                self.run_well(request, well_id, curve_loader, options, index, total)
+            };
+
+            on_well(&mut run);
+            result.runs.push(run);
+        }
+
+        options
+            .progress_state
+            .set_progress_with_message(100.0, "Batch finished");
+
+        Ok(result)
+    }
+
+    /// Bind curves and run the UDF for one well of a batch.
+    fn run_well(
+        &self,
+        request: &BatchRequest,
+        well_id: Uuid,
+        curve_loader: &dyn CurveLoader,
+        options: &ExecutionOptions,
+        index: usize,
+        total: usize,
+    ) -> WellRun {
+        let curves = match curve_loader.list_well_curves(well_id) {
+            Ok(curves) => curves,
+            Err(e) => return WellRun::not_run(well_id, ExecutionStatus::Failed, e.to_string()),
+        };
+
+        let (bound_curves, mut warnings) = match bind_curves(&request.bindings, &curves) {
+            Ok(bound) => bound,
+            Err(e) => return WellRun::not_run(well_id, ExecutionStatus::Failed, e),
+        };
+
+        let mut parameters = request.parameters.clone();
+        for (name, curve_id) in &bound_curves {
+            parameters.insert(name.clone(), ParameterValue::Curve(*curve_id));
+        }
+
+        // Map this well's progress into its slice of the aggregate
+        let well_options = options.for_step(index, total);
+
+        match self.execute(
+            &request.udf_id,
+            well_id,
+            request.workspace_id,
+            parameters,
+            curve_loader,
+            &well_options,
+        ) {
+            Ok(result) => {
+                if let Some(output) = &result.output {
+                    warnings.extend(output.warnings.iter().cloned());
+                }
+                WellRun {
+                    well_id,
+                    status: result.record.status,
+                    bound_curves,
+                    error: result.record.error_message.clone(),
+                    result: Some(result),
+                    warnings,
+                }
+            }
+            Err(e) => WellRun::not_run(well_id, ExecutionStatus::Failed, e.to_string()),
+        }
+    }
+}
+
+/// Resolve curve bindings against the curves of one well.
+///
+/// When several curves match a binding, the first by mnemonic is used and a
+/// warning names the alternatives. Returns an error if any binding matches
+/// no curve.
+pub fn bind_curves(
+    bindings: &HashMap<String, CurveBinding>,
+    curves: &[CurveMetadataInfo],
+) -> Result<(HashMap<String, Uuid>, Vec<String>), String> {
+    let mut bound = HashMap::new();
+    let mut warnings = Vec::new();
+
+    // Sorted for deterministic error and warning order
+    let sorted: BTreeMap<&String, &CurveBinding> = bindings.iter().collect();
+
+    for (name, binding) in sorted {
+        let mut matches: Vec<&CurveMetadataInfo> =
+            curves.iter().filter(|c| binding.matches(c)).collect();
+        matches.sort_by(|a, b| a.mnemonic.cmp(&b.mnemonic));
+
+        let chosen = matches
+            .first()
+            .ok_or_else(|| format!("No curve matches {} for parameter '{}'", binding, name))?;
+
+        if matches.len() > 1 {
+            let others: Vec<&str> = matches[1..].iter().map(|c| c.mnemonic.as_str()).collect();
+            warnings.push(format!(
+                "{} curves match {} for parameter '{}'; using {} (also: {})",
+                matches.len(),
+                binding,
+                name,
+                chosen.mnemonic,
+                others.join(", ")
+            ));
+        }
+
+        bound.insert(name.clone(), chosen.curve_id);
+    }
+
+    Ok((bound, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::context::ProgressState;
+    use crate::compute::test_support::{create_engine, curve, MockCurveLoader};
+    use crate::compute::types::CurveData;
+    use std::sync::Arc;
+
+    fn create_curve(mnemonic: &str, curve_type: CurveDataType) -> Arc<CurveData> {
+        Arc::new(CurveData {
+            curve_type,
+            property_id: (curve_type == CurveDataType::GammaRay).then(|| "gamma_ray".to_string()),
+            ..curve(
+                mnemonic,
+                "gAPI",
+                vec![100.0, 100.5, 101.0],
+                vec![Some(30.0), Some(65.0), Some(100.0)],
+            )
+        })
+    }
+
+    fn vshale_request(binding: CurveBinding) -> BatchRequest {
+        let mut parameters = HashMap::new();
+        parameters.insert("gr_min".to_string(), ParameterValue::Number(30.0));
+        parameters.insert("gr_max".to_string(), ParameterValue::Number(100.0));
+
+        let mut bindings = HashMap::new();
+        bindings.insert("gr_curve".to_string(), binding);
+
+        BatchRequest {
+            udf_id: "petro:vshale_linear".to_string(),
+            workspace_id: Uuid::new_v4(),
+            target: BatchTarget::Workspace,
+            parameters,
+            bindings,
+        }
+    }
+
+    #[test]
+    fn test_batch_binds_per_well_and_reports_failures() {
+        let engine = create_engine();
+        let loader = MockCurveLoader::default()
+            .with_well(
+                Uuid::new_v4(),
+                vec![create_curve("GR", CurveDataType::GammaRay)],
+            )
+            .with_well(
+                Uuid::new_v4(),
+                vec![create_curve("gr", CurveDataType::GammaRay)],
+            )
+            .with_well(
+                Uuid::new_v4(),
+                vec![create_curve("RHOB", CurveDataType::Density)],
+            );
+
+        let progress = Arc::new(ProgressState::new());
+        let options = ExecutionOptions::new().with_progress_state(progress.clone());
+        let mut reported = 0;
+        let result = engine
+            .execute_batch(
+                &vshale_request(CurveBinding::Mnemonic("GR".to_string())),
+                &loader,
+                &options,
+                |_| reported += 1,
+            )
+            .unwrap();
+
+        assert_eq!(reported, 3);
+        assert_eq!(result.count(ExecutionStatus::Completed), 2);
+        assert_eq!(result.count(ExecutionStatus::Failed), 1);
+        assert!(result.runs[2]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("mnemonic 'GR'"));
+        assert_eq!(
+            result.runs[1].bound_curves["gr_curve"],
+            loader.wells[1].1[0].curve_id
+        );
+        assert_eq!(progress.get_progress(), 100);
+    }
+
+    #[test]
+    fn test_bind_by_property_and_type_warns_on_ambiguity() {
+        let curves: Vec<CurveMetadataInfo> = vec![
+            create_curve("GR_EDTC", CurveDataType::GammaRay),
+            create_curve("GR", CurveDataType::GammaRay),
+            create_curve("RHOB", CurveDataType::Density),
+        ]
+        .iter()
+        .map(|c| CurveMetadataInfo::from_curve(c))
+        .collect();
+
+        let mut bindings = HashMap::new();
+        bindings.insert(
+            "gr_curve".to_string(),
+            CurveBinding::PropertyId("gamma_ray".to_string()),
+        );
+        bindings.insert(
+            "density".to_string(),
+            CurveBinding::CurveType(CurveDataType::Density),
+        );
+
+        let (bound, warnings) = bind_curves(&bindings, &curves).unwrap();
+        assert_eq!(
+            bound["gr_curve"], curves[1].curve_id,
+            "first by mnemonic wins"
+        );
+        assert_eq!(bound["density"], curves[2].curve_id);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("GR_EDTC"));
+    }
+
+    #[test]
+    fn test_cancelled_batch_skips_remaining_wells() {
+        let engine = create_engine();
+        let loader = MockCurveLoader::default()
+            .with_well(
+                Uuid::new_v4(),
+                vec![create_curve("GR", CurveDataType::GammaRay)],
+            )
+            .with_well(
+                Uuid::new_v4(),
+                vec![create_curve("GR", CurveDataType::GammaRay)],
+            );
+
+        let options = ExecutionOptions::new();
+        let token = options.cancellation_token.clone();
+        let result = engine
+            .execute_batch(
+                &vshale_request(CurveBinding::CurveType(CurveDataType::GammaRay)),
+                &loader,
+                &options,
+                |_| token.cancel(),
+            )
+            .unwrap();
+
+        assert_eq!(result.runs[0].status, ExecutionStatus::Completed);
+        assert_eq!(result.runs[1].status, ExecutionStatus::Cancelled);
+        assert!(result.runs[1].result.is_none());
+    }
+}
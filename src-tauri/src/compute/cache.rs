@@ -0,0 +1,197 @@
+//! Result caching for UDF executions.
+//!
+//! UDFs are pure functions of their version, parameter values and input
+//! curve data, so an execution can be identified by a hash of exactly
+//! those. When the engine is given a `ResultCache` it looks the key up
+//! before running the UDF and reuses a stored output on a hit, which makes
+//! re-running batches and workflows over unchanged data nearly free.
+//!
+//! Input curves are keyed by content (parquet hash, unit and type) and
+//! mnemonic rather than by curve ID, so the in-memory intermediate curves
+//! of a workflow hit the cache across runs just like stored curves do. The
+//! mnemonic is part of the key because output mnemonics are derived from it.
+
+use crate::compute::error::UdfError;
+use crate::compute::parameters::ParameterValue;
+use crate::compute::types::{CurveData, ExecutionRecord, InputReference, UdfOutput};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Output of an earlier execution found in the cache.
+#[derive(Debug, Clone)]
+pub struct CachedResult {
+    /// Execution that originally produced the output
+    pub execution_id: Uuid,
+    /// The stored output
+    pub output: UdfOutput,
+}
+
+/// Storage for execution outputs, keyed by `cache_key`.
+///
+/// Implementations are best-effort: the engine logs lookup and store
+/// errors and falls back to running the UDF.
+pub trait ResultCache: Send + Sync {
+    /// Find the output stored under a cache key.
+    fn lookup(&self, key: &str) -> Result<Option<CachedResult>, UdfError>;
+
+    /// Store the output of a completed execution under a cache key.
+    fn store(
+        &self,
+        key: &str,
+        record: &ExecutionRecord,
+        output: &UdfOutput,
+    ) -> Result<(), UdfError>;
+}
+
+/// Compute the cache key of an execution.
+///
+/// `parameters` are the values as passed to the engine, `curves` and
+/// `curve_lists` the loaded input curves keyed by parameter name and
+/// `input_refs` their references, recording any unit conversion. Curve
+/// parameters are keyed by the content of the loaded curves; null
+/// parameters are ignored so an omitted value and an explicit null share
+/// a key.
+pub fn cache_key(
+    udf_id: &str,
+    udf_version: &str,
+    parameters: &HashMap<String, ParameterValue>,
+    curves: &HashMap<String, Arc<CurveData>>,
+    curve_lists: &HashMap<String, Vec<Arc<CurveData>>>,
+    input_refs: &[InputReference],
+) -> String {
+    // BTreeMap keeps the serialized parameters in a stable order
+    let values: BTreeMap<&str, serde_json::Value> = parameters
+        .iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(name, value)| {
+            let value = match (curves.get(name), curve_lists.get(name)) {
+                (Some(curve), _) => curve_content(curve, input_refs),
+                (None, Some(list)) => serde_json::Value::Array(
+                    list.iter().map(|c| curve_content(c, input_refs)).collect(),
+                ),
+                (None, None) => serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
+            };
+            (name.as_str(), value)
+        })
+        .collect();
+
+    let key = json!({
+        "udf_id": udf_id,
+        "udf_version": udf_version,
+        "parameters": values,
+    });
+
+    let digest = Sha256::digest(key.to_string().as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// What a curve input contributes to the cache key.
+///
+/// Converted curves are keyed by the unit they were stored in, since their
+/// parquet hash is that of the stored values.
+fn curve_content(curve: &CurveData, input_refs: &[InputReference]) -> serde_json::Value {
+    let unit = input_refs
+        .iter()
+        .find(|input| input.curve_id == curve.curve_id)
+        .and_then(|input| input.unit_conversion.as_ref())
+        .map_or(curve.unit.as_str(), |c| c.from_unit.as_str());
+
+    json!({
+        "parquet_hash": curve.parquet_hash,
+        "mnemonic": curve.mnemonic,
+        "unit": unit,
+        "curve_type": curve.curve_type,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_support::curve;
+    use crate::compute::units::UnitConversion;
+
+    fn gr_curve(parquet_hash: &str) -> Arc<CurveData> {
+        Arc::new(CurveData {
+            parquet_hash: parquet_hash.to_string(),
+            ..curve("GR", "gAPI", vec![100.0], vec![Some(50.0)])
+        })
+    }
+
+    fn key_for(gr: &Arc<CurveData>, gr_max: f64, version: &str) -> String {
+        let mut parameters = HashMap::new();
+        parameters.insert("gr_curve".to_string(), ParameterValue::Curve(gr.curve_id));
+        parameters.insert("gr_min".to_string(), ParameterValue::Number(30.0));
+        parameters.insert("gr_max".to_string(), ParameterValue::Number(gr_max));
+        parameters.insert("comment".to_string(), ParameterValue::Null);
+
+        let mut curves = HashMap::new();
+        curves.insert("gr_curve".to_string(), gr.clone());
+        cache_key(
+            "petro:vshale_linear",
+            version,
+            &parameters,
+            &curves,
+            &HashMap::new(),
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_content_not_curve_id() {
+        let gr = gr_curve("hash_a");
+        let key = key_for(&gr, 100.0, "1.0.0");
+
+        assert_eq!(key, key_for(&gr, 100.0, "1.0.0"));
+        assert_eq!(key, key_for(&gr_curve("hash_a"), 100.0, "1.0.0"));
+        assert_ne!(key, key_for(&gr_curve("hash_b"), 100.0, "1.0.0"));
+        assert_ne!(key, key_for(&gr, 120.0, "1.0.0"));
+        assert_ne!(key, key_for(&gr, 100.0, "1.1.0"));
+
+        // Output mnemonics are derived from the input's
+        let renamed = Arc::new(CurveData {
+            mnemonic: "GR_EDIT".to_string(),
+            ..(*gr).clone()
+        });
+        assert_ne!(key, key_for(&renamed, 100.0, "1.0.0"));
+    }
+
+    #[test]
+    fn test_cache_key_uses_stored_unit_of_converted_curves() {
+        let stored = |unit: &str| Arc::new(curve("RHOB", unit, vec![100.0], vec![Some(2.5)]));
+        let key = |curve: &Arc<CurveData>, conversion: Option<UnitConversion>| {
+            let mut parameters = HashMap::new();
+            parameters.insert("density".to_string(), ParameterValue::Curve(curve.curve_id));
+            let mut curves = HashMap::new();
+            curves.insert("density".to_string(), curve.clone());
+            let input = InputReference {
+                curve_id: curve.curve_id,
+                version: 1,
+                parquet_hash: curve.parquet_hash.clone(),
+                unit_conversion: conversion,
+                alignment: None,
+            };
+            cache_key(
+                "test:density",
+                "1.0.0",
+                &parameters,
+                &curves,
+                &HashMap::new(),
+                &[input],
+            )
+        };
+
+        // Stored in kg/m3 and converted, then relabelled g/cm3 with the
+        // same data: the values differ, so must the keys
+        let conversion = UnitConversion {
+            from_unit: "kg/m3".to_string(),
+            to_unit: "g/cm3".to_string(),
+            factor: 0.001,
+        };
+        let converted = key(&stored("g/cm3"), Some(conversion));
+        assert_ne!(converted, key(&stored("g/cm3"), None));
+        assert_eq!(converted, key(&stored("kg/m3"), None));
+    }
+}
@@ -0,0 +1,364 @@
+//! Cross-parameter constraints.
+//!
+//! Rules between several parameters of a UDF (ordering, mutual exclusion,
+//! conditional requirement) are declared by the UDF through
+//! `Udf::parameter_constraints` rather than checked by hand in
+//! `check_parameters`. The engine evaluates them after the per-parameter
+//! validation and exports them with the parameter definitions, so the
+//! frontend can show the rules before anything is executed.
+
+use crate::compute::error::ValidationError;
+use crate::compute::parameters::{ParameterDefinition, ParameterValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A declarative rule between parameters.
+///
+/// Ordering constraints only apply when both parameters have a value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParameterConstraint {
+    /// `parameter` must be greater than `other`
+    GreaterThan { parameter: String, other: String },
+    /// `parameter` must be greater than or equal to `other`
+    GreaterOrEqual { parameter: String, other: String },
+    /// `parameter` must differ from `other`
+    NotEqual { parameter: String, other: String },
+    /// At most one of `parameters` may be set
+    MutuallyExclusive { parameters: Vec<String> },
+    /// `parameter` is required when `other` is set (to `equals`, if given)
+    RequiredIf {
+        parameter: String,
+        other: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        equals: Option<ParameterValue>,
+    },
+}
+
+impl ParameterConstraint {
+    /// `parameter` must be greater than `other`.
+    pub fn greater_than(parameter: impl Into<String>, other: impl Into<String>) -> Self {
+        Self::GreaterThan {
+            parameter: parameter.into(),
+            other: other.into(),
+        }
+    }
+
+    /// `parameter` must be greater than or equal to `other`.
+    pub fn greater_or_equal(parameter: impl Into<String>, other: impl Into<String>) -> Self {
+        Self::GreaterOrEqual {
+            parameter: parameter.into(),
+            other: other.into(),
+        }
+    }
+
+    /// `parameter` must differ from `other`.
+    pub fn not_equal(parameter: impl Into<String>, other: impl Into<String>) -> Self {
+        Self::NotEqual {
+            parameter: parameter.into(),
+            other: other.into(),
+        }
+    }
+
+    /// At most one of `parameters` may be set.
+    pub fn mutually_exclusive<S: Into<String>>(parameters: impl IntoIterator<Item = S>) -> Self {
+        Self::MutuallyExclusive {
+            parameters: parameters.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// `parameter` is required whenever `other` is set.
+    pub fn required_if(parameter: impl Into<String>, other: impl Into<String>) -> Self {
+        Self::RequiredIf {
+            parameter: parameter.into(),
+            other: other.into(),
+            equals: None,
+        }
+    }
+
+    /// `parameter` is required when `other` has the value `equals`.
+    pub fn required_if_equals(
+        parameter: impl Into<String>,
+        other: impl Into<String>,
+        equals: ParameterValue,
+    ) -> Self {
+        Self::RequiredIf {
+            parameter: parameter.into(),
+            other: other.into(),
+            equals: Some(equals),
+        }
+    }
+
+    /// Names of the parameters the constraint refers to.
+    ///
+    /// The first one is the parameter errors are reported on.
+    pub fn parameters(&self) -> Vec<&str> {
+        match self {
+            Self::GreaterThan { parameter, other }
+            | Self::GreaterOrEqual { parameter, other }
+            | Self::NotEqual { parameter, other }
+            | Self::RequiredIf {
+                parameter, other, ..
+            } => vec![parameter.as_str(), other.as_str()],
+            Self::MutuallyExclusive { parameters } => {
+                parameters.iter().map(String::as_str).collect()
+            }
+        }
+    }
+
+    /// Human-readable statement of the rule, using parameter labels.
+    pub fn describe(&self, definitions: &[Box<dyn ParameterDefinition>]) -> String {
+        let label = |name: &str| label_of(definitions, name);
+        match self {
+            Self::GreaterThan { parameter, other } => {
+                format!("{} must be greater than {}", label(parameter), label(other))
+            }
+            Self::GreaterOrEqual { parameter, other } => {
+                format!("{} must be at least {}", label(parameter), label(other))
+            }
+            Self::NotEqual { parameter, other } => {
+                format!("{} must differ from {}", label(parameter), label(other))
+            }
+            Self::MutuallyExclusive { parameters } => format!(
+                "Only one of {} may be set",
+                parameters
+                    .iter()
+                    .map(|p| label(p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::RequiredIf {
+                parameter,
+                other,
+                equals: None,
+            } => format!(
+                "{} is required when {} is set",
+                label(parameter),
+                label(other)
+            ),
+            Self::RequiredIf {
+                parameter,
+                other,
+                equals: Some(value),
+            } => format!(
+                "{} is required when {} is {}",
+                label(parameter),
+                label(other),
+                display_value(value)
+            ),
+        }
+    }
+
+    /// Get the constraint as JSON for the frontend.
+    pub fn to_json(&self, definitions: &[Box<dyn ParameterDefinition>]) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        if let Some(object) = json.as_object_mut() {
+            object.insert(
+                "description".to_string(),
+                serde_json::Value::String(self.describe(definitions)),
+            );
+        }
+        json
+    }
+
+    /// Check the constraint against resolved parameter values.
+    ///
+    /// `values` hold the value of every parameter after defaults were
+    /// applied; missing entries count as unset.
+    pub fn evaluate(
+        &self,
+        definitions: &[Box<dyn ParameterDefinition>],
+        values: &HashMap<String, ParameterValue>,
+    ) -> Result<(), ValidationError> {
+        let label = |name: &str| label_of(definitions, name);
+        let value = |name: &str| values.get(name).filter(|v| is_set(v));
+
+        match self {
+            Self::GreaterThan { parameter, other } | Self::GreaterOrEqual { parameter, other } => {
+                let (Some(a), Some(b)) = (
+                    value(parameter).and_then(ParameterValue::as_f64),
+                    value(other).and_then(ParameterValue::as_f64),
+                ) else {
+                    return Ok(());
+                };
+                let strict = matches!(self, Self::GreaterThan { .. });
+                if (strict && a > b) || (!strict && a >= b) {
+                    return Ok(());
+                }
+                let bound = if strict { "above" } else { "of at least" };
+                let suggestion = format!(
+                    "Enter a {} {} {} or lower {}",
+                    label(parameter),
+                    bound,
+                    b,
+                    label(other)
+                );
+                Err(ValidationError::new(parameter, self.describe(definitions))
+                    .with_suggestion(suggestion))
+            }
+            Self::NotEqual { parameter, other } => match (value(parameter), value(other)) {
+                (Some(a), Some(b)) if values_equal(a, b) => {
+                    Err(ValidationError::new(parameter, self.describe(definitions))
+                        .with_suggestion(format!(
+                            "Change {} or {}",
+                            label(parameter),
+                            label(other)
+                        )))
+                }
+                _ => Ok(()),
+            },
+            Self::MutuallyExclusive { parameters } => {
+                let set: Vec<&String> = parameters.iter().filter(|p| value(p).is_some()).collect();
+                if set.len() <= 1 {
+                    return Ok(());
+                }
+                Err(ValidationError::new(set[1], self.describe(definitions))
+                    .with_suggestion(format!("Clear {} or {}", label(set[0]), label(set[1]))))
+            }
+            Self::RequiredIf {
+                parameter,
+                other,
+                equals,
+            } => {
+                let triggered = match (value(other), equals) {
+                    (Some(actual), Some(expected)) => values_equal(actual, expected),
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                if !triggered || value(parameter).is_some() {
+                    return Ok(());
+                }
+                Err(
+                    ValidationError::new(parameter, self.describe(definitions)).with_suggestion(
+                        format!("Provide {} or clear {}", label(parameter), label(other)),
+                    ),
+                )
+            }
+        }
+    }
+}
+
+/// Display label of a parameter, falling back to its name.
+fn label_of<'a>(definitions: &'a [Box<dyn ParameterDefinition>], name: &'a str) -> &'a str {
+    definitions
+        .iter()
+        .find(|d| d.name() == name)
+        .map(|d| d.label())
+        .unwrap_or(name)
+}
+
+/// Whether a value counts as set (numeric NaN defaults mean "unset").
+fn is_set(value: &ParameterValue) -> bool {
+    match value {
+        ParameterValue::Null => false,
+        ParameterValue::Number(n) => !n.is_nan(),
+        ParameterValue::String(s) => !s.is_empty(),
+        ParameterValue::CurveList(ids) => !ids.is_empty(),
+        _ => true,
+    }
+}
+
+/// Compare values, treating integers and floats as numbers.
+fn values_equal(a: &ParameterValue, b: &ParameterValue) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => (x - y).abs() < 1e-10,
+        _ => serde_json::to_value(a).ok() == serde_json::to_value(b).ok(),
+    }
+}
+
+fn display_value(value: &ParameterValue) -> String {
+    match value {
+        ParameterValue::String(s) => format!("'{}'", s),
+        other => serde_json::to_value(other)
+            .map(|v| v.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::parameters::{ChoiceParameter, NumericParameter};
+
+    fn definitions() -> Vec<Box<dyn ParameterDefinition>> {
+        vec![
+            Box::new(NumericParameter::required("gr_min", "GR Min")),
+            Box::new(NumericParameter::required("gr_max", "GR Max")),
+            Box::new(
+                ChoiceParameter::optional("method", "Method")
+                    .with_option("linear", "Linear")
+                    .with_option("custom", "Custom")
+                    .with_default("linear"),
+            ),
+            Box::new(NumericParameter::optional("exponent", "Exponent", f64::NAN)),
+        ]
+    }
+
+    fn values(entries: &[(&str, ParameterValue)]) -> HashMap<String, ParameterValue> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_ordering_constraint() {
+        let defs = definitions();
+        let constraint = ParameterConstraint::greater_than("gr_max", "gr_min");
+
+        let ok = values(&[
+            ("gr_min", ParameterValue::Integer(30)),
+            ("gr_max", ParameterValue::Number(100.0)),
+        ]);
+        assert!(constraint.evaluate(&defs, &ok).is_ok());
+
+        let reversed = values(&[
+            ("gr_min", ParameterValue::Number(100.0)),
+            ("gr_max", ParameterValue::Number(30.0)),
+        ]);
+        let err = constraint.evaluate(&defs, &reversed).unwrap_err();
+        assert_eq!(err.field, "gr_max");
+        assert_eq!(err.message, "GR Max must be greater than GR Min");
+        assert!(err.suggestion.is_some());
+
+        // Unset values are left to the per-parameter validation
+        assert!(constraint.evaluate(&defs, &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_required_if_and_mutual_exclusion() {
+        let defs = definitions();
+        let required = ParameterConstraint::required_if_equals(
+            "exponent",
+            "method",
+            ParameterValue::String("custom".to_string()),
+        );
+
+        let custom = values(&[
+            ("method", ParameterValue::String("custom".to_string())),
+            ("exponent", ParameterValue::Number(f64::NAN)),
+        ]);
+        assert_eq!(
+            required.evaluate(&defs, &custom).unwrap_err().field,
+            "exponent"
+        );
+        let linear = values(&[("method", ParameterValue::String("linear".to_string()))]);
+        assert!(required.evaluate(&defs, &linear).is_ok());
+
+        let exclusive = ParameterConstraint::mutually_exclusive(["gr_min", "exponent"]);
+        let both = values(&[
+            ("gr_min", ParameterValue::Number(30.0)),
+            ("exponent", ParameterValue::Number(2.0)),
+        ]);
+        assert!(exclusive.evaluate(&defs, &both).is_err());
+        assert!(exclusive.evaluate(&defs, &custom).is_ok());
+
+        let json = required.to_json(&defs);
+        assert_eq!(json["type"], "required_if");
+        assert_eq!(json["equals"], "custom");
+        assert_eq!(
+            json["description"],
+            "Exponent is required when Method is 'custom'"
+        );
+    }
+}
@@ -4,9 +4,12 @@
 //! during UDF execution. It enforces immutability of inputs and tracks
 //! all data access for provenance.
 
+use crate::compute::alignment::{DepthAlignment, DEPTH_TOLERANCE};
 use crate::compute::error::UdfError;
+use crate::compute::interval::DepthInterval;
 use crate::compute::parameters::ParameterValues;
 use crate::compute::types::{CurveData, CurveDataType, InputReference};
+use crate::compute::units::UnitConversion;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
@@ -41,12 +44,23 @@ impl CancellationToken {
 }
 
 /// Shared progress state for execution tracking.
-#[derive(Debug)]
 pub struct ProgressState {
     /// Progress percentage (0-100)
     progress: AtomicU8,
     /// Current status message
     message: std::sync::RwLock<Option<String>>,
+    /// Optional listener notified when progress or message changes
+    listener: Option<ProgressCallback>,
+}
+
+impl std::fmt::Debug for ProgressState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressState")
+            .field("progress", &self.get_progress())
+            .field("message", &self.get_message())
+            .field("has_listener", &self.listener.is_some())
+            .finish()
+    }
 }
 
 impl Default for ProgressState {
@@ -54,6 +68,7 @@ impl Default for ProgressState {
         Self {
             progress: AtomicU8::new(0),
             message: std::sync::RwLock::new(None),
+            listener: None,
         }
     }
 }
@@ -64,18 +79,57 @@ impl ProgressState {
         Self::default()
     }
 
+    /// Create a progress state that pushes updates to a listener.
+    ///
+    /// The listener is only called when the whole-percent value or the
+    /// message changes, so UDFs can report progress on every sample
+    /// without flooding the listener.
+    pub fn with_listener(listener: ProgressCallback) -> Self {
+        Self {
+            listener: Some(listener),
+            ..Self::default()
+        }
+    }
+
+    /// Create a progress state for one step of a multi-step run.
+    ///
+    /// Progress reported to the step (0-100) is mapped onto the step's
+    /// share of `parent`, so step `index` of `total` covers
+    /// `index / total .. (index + 1) / total` of the overall progress.
+    pub fn for_step(parent: Arc<ProgressState>, index: usize, total: usize) -> Self {
+        Self::with_listener(Box::new(move |progress: f64, _message: Option<&str>| {
+            parent.set_progress((index as f64 + progress / 100.0) / total as f64 * 100.0);
+        }))
+    }
+
     /// Set progress (0-100).
     pub fn set_progress(&self, percent: f64) {
-        let clamped = percent.clamp(0.0, 100.0) as u8;
-        self.progress.store(clamped, Ordering::SeqCst);
+        if self.store_progress(percent) {
+            self.notify();
+        }
     }
 
     /// Set progress with a message.
     pub fn set_progress_with_message(&self, percent: f64, message: impl Into<String>) {
-        self.set_progress(percent);
+        self.store_progress(percent);
         if let Ok(mut msg) = self.message.write() {
             *msg = Some(message.into());
         }
+        self.notify();
+    }
+
+    /// Store the clamped progress, returning true if the value changed.
+    fn store_progress(&self, percent: f64) -> bool {
+        let clamped = percent.clamp(0.0, 100.0) as u8;
+        self.progress.swap(clamped, Ordering::SeqCst) != clamped
+    }
+
+    /// Notify the listener (if any) of the current state.
+    fn notify(&self) {
+        if let Some(listener) = &self.listener {
+            let message = self.get_message();
+            listener(self.get_progress() as f64, message.as_deref());
+        }
     }
 
     /// Get current progress (0-100).
@@ -99,12 +153,16 @@ pub struct ExecutionContext {
     parameters: ParameterValues,
     /// Loaded curve data by parameter name
     curves: HashMap<String, Arc<CurveData>>,
+    /// Loaded curve lists by parameter name
+    curve_lists: HashMap<String, Vec<Arc<CurveData>>>,
     /// Input references for provenance tracking
     input_refs: Vec<InputReference>,
     /// Well ID for the execution (all curves must be from same well)
     well_id: Uuid,
     /// Workspace ID
     workspace_id: Uuid,
+    /// Depth interval the execution is restricted to
+    depth_interval: Option<DepthInterval>,
     /// Execution metadata
     metadata: HashMap<String, String>,
     /// Cancellation token for cooperative cancellation
@@ -119,6 +177,7 @@ impl std::fmt::Debug for ExecutionContext {
             .field("well_id", &self.well_id)
             .field("workspace_id", &self.workspace_id)
             .field("curves", &self.curves.keys().collect::<Vec<_>>())
+            .field("curve_lists", &self.curve_lists.keys().collect::<Vec<_>>())
             .field("input_refs", &self.input_refs.len())
             .finish()
     }
@@ -132,9 +191,11 @@ impl ExecutionContext {
         Self {
             parameters,
             curves: HashMap::new(),
+            curve_lists: HashMap::new(),
             input_refs: Vec::new(),
             well_id,
             workspace_id,
+            depth_interval: None,
             metadata: HashMap::new(),
             cancellation_token: Arc::new(CancellationToken::new()),
             progress_state: Arc::new(ProgressState::new()),
@@ -151,9 +212,11 @@ impl ExecutionContext {
         Self {
             parameters,
             curves: HashMap::new(),
+            curve_lists: HashMap::new(),
             input_refs: Vec::new(),
             well_id,
             workspace_id,
+            depth_interval: None,
             metadata: HashMap::new(),
             cancellation_token,
             progress_state: Arc::new(ProgressState::new()),
@@ -212,6 +275,18 @@ impl ExecutionContext {
         self.progress_state.clone()
     }
 
+    /// Report loop progress and check for cancellation.
+    ///
+    /// Convenience for UDF sample loops: sets progress to `completed / total`
+    /// and returns `UdfError::Cancelled` if cancellation has been requested.
+    pub fn checkpoint(&self, completed: usize, total: usize) -> Result<(), UdfError> {
+        self.check_cancelled()?;
+        if total > 0 {
+            self.set_progress(completed as f64 / total as f64 * 100.0);
+        }
+        Ok(())
+    }
+
     /// Get the well ID for this execution.
     pub fn well_id(&self) -> Uuid {
         self.well_id
@@ -222,6 +297,14 @@ impl ExecutionContext {
         self.workspace_id
     }
 
+    /// Get the depth interval the execution is restricted to, if any.
+    ///
+    /// Input samples outside it are null and output samples outside it are
+    /// discarded by the engine.
+    pub fn depth_interval(&self) -> Option<DepthInterval> {
+        self.depth_interval
+    }
+
     /// Get parameter values.
     pub fn parameters(&self) -> &ParameterValues {
         &self.parameters
@@ -240,11 +323,46 @@ impl ExecutionContext {
             .ok_or_else(|| UdfError::MissingCurve(param_name.to_string()))
     }
 
+    /// Get the curves bound to a curve list parameter, in list order.
+    pub fn get_curve_list(&self, param_name: &str) -> Option<&[Arc<CurveData>]> {
+        self.curve_lists
+            .get(param_name)
+            .map(|curves| curves.as_slice())
+    }
+
+    /// Get a required curve list, returning an error if not found.
+    pub fn require_curve_list(&self, param_name: &str) -> Result<&[Arc<CurveData>], UdfError> {
+        self.get_curve_list(param_name)
+            .ok_or_else(|| UdfError::MissingCurve(param_name.to_string()))
+    }
+
     /// Get all loaded curves.
     pub fn curves(&self) -> &HashMap<String, Arc<CurveData>> {
         &self.curves
     }
 
+    /// Get all loaded curve lists.
+    pub fn curve_lists(&self) -> &HashMap<String, Vec<Arc<CurveData>>> {
+        &self.curve_lists
+    }
+
+    /// All input curves, single and listed, with display names.
+    ///
+    /// List members are named `param[index]`.
+    fn all_curves(&self) -> impl Iterator<Item = (String, &Arc<CurveData>)> {
+        let single = self
+            .curves
+            .iter()
+            .map(|(name, curve)| (name.clone(), curve));
+        let listed = self.curve_lists.iter().flat_map(|(name, curves)| {
+            curves
+                .iter()
+                .enumerate()
+                .map(move |(i, curve)| (format!("{}[{}]", name, i), curve))
+        });
+        single.chain(listed)
+    }
+
     /// Get input references for provenance tracking.
     pub fn input_refs(&self) -> &[InputReference] {
         &self.input_refs
@@ -259,11 +377,29 @@ impl ExecutionContext {
             curve_id: curve.curve_id,
             version: curve.version,
             parquet_hash: curve.parquet_hash.clone(),
+            unit_conversion: None,
+            alignment: None,
         });
 
         self.curves.insert(param_name, curve);
     }
 
+    /// Add a curve list to the context.
+    ///
+    /// Every curve of the list is tracked for provenance.
+    pub fn add_curve_list(&mut self, param_name: String, curves: Vec<Arc<CurveData>>) {
+        for curve in &curves {
+            self.input_refs.push(InputReference {
+                curve_id: curve.curve_id,
+                version: curve.version,
+                parquet_hash: curve.parquet_hash.clone(),
+                unit_conversion: None,
+                alignment: None,
+            });
+        }
+        self.curve_lists.insert(param_name, curves);
+    }
+
     /// Set execution metadata.
     pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
         self.metadata.insert(key.into(), value.into());
@@ -281,7 +417,7 @@ impl ExecutionContext {
     pub fn validate_depth_compatibility(&self) -> Result<(), UdfError> {
         let mut reference_depths: Option<&Arc<Vec<f64>>> = None;
 
-        for (name, curve) in &self.curves {
+        for (name, curve) in self.all_curves() {
             match reference_depths {
                 None => {
                     reference_depths = Some(&curve.depths);
@@ -302,7 +438,7 @@ impl ExecutionContext {
                         for (i, (d1, d2)) in
                             ref_depths.iter().zip(curve.depths.iter()).enumerate()
                         {
-                            if (d1 - d2).abs() > 1e-6 {
+                            if (d1 - d2).abs() > DEPTH_TOLERANCE {
                                 return Err(UdfError::IncompatibleData(format!(
                                     "Depth mismatch at index {} for curve '{}': {} vs {}",
                                     i, name, d1, d2
@@ -321,12 +457,12 @@ impl ExecutionContext {
     ///
     /// All curves should have compatible depths after validation.
     pub fn get_depths(&self) -> Option<Arc<Vec<f64>>> {
-        self.curves.values().next().map(|c| c.depths.clone())
+        self.all_curves().next().map(|(_, c)| c.depths.clone())
     }
 
     /// Check if a curve type is present in the context.
     pub fn has_curve_type(&self, curve_type: CurveDataType) -> bool {
-        self.curves.values().any(|c| c.curve_type == curve_type)
+        self.all_curves().any(|(_, c)| c.curve_type == curve_type)
     }
 }
 
@@ -338,6 +474,10 @@ pub struct ExecutionContextBuilder {
     workspace_id: Uuid,
     parameters: ParameterValues,
     curves: HashMap<String, Arc<CurveData>>,
+    curve_lists: HashMap<String, Vec<Arc<CurveData>>>,
+    unit_conversions: HashMap<Uuid, UnitConversion>,
+    alignments: HashMap<Uuid, DepthAlignment>,
+    depth_interval: Option<DepthInterval>,
     metadata: HashMap<String, String>,
     cancellation_token: Option<Arc<CancellationToken>>,
     progress_state: Option<Arc<ProgressState>>,
@@ -351,6 +491,10 @@ impl ExecutionContextBuilder {
             workspace_id,
             parameters: ParameterValues::default(),
             curves: HashMap::new(),
+            curve_lists: HashMap::new(),
+            unit_conversions: HashMap::new(),
+            alignments: HashMap::new(),
+            depth_interval: None,
             metadata: HashMap::new(),
             cancellation_token: None,
             progress_state: None,
@@ -369,6 +513,34 @@ impl ExecutionContextBuilder {
         self
     }
 
+    /// Add a curve list.
+    pub fn with_curve_list(
+        mut self,
+        param_name: impl Into<String>,
+        curves: Vec<Arc<CurveData>>,
+    ) -> Self {
+        self.curve_lists.insert(param_name.into(), curves);
+        self
+    }
+
+    /// Record the unit conversion applied to an input curve's values.
+    pub fn with_unit_conversion(mut self, curve_id: Uuid, conversion: UnitConversion) -> Self {
+        self.unit_conversions.insert(curve_id, conversion);
+        self
+    }
+
+    /// Record the resampling of an input curve onto the execution's depths.
+    pub fn with_depth_alignment(mut self, curve_id: Uuid, alignment: DepthAlignment) -> Self {
+        self.alignments.insert(curve_id, alignment);
+        self
+    }
+
+    /// Restrict the execution to a depth interval.
+    pub fn with_depth_interval(mut self, interval: Option<DepthInterval>) -> Self {
+        self.depth_interval = interval;
+        self
+    }
+
     /// Add metadata.
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
@@ -399,9 +571,11 @@ impl ExecutionContextBuilder {
         let mut ctx = ExecutionContext {
             parameters: self.parameters,
             curves: HashMap::new(),
+            curve_lists: HashMap::new(),
             input_refs: Vec::new(),
             well_id: self.well_id,
             workspace_id: self.workspace_id,
+            depth_interval: self.depth_interval,
             metadata: self.metadata,
             cancellation_token,
             progress_state,
@@ -410,6 +584,13 @@ impl ExecutionContextBuilder {
         for (name, curve) in self.curves {
             ctx.add_curve(name, curve);
         }
+        for (name, curves) in self.curve_lists {
+            ctx.add_curve_list(name, curves);
+        }
+        for input in &mut ctx.input_refs {
+            input.unit_conversion = self.unit_conversions.get(&input.curve_id).cloned();
+            input.alignment = self.alignments.get(&input.curve_id).cloned();
+        }
 
         ctx
     }
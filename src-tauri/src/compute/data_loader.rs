@@ -3,35 +3,64 @@
 //! This module provides the bridge between the UDF execution engine
 //! and the DataForge database/blob storage.
 
-use crate::compute::engine::{CurveLoader, CurveMetadataInfo};
+use crate::compute::engine::{CurveLoader, CurveMetadataInfo, WellMarker};
 use crate::compute::error::UdfError;
+use crate::compute::mnemonics::MnemonicDictionary;
+use crate::compute::staleness::{has_column, has_table};
+use crate::compute::taxonomy::CurveTaxonomy;
 use crate::compute::types::{CurveData, CurveDataType};
 use duckdb::Connection as DuckDbConnection;
-use rusqlite::Connection;
-use std::path::PathBuf;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
 /// DataForge curve loader that reads from SQLite metadata and Parquet blobs.
-pub struct DataForgeCurveLoader<'a> {
-    /// Reference to the SQLite database connection
-    db: &'a Connection,
+///
+/// The loader owns its own connection so executions can run on worker
+/// threads without holding the global compute state lock.
+pub struct DataForgeCurveLoader {
+    /// SQLite database connection
+    db: Connection,
     /// Path to the blobs directory
     blobs_dir: PathBuf,
+    /// Curve types of the database's curve properties
+    taxonomy: CurveTaxonomy,
+    /// Curve types of mnemonics, for curves without a property
+    mnemonics: MnemonicDictionary,
     /// Cached depth arrays by well_id to share across curves
     depth_cache: std::cell::RefCell<std::collections::HashMap<Uuid, Arc<Vec<f64>>>>,
 }
 
-impl<'a> DataForgeCurveLoader<'a> {
-    /// Create a new curve loader.
-    pub fn new(db: &'a Connection, blobs_dir: PathBuf) -> Self {
+impl DataForgeCurveLoader {
+    /// Create a new curve loader from an existing connection.
+    pub fn new(db: Connection, blobs_dir: PathBuf) -> Self {
+        // Without a readable curve_properties table the built-in properties apply
+        let taxonomy = CurveTaxonomy::load(&db).unwrap_or_default();
         Self {
             db,
             blobs_dir,
+            taxonomy,
+            mnemonics: MnemonicDictionary::defaults(),
             depth_cache: std::cell::RefCell::new(std::collections::HashMap::new()),
         }
     }
 
+    /// Use a workspace's mnemonic dictionary instead of the defaults.
+    pub fn with_mnemonics(mut self, mnemonics: MnemonicDictionary) -> Self {
+        self.mnemonics = mnemonics;
+        self
+    }
+
+    /// Open a dedicated read-only connection to the DataForge database.
+    pub fn open(db_path: &Path, blobs_dir: PathBuf) -> Result<Self, UdfError> {
+        let db = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self::new(db, blobs_dir))
+    }
+
     /// Get the path to a parquet blob by its hash.
     fn blob_path(&self, hash: &str) -> PathBuf {
         self.blobs_dir
@@ -40,43 +69,42 @@ impl<'a> DataForgeCurveLoader<'a> {
             .join(format!("{}.parquet", hash))
     }
 
-    /// Map a mnemonic to a curve type.
-    fn detect_curve_type(&self, mnemonic: &str, main_curve_type: Option<&str>) -> CurveDataType {
-        // First check if we have a stored main_curve_type
-        if let Some(mct) = main_curve_type {
-            return CurveDataType::from_main_curve_type(mct);
+    /// Map a curve property, or else a mnemonic, to a curve type.
+    fn detect_curve_type(&self, mnemonic: &str, property_id: Option<&str>) -> CurveDataType {
+        // First check if the curve has a property
+        if let Some(property_id) = property_id {
+            return self.taxonomy.curve_type(property_id);
         }
 
         // Fallback: detect from mnemonic
-        let upper = mnemonic.to_uppercase();
-        if upper.contains("GR") || upper.contains("GAMMA") {
-            CurveDataType::GammaRay
-        } else if upper.contains("RHOB") || upper.contains("DENSITY") {
-            CurveDataType::Density
-        } else if upper.contains("NPHI") || upper.contains("NEUTRON") {
-            CurveDataType::NeutronPorosity
-        } else if upper.contains("RT") || upper.contains("RES") || upper.contains("ILD") {
-            CurveDataType::Resistivity
-        } else if upper.contains("CALI") || upper.contains("CALIPER") {
-            CurveDataType::Caliper
-        } else if upper.contains("DT") || upper.contains("SONIC") {
-            CurveDataType::Sonic
-        } else if upper.contains("SP") {
-            CurveDataType::SpontaneousPotential
-        } else if upper.contains("PE") || upper.contains("PHOTO") {
-            CurveDataType::PhotoelectricFactor
-        } else if upper.contains("DEPTH") {
-            CurveDataType::Depth
-        } else if upper.contains("VSH") || upper.contains("PHI") || upper.contains("SW") {
-            CurveDataType::Computed
-        } else {
-            CurveDataType::Unknown
+        self.mnemonics
+            .detect(mnemonic)
+            .unwrap_or(CurveDataType::Unknown)
+    }
+
+    /// Build curve metadata from a `METADATA_COLUMNS` row.
+    fn curve_metadata(&self, curve_id: Uuid, row: MetadataRow) -> CurveMetadataInfo {
+        let curve_type = self.detect_curve_type(&row.mnemonic, row.property_id.as_deref());
+
+        CurveMetadataInfo {
+            curve_id,
+            curve_type,
+            mnemonic: row.mnemonic,
+            unit: row.unit.unwrap_or_default(),
+            row_count: row.row_count,
+            property_id: row.property_id,
+            depth_range: row.top_depth.zip(row.bottom_depth),
+            null_count: row.null_count,
         }
     }
-}
 
-impl<'a> CurveLoader for DataForgeCurveLoader<'a> {
-    fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+    /// Load a curve from its current data, or from the blob with the given
+    /// hash if one is pinned.
+    fn read_curve(
+        &self,
+        curve_id: Uuid,
+        pinned_hash: Option<&str>,
+    ) -> Result<Arc<CurveData>, UdfError> {
         // Query curve metadata with join to curve_properties
         // DataForge uses property_id -> curve_properties.id for curve type
         let (mnemonic, unit, parquet_hash, version, well_id, property_id): (
@@ -109,11 +137,11 @@ impl<'a> CurveLoader for DataForgeCurveLoader<'a> {
             )
             .map_err(|e| UdfError::CurveLoadError(format!("Curve not found: {}", e)))?;
 
-        // Convert property_id to MainCurveType format
-        let main_curve_type = property_id.map(|pid| property_id_to_curve_type_code(&pid));
-
-        let parquet_hash =
-            parquet_hash.ok_or_else(|| UdfError::CurveLoadError("Curve has no data".to_string()))?;
+        let parquet_hash = match pinned_hash {
+            Some(hash) => hash.to_string(),
+            None => parquet_hash
+                .ok_or_else(|| UdfError::CurveLoadError("Curve has no data".to_string()))?,
+        };
 
         let blob_path = self.blob_path(&parquet_hash);
 
@@ -198,12 +226,13 @@ impl<'a> CurveLoader for DataForgeCurveLoader<'a> {
             }
         };
 
-        let curve_type = self.detect_curve_type(&mnemonic, main_curve_type.as_deref());
+        let curve_type = self.detect_curve_type(&mnemonic, property_id.as_deref());
 
         Ok(Arc::new(CurveData {
             curve_id,
             mnemonic,
             curve_type,
+            property_id,
             unit: unit.unwrap_or_default(),
             depths: depths_arc,
             values,
@@ -211,49 +240,190 @@ impl<'a> CurveLoader for DataForgeCurveLoader<'a> {
             version,
         }))
     }
+}
+
+impl CurveLoader for DataForgeCurveLoader {
+    fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+        self.read_curve(curve_id, None)
+    }
+
+    fn load_curve_version(
+        &self,
+        curve_id: Uuid,
+        parquet_hash: &str,
+    ) -> Result<Arc<CurveData>, UdfError> {
+        // Blobs are content-addressed and kept when a curve gets new data,
+        // so earlier versions stay readable
+        self.read_curve(curve_id, Some(parquet_hash))
+    }
 
     fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError> {
-        let (mnemonic, unit, row_count, property_id): (String, Option<String>, i64, Option<String>) = self
+        let row = self
             .db
             .query_row(
-                r#"SELECT c.mnemonic, c.unit,
-                          COALESCE(c.native_sample_count, 0),
-                          cp.id as property_id
-                   FROM curves c
-                   LEFT JOIN curve_properties cp ON c.property_id = cp.id
-                   WHERE c.id = ?1"#,
+                &format!("SELECT {} WHERE c.id = ?1", METADATA_COLUMNS),
                 [curve_id.to_string()],
-                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+                |row| MetadataRow::read(row, 0),
             )
             .map_err(|e| UdfError::CurveLoadError(format!("Curve not found: {}", e)))?;
 
-        let main_curve_type = property_id.map(|pid| property_id_to_curve_type_code(&pid));
-        let curve_type = self.detect_curve_type(&mnemonic, main_curve_type.as_deref());
+        Ok(self.curve_metadata(curve_id, row))
+    }
 
-        Ok(CurveMetadataInfo {
-            curve_id,
-            mnemonic,
-            curve_type,
-            unit: unit.unwrap_or_default(),
-            row_count,
+    fn list_well_curves(&self, well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError> {
+        let mut stmt = self.db.prepare(&format!(
+            "SELECT c.id, {} WHERE c.well_id = ?1 AND c.deleted_at IS NULL ORDER BY c.mnemonic",
+            METADATA_COLUMNS
+        ))?;
+
+        let rows = stmt
+            .query_map([well_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, MetadataRow::read(row, 1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, row)| {
+                let curve_id = Uuid::parse_str(&id)
+                    .map_err(|e| UdfError::CurveLoadError(format!("Invalid curve UUID: {}", e)))?;
+                Ok(self.curve_metadata(curve_id, row))
+            })
+            .collect()
+    }
+
+    fn list_wells(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        let mut stmt = self
+            .db
+            .prepare("SELECT id FROM wells WHERE workspace_id = ?1 ORDER BY name")?;
+
+        let ids = stmt
+            .query_map([workspace_id.to_string()], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        ids.iter()
+            .map(|id| {
+                Uuid::parse_str(id)
+                    .map_err(|e| UdfError::CurveLoadError(format!("Invalid well UUID: {}", e)))
+            })
+            .collect()
+    }
+
+    fn list_well_markers(&self, well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+        load_well_markers(&self.db, well_id)
+    }
+
+    fn well_depth_unit(&self, well_id: Uuid) -> Result<Option<String>, UdfError> {
+        load_well_depth_unit(&self.db, well_id)
+    }
+}
+
+/// Columns and joins of a curve metadata query.
+///
+/// Gridded curves are loaded on depth indices, so their native depth extent
+/// says nothing about the loaded depths and is left out.
+const METADATA_COLUMNS: &str = r#"c.mnemonic, c.unit,
+       COALESCE(c.native_sample_count, 0),
+       cp.id as property_id,
+       CASE WHEN c.gridded_parquet_hash IS NULL THEN c.native_top_depth END,
+       CASE WHEN c.gridded_parquet_hash IS NULL THEN c.native_bottom_depth END,
+       c.null_count
+  FROM curves c
+  LEFT JOIN curve_properties cp ON c.property_id = cp.id"#;
+
+/// The `METADATA_COLUMNS` of a row.
+struct MetadataRow {
+    mnemonic: String,
+    unit: Option<String>,
+    row_count: i64,
+    property_id: Option<String>,
+    top_depth: Option<f64>,
+    bottom_depth: Option<f64>,
+    null_count: Option<i64>,
+}
+
+impl MetadataRow {
+    /// Read the columns starting at index `offset`.
+    fn read(row: &rusqlite::Row<'_>, offset: usize) -> rusqlite::Result<Self> {
+        Ok(Self {
+            mnemonic: row.get(offset)?,
+            unit: row.get(offset + 1)?,
+            row_count: row.get(offset + 2)?,
+            property_id: row.get(offset + 3)?,
+            top_depth: row.get(offset + 4)?,
+            bottom_depth: row.get(offset + 5)?,
+            null_count: row.get(offset + 6)?,
         })
     }
 }
 
-/// Convert DataForge property_id to MainCurveType code
-fn property_id_to_curve_type_code(property_id: &str) -> String {
-    match property_id {
-        "gamma_ray" => "GR".to_string(),
-        "bulk_density" => "RHOB".to_string(),
-        "neutron_porosity" => "NPHI".to_string(),
-        "deep_resistivity" | "medium_resistivity" | "shallow_resistivity" => "RT".to_string(),
-        "caliper" => "CALI".to_string(),
-        "compressional_slowness" | "shear_slowness" => "DT".to_string(),
-        "spontaneous_potential" => "SP".to_string(),
-        "photoelectric" => "PE".to_string(),
-        "depth" => "DEPTH".to_string(),
-        _ => "OTHER".to_string(),
+/// Load the well tops of a well, shallowest first.
+///
+/// Databases without a `markers` table have no well tops.
+pub fn load_well_markers(db: &Connection, well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+    if !has_table(db, "markers")? {
+        return Ok(Vec::new());
+    }
+
+    let not_deleted = if has_column(db, "markers", "deleted_at")? {
+        " AND deleted_at IS NULL"
+    } else {
+        ""
+    };
+    let mut stmt = db.prepare(&format!(
+        r#"SELECT id, name, measured_depth, tvd, marker_type, quality, comments
+           FROM markers
+           WHERE well_id = ?1{}
+           ORDER BY measured_depth"#,
+        not_deleted
+    ))?;
+
+    let markers = stmt
+        .query_map([well_id.to_string()], |row| {
+            Ok(WellMarker {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                measured_depth: row.get(2)?,
+                tvd: row.get(3)?,
+                marker_type: row.get(4)?,
+                quality: row.get(5)?,
+                comments: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(markers)
+}
+
+/// Load the depth unit of a well.
+///
+/// Read from the well when the database records it, otherwise from the
+/// unit of the well's depth index curve.
+pub fn load_well_depth_unit(db: &Connection, well_id: Uuid) -> Result<Option<String>, UdfError> {
+    if has_column(db, "wells", "depth_unit")? {
+        let unit: Option<String> = db
+            .query_row(
+                "SELECT depth_unit FROM wells WHERE id = ?1",
+                [well_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        if unit.is_some() {
+            return Ok(unit);
+        }
     }
+
+    let unit = db
+        .query_row(
+            r#"SELECT unit FROM curves
+               WHERE well_id = ?1 AND unit IS NOT NULL
+                 AND UPPER(mnemonic) IN ('DEPT', 'DEPTH', 'MD')
+               LIMIT 1"#,
+            [well_id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(unit)
 }
 
 /// Schema for storing execution records.
@@ -264,8 +434,11 @@ CREATE TABLE IF NOT EXISTS execution_records (
     udf_version TEXT NOT NULL,
     inputs TEXT NOT NULL,           -- JSON array of InputReference
     parameters TEXT NOT NULL,       -- JSON object of parameter values
+    parameter_conversions TEXT,     -- JSON array of ParameterConversion
+    depth_intervals TEXT,           -- JSON object of DepthInterval by parameter
     output_curve_id TEXT,
     output_parquet_hash TEXT,
+    outputs TEXT,                   -- JSON array of OutputReference
     started_at TEXT NOT NULL,       -- ISO 8601 timestamp
     completed_at TEXT,              -- ISO 8601 timestamp
     compute_app_version TEXT NOT NULL,
@@ -280,33 +453,45 @@ CREATE INDEX IF NOT EXISTS idx_execution_records_status ON execution_records(sta
 "#;
 
 /// Save an execution record to the database.
+///
+/// Saving a record that already exists updates its outputs and status, so
+/// an execution can be committed after it was first recorded.
 pub fn save_execution_record(
     db: &Connection,
     record: &crate::compute::types::ExecutionRecord,
 ) -> Result<(), UdfError> {
     let inputs_json = serde_json::to_string(&record.inputs)?;
+    let outputs_json = serde_json::to_string(&record.outputs)?;
     let params_json = record.parameters.to_string();
-    let status = match record.status {
-        crate::compute::types::ExecutionStatus::Completed => "completed",
-        crate::compute::types::ExecutionStatus::Failed => "failed",
-        crate::compute::types::ExecutionStatus::Cancelled => "cancelled",
-    };
+    let conversions_json = serde_json::to_string(&record.parameter_conversions)?;
+    let intervals_json = serde_json::to_string(&record.depth_intervals)?;
+    let status = record.status.as_str();
 
     db.execute(
         "INSERT INTO execution_records (
-            id, udf_id, udf_version, inputs, parameters,
-            output_curve_id, output_parquet_hash,
+            id, udf_id, udf_version, inputs, parameters, parameter_conversions,
+            depth_intervals, output_curve_id, output_parquet_hash, outputs,
             started_at, completed_at, compute_app_version,
             status, error_message
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        ON CONFLICT(id) DO UPDATE SET
+            output_curve_id = excluded.output_curve_id,
+            output_parquet_hash = excluded.output_parquet_hash,
+            outputs = excluded.outputs,
+            completed_at = excluded.completed_at,
+            status = excluded.status,
+            error_message = excluded.error_message",
         rusqlite::params![
             record.id.to_string(),
             record.udf_id,
             record.udf_version,
             inputs_json,
             params_json,
+            conversions_json,
+            intervals_json,
             record.output_curve_id.map(|u| u.to_string()),
             record.output_parquet_hash,
+            outputs_json,
             record.started_at.to_rfc3339(),
             record.completed_at.map(|t| t.to_rfc3339()),
             record.compute_app_version,
@@ -321,5 +506,21 @@ pub fn save_execution_record(
 /// Initialize the compute database schema.
 pub fn init_compute_schema(db: &Connection) -> Result<(), UdfError> {
     db.execute_batch(EXECUTION_RECORDS_SCHEMA)?;
+
+    // Tables created before multi-output support lack the outputs column
+    if !has_column(db, "execution_records", "outputs")? {
+        db.execute_batch("ALTER TABLE execution_records ADD COLUMN outputs TEXT;")?;
+    }
+
+    // Nor do those created before numeric parameters were converted
+    if !has_column(db, "execution_records", "parameter_conversions")? {
+        db.execute_batch("ALTER TABLE execution_records ADD COLUMN parameter_conversions TEXT;")?;
+    }
+
+    // Nor those created before interval parameters were recorded
+    if !has_column(db, "execution_records", "depth_intervals")? {
+        db.execute_batch("ALTER TABLE execution_records ADD COLUMN depth_intervals TEXT;")?;
+    }
+
     Ok(())
 }
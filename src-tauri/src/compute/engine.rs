@@ -2,22 +2,42 @@
 //!
 //! The ExecutionEngine orchestrates UDF execution with multi-stage validation:
 //! 1. GUI validation (frontend - not in this module)
-//! 2. Parameter definition validation
+//! 2. Parameter definition and cross-parameter constraint validation
 //! 3. UDF-level validation (check_parameters)
 //! 4. Pre-execution hook (prepare)
-//! 5. Execution
+//! 5. Execution (or reuse of a cached result, see `cache`)
 //! 6. Post-execution hook (postprocess)
+//!
+//! Every UDF accepts depth range and zone parameters; executions restricted
+//! by them see inputs and produce outputs masked to that interval (see
+//! `interval`). Sample counts and nulls of the loaded curves are checked
+//! against their parameters before the UDF runs (see `nulls`), and converted
+//! to the unit their parameter expects (see `units`). Numeric parameters
+//! targeting a curve's unit or the well's depth unit are converted to it.
+//! Curves on different depth grids are resampled onto common depths if the
+//! execution picks an alignment strategy (see `alignment`).
 
-use crate::compute::context::{ExecutionContext, ExecutionContextBuilder};
+use crate::compute::alignment::{self, AlignmentStrategy, Interpolation};
+use crate::compute::cache::{cache_key, ResultCache};
+use crate::compute::constraints::ParameterConstraint;
+use crate::compute::context::{
+    CancellationToken, ExecutionContext, ExecutionContextBuilder, ProgressState,
+};
 use crate::compute::error::{UdfError, ValidationError};
+use crate::compute::interval::{self, DepthInterval};
+use crate::compute::nulls::{self, CurveInput, NullPolicy, RowMask};
 use crate::compute::parameters::{CurveParameter, ParameterDefinition, ParameterValue, ParameterValues};
+use crate::compute::preflight;
 use crate::compute::registry::UdfRegistry;
 use crate::compute::types::{
-    CurveData, CurveDataType, ExecutionRecord, ExecutionStatus, UdfOutput,
+    CurveData, CurveDataType, ExecutionRecord, ExecutionStatus, OutputDeclaration, UdfMetadata,
+    UdfOutput,
 };
+use crate::compute::units::{self, ParameterConversion, UnitConversion};
 use crate::compute::Udf;
 use chrono::Utc;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -30,6 +50,123 @@ pub struct ExecutionResult {
     pub output: Option<UdfOutput>,
 }
 
+/// Output of the execution stages, before post-processing.
+struct StagedOutput {
+    context: ExecutionContext,
+    output: UdfOutput,
+    cache_key: String,
+    /// Execution the output was cached from, if the UDF did not run
+    cached_from: Option<Uuid>,
+    /// Depth interval the execution is restricted to
+    interval: Option<DepthInterval>,
+    /// Interval each depth range or zone parameter resolved to
+    intervals: BTreeMap<String, DepthInterval>,
+    /// Null samples hidden from the UDF by the mask null policy
+    row_mask: Option<RowMask>,
+    /// Numeric parameters converted to the unit of a curve or the depths
+    parameter_conversions: Vec<ParameterConversion>,
+}
+
+/// Per-execution options passed to the engine.
+///
+/// Holds the shared handles used to control a running execution from
+/// outside (e.g. the `cancel_execution` and `get_execution_progress`
+/// commands).
+#[derive(Clone, Default)]
+pub struct ExecutionOptions {
+    /// Cancellation token checked between stages and inside UDF loops
+    pub cancellation_token: Arc<CancellationToken>,
+    /// Progress state updated by the engine and the UDF
+    pub progress_state: Arc<ProgressState>,
+    /// Execution ID to record (a new ID is generated if not set)
+    pub execution_id: Option<Uuid>,
+    /// Cache consulted before running the UDF and updated after
+    pub result_cache: Option<Arc<dyn ResultCache>>,
+    /// Run the UDF even if a cached result exists (the cache is refreshed)
+    pub force_recompute: bool,
+    /// Recorded intervals of depth range and zone parameters, used instead
+    /// of resolving them against the well's current tops
+    pub depth_intervals: BTreeMap<String, DepthInterval>,
+}
+
+impl std::fmt::Debug for ExecutionOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionOptions")
+            .field("cancellation_token", &self.cancellation_token)
+            .field("progress_state", &self.progress_state)
+            .field("execution_id", &self.execution_id)
+            .field("result_cache", &self.result_cache.is_some())
+            .field("force_recompute", &self.force_recompute)
+            .field("depth_intervals", &self.depth_intervals)
+            .finish()
+    }
+}
+
+impl ExecutionOptions {
+    /// Create options with fresh cancellation and progress handles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a shared cancellation token.
+    pub fn with_cancellation_token(mut self, token: Arc<CancellationToken>) -> Self {
+        self.cancellation_token = token;
+        self
+    }
+
+    /// Use a shared progress state.
+    pub fn with_progress_state(mut self, state: Arc<ProgressState>) -> Self {
+        self.progress_state = state;
+        self
+    }
+
+    /// Record the execution under a caller-assigned ID.
+    ///
+    /// Used when the ID is handed to the frontend before the run starts,
+    /// so progress tracking and provenance refer to the same execution.
+    pub fn with_execution_id(mut self, execution_id: Uuid) -> Self {
+        self.execution_id = Some(execution_id);
+        self
+    }
+
+    /// Reuse and store results in a cache.
+    pub fn with_result_cache(mut self, cache: Arc<dyn ResultCache>) -> Self {
+        self.result_cache = Some(cache);
+        self
+    }
+
+    /// Run the UDF even if the cache holds a result for the same inputs.
+    pub fn with_force_recompute(mut self, force: bool) -> Self {
+        self.force_recompute = force;
+        self
+    }
+
+    /// Resolve interval parameters to the depths an execution recorded.
+    ///
+    /// Used when re-running a recorded execution, so it is restricted to
+    /// the same depths even if the well's tops have moved since.
+    pub fn with_depth_intervals(mut self, intervals: BTreeMap<String, DepthInterval>) -> Self {
+        self.depth_intervals = intervals;
+        self
+    }
+
+    /// Options for one step of a batch or workflow run.
+    ///
+    /// The step shares cancellation and caching with the run and reports
+    /// progress into its slice of the run's progress.
+    pub fn for_step(&self, index: usize, total: usize) -> Self {
+        Self {
+            progress_state: Arc::new(ProgressState::for_step(
+                self.progress_state.clone(),
+                index,
+                total,
+            )),
+            execution_id: None,
+            ..self.clone()
+        }
+    }
+}
+
 /// Execution engine for running UDFs with validation.
 pub struct ExecutionEngine {
     /// Reference to the UDF registry
@@ -50,7 +187,11 @@ impl ExecutionEngine {
     /// Execute a UDF with the given parameters and curve data.
     ///
     /// This is the main entry point for UDF execution. It performs
-    /// multi-stage validation and tracks provenance.
+    /// multi-stage validation and tracks provenance. Cancellation requested
+    /// through `options.cancellation_token` produces a record with
+    /// `ExecutionStatus::Cancelled`. With `options.result_cache` set, a
+    /// previous output for the same UDF version, parameters and input
+    /// content is reused and recorded in `cached_from`.
     pub fn execute(
         &self,
         udf_id: &str,
@@ -58,6 +199,7 @@ impl ExecutionEngine {
         workspace_id: Uuid,
         parameters: HashMap<String, ParameterValue>,
         curve_loader: &dyn CurveLoader,
+        options: &ExecutionOptions,
     ) -> Result<ExecutionResult, UdfError> {
         let started_at = Utc::now();
 
@@ -71,37 +213,99 @@ impl ExecutionEngine {
 
         // Create initial execution record
         let mut record = ExecutionRecord {
-            id: Uuid::new_v4(),
+            id: options.execution_id.unwrap_or_else(Uuid::new_v4),
             udf_id: udf_id.to_string(),
             udf_version: metadata.version.clone(),
+            well_id,
+            workspace_id,
             inputs: Vec::new(),
             parameters: serde_json::to_value(&parameters).unwrap_or(serde_json::Value::Null),
+            parameter_conversions: Vec::new(),
+            depth_intervals: BTreeMap::new(),
             output_curve_id: None,
             output_parquet_hash: None,
+            outputs: Vec::new(),
             started_at,
             completed_at: None,
             compute_app_version: self.app_version.clone(),
             status: ExecutionStatus::Failed,
             error_message: None,
+            workflow: None,
+            cache_key: None,
+            cached_from: None,
         };
 
         // Execute with error handling
-        match self.execute_inner(&udf, well_id, workspace_id, parameters, curve_loader) {
-            Ok((mut context, mut output)) => {
-                // Post-process
-                if let Err(e) = udf.postprocess(&mut output, &context) {
-                    record.completed_at = Some(Utc::now());
-                    record.error_message = Some(e.to_string());
-                    return Ok(ExecutionResult {
-                        record,
-                        output: None,
-                    });
+        match self.execute_inner(&udf, &record, parameters, curve_loader, options) {
+            Ok(staged) => {
+                let StagedOutput {
+                    context,
+                    mut output,
+                    cache_key,
+                    cached_from,
+                    interval,
+                    intervals,
+                    row_mask,
+                    parameter_conversions,
+                } = staged;
+                record.cache_key = Some(cache_key.clone());
+
+                // Post-process, then check the outputs match the declarations.
+                // Cached outputs went through both when they were produced.
+                if cached_from.is_none() {
+                    let postprocessed = udf
+                        .postprocess(&mut output, &context)
+                        .and_then(|_| check_outputs(&metadata.outputs, &output));
+                    if let Err(e) = postprocessed {
+                        record.completed_at = Some(Utc::now());
+                        record.status = status_for_error(&e);
+                        record.error_message = Some(e.to_string());
+                        return Ok(ExecutionResult {
+                            record,
+                            output: None,
+                        });
+                    }
+
+                    // Put outputs back on the depths the null policy masked
+                    if let Some(row_mask) = &row_mask {
+                        for produced in &mut output.outputs {
+                            row_mask.expand_output(&mut produced.curve_data);
+                        }
+                        output.metadata.insert(
+                            "masked_null_samples".to_string(),
+                            serde_json::json!(row_mask.masked_count()),
+                        );
+                    }
+
+                    // Restrict outputs to the interval, whatever the UDF did
+                    // outside it
+                    if let Some(interval) = &interval {
+                        for produced in &mut output.outputs {
+                            interval::mask_output(&mut produced.curve_data, interval);
+                        }
+                        output.metadata.insert(
+                            "depth_interval".to_string(),
+                            serde_json::to_value(interval).unwrap_or(serde_json::Value::Null),
+                        );
+                    }
                 }
 
                 // Update record with success info
                 record.inputs = context.input_refs().to_vec();
+                record.parameter_conversions = parameter_conversions;
+                record.depth_intervals = intervals;
+                record.cached_from = cached_from;
                 record.status = ExecutionStatus::Completed;
                 record.completed_at = Some(Utc::now());
+                options
+                    .progress_state
+                    .set_progress_with_message(100.0, "Completed");
+
+                if let (Some(cache), None) = (&options.result_cache, cached_from) {
+                    if let Err(e) = cache.store(&cache_key, &record, &output) {
+                        log::warn!("⚠️ Failed to cache result of {}: {}", record.id, e);
+                    }
+                }
 
                 Ok(ExecutionResult {
                     record,
@@ -110,6 +314,7 @@ impl ExecutionEngine {
             }
             Err(e) => {
                 record.completed_at = Some(Utc::now());
+                record.status = status_for_error(&e);
                 record.error_message = Some(e.to_string());
                 Ok(ExecutionResult {
                     record,
@@ -123,16 +328,23 @@ impl ExecutionEngine {
     fn execute_inner(
         &self,
         udf: &Arc<dyn Udf>,
-        well_id: Uuid,
-        workspace_id: Uuid,
+        record: &ExecutionRecord,
         parameters: HashMap<String, ParameterValue>,
         curve_loader: &dyn CurveLoader,
-    ) -> Result<(ExecutionContext, UdfOutput), UdfError> {
-        let param_defs = udf.parameter_definitions();
-        let param_values = ParameterValues::from_map(parameters.clone());
+        options: &ExecutionOptions,
+    ) -> Result<StagedOutput, UdfError> {
+        let param_defs = parameter_definitions(udf.as_ref());
+        let cancellation_token = &options.cancellation_token;
+        let progress_state = &options.progress_state;
+
+        if cancellation_token.is_cancelled() {
+            return Err(UdfError::Cancelled);
+        }
+        progress_state.set_progress_with_message(0.0, "Validating parameters");
 
-        // Stage 1: Parameter definition validation
-        let validation_errors = self.validate_parameters(&param_defs, &parameters)?;
+        // Stage 1: Parameter definition validation (constraints are checked
+        // once the values are converted to the unit the UDF works in)
+        let validation_errors = self.validate_parameters(&param_defs, &[], &parameters)?;
         if !validation_errors.is_empty() {
             return Err(UdfError::ParameterValidation(
                 validation_errors
@@ -143,33 +355,183 @@ impl ExecutionEngine {
             ));
         }
 
-        // Stage 2: Load curve data and validate types
-        let mut context_builder = ExecutionContextBuilder::new(well_id, workspace_id)
-            .with_parameters(param_values);
+        // Stage 2: Resolve depth interval restrictions
+        let intervals = interval::resolve_intervals(
+            &param_defs,
+            &parameters,
+            &options.depth_intervals,
+            record.well_id,
+            curve_loader,
+        )?;
+        let depth_interval = interval::combine_intervals(&intervals)?;
+
+        // Stage 3: Load curve data and validate types
+        progress_state.set_progress_with_message(0.0, "Loading input curves");
+        let mut context_builder = ExecutionContextBuilder::new(record.well_id, record.workspace_id)
+            .with_cancellation_token(cancellation_token.clone())
+            .with_progress_state(progress_state.clone())
+            .with_depth_interval(depth_interval);
 
+        let mut inputs = Vec::new();
         for def in &param_defs {
             if def.param_type() == "curve" {
                 if let Some(value) = parameters.get(def.name()) {
                     if let Some(curve_id) = value.as_curve() {
-                        // Load the curve
-                        let curve = curve_loader.load_curve(curve_id)?;
+                        if cancellation_token.is_cancelled() {
+                            return Err(UdfError::Cancelled);
+                        }
+
+                        // Load, check, convert and mask the curve
+                        let (curve, conversion) = load_input_curve(
+                            def.as_ref(),
+                            curve_id,
+                            curve_loader,
+                            depth_interval.as_ref(),
+                        )?;
+                        if let Some(conversion) = conversion {
+                            context_builder =
+                                context_builder.with_unit_conversion(curve.curve_id, conversion);
+                        }
+
+                        inputs.push(CurveInput {
+                            def: def.as_ref(),
+                            curves: vec![curve],
+                        });
+                    }
+                }
+            } else if def.param_type() == "curve_list" {
+                let curve_ids = parameters
+                    .get(def.name())
+                    .and_then(|value| value.as_curve_list())
+                    .unwrap_or_default();
+                if curve_ids.is_empty() {
+                    continue;
+                }
 
-                        // Validate curve type if this is a CurveParameter
-                        // We need to downcast to check allowed_types
-                        self.validate_curve_type(def.as_ref(), &curve)?;
+                let mut curves = Vec::with_capacity(curve_ids.len());
+                for curve_id in curve_ids {
+                    if cancellation_token.is_cancelled() {
+                        return Err(UdfError::Cancelled);
+                    }
 
-                        context_builder = context_builder.with_curve(def.name(), curve);
+                    // Every listed curve must satisfy the type constraints
+                    let (curve, conversion) = load_input_curve(
+                        def.as_ref(),
+                        *curve_id,
+                        curve_loader,
+                        depth_interval.as_ref(),
+                    )?;
+                    if let Some(conversion) = conversion {
+                        context_builder =
+                            context_builder.with_unit_conversion(curve.curve_id, conversion);
                     }
+                    curves.push(curve);
                 }
+
+                inputs.push(CurveInput {
+                    def: def.as_ref(),
+                    curves,
+                });
+            }
+        }
+
+        // Convert numeric parameters to the unit of their curve or the depths
+        let curve_units: HashMap<&str, &str> = inputs
+            .iter()
+            .filter_map(|input| Some((input.def.name(), input.curves.first()?.unit.as_str())))
+            .collect();
+        let (converted_parameters, parameter_conversions) = units::convert_parameters(
+            &param_defs,
+            &parameters,
+            &curve_units,
+            record.well_id,
+            curve_loader,
+        )?;
+        let constraint_errors = check_constraints(
+            &param_defs,
+            &udf.parameter_constraints(),
+            &converted_parameters,
+            &[],
+        );
+        if !constraint_errors.is_empty() {
+            return Err(UdfError::ParameterValidation(
+                constraint_errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ));
+        }
+
+        // Resample curves on other depth grids, as the alignment parameters pick
+        let alignments = alignment::align(
+            &mut inputs,
+            &AlignmentStrategy::from_parameters(&converted_parameters),
+            Interpolation::from_parameters(&converted_parameters),
+        )?;
+        if let (Some(interval), false) = (&depth_interval, alignments.is_empty()) {
+            // Resampling may have filled samples outside the interval
+            for curve in inputs.iter_mut().flat_map(|input| &mut input.curves) {
+                *curve = Arc::new(interval::mask_curve(curve, interval));
             }
         }
+        for (curve_id, alignment) in alignments {
+            context_builder = context_builder.with_depth_alignment(curve_id, alignment);
+        }
+        context_builder =
+            context_builder.with_parameters(ParameterValues::from_map(converted_parameters));
+
+        // Enforce sample counts and nulls, applying the null policy
+        let row_mask = nulls::enforce(
+            &mut inputs,
+            NullPolicy::from_parameters(&parameters),
+            depth_interval.as_ref(),
+        )
+        .map_err(|errors| {
+            UdfError::ParameterValidation(
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            )
+        })?;
+
+        for CurveInput { def, mut curves } in inputs {
+            context_builder = if def.param_type() == "curve_list" {
+                context_builder.with_curve_list(def.name(), curves)
+            } else {
+                context_builder.with_curve(def.name(), curves.remove(0))
+            };
+        }
 
         let mut context = context_builder.build();
 
-        // Stage 3: Validate depth compatibility
+        // Interval parameters are keyed by the depths they resolved to, so
+        // moving a well top invalidates cached results of its zones
+        let mut keyed_parameters = parameters.clone();
+        for (name, interval) in &intervals {
+            keyed_parameters.insert(
+                name.clone(),
+                ParameterValue::DepthRange {
+                    top: interval.top,
+                    base: interval.base,
+                },
+            );
+        }
+        let cache_key = cache_key(
+            &record.udf_id,
+            &record.udf_version,
+            &keyed_parameters,
+            context.curves(),
+            context.curve_lists(),
+            context.input_refs(),
+        );
+
+        // Stage 4: Validate depth compatibility (including every listed curve)
         context.validate_depth_compatibility()?;
 
-        // Stage 4: UDF-level parameter validation
+        // Stage 5: UDF-level parameter validation
         if let Err(errors) = udf.check_parameters(&context) {
             return Err(UdfError::ParameterValidation(
                 errors
@@ -180,30 +542,65 @@ impl ExecutionEngine {
             ));
         }
 
-        // Stage 5: Check if UDF can execute
+        // Stage 6: Check if UDF can execute
         if !udf.can_execute(&context) {
             return Err(UdfError::PreCheckFailed(
                 "UDF cannot execute in current context".to_string(),
             ));
         }
 
-        // Stage 6: Pre-execution hook
+        // Stage 7: Pre-execution hook
         if !udf.prepare(&mut context)? {
             return Err(UdfError::PreCheckFailed(
                 "Pre-execution check failed".to_string(),
             ));
         }
 
-        // Stage 7: Execute
+        // Stage 8: Execute, unless the cache already holds the result
+        context.check_cancelled()?;
+        if let (Some(cache), false) = (&options.result_cache, options.force_recompute) {
+            match cache.lookup(&cache_key) {
+                Ok(Some(cached)) => {
+                    return Ok(StagedOutput {
+                        context,
+                        output: cached.output,
+                        cache_key,
+                        cached_from: Some(cached.execution_id),
+                        interval: depth_interval,
+                        intervals: intervals.into_iter().collect(),
+                        row_mask,
+                        parameter_conversions,
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("⚠️ Result cache lookup failed: {}", e),
+            }
+        }
+
+        context.set_progress_with_message(0.0, format!("Running {}", udf.metadata().name));
         let output = udf.execute(&context)?;
+        context.check_cancelled()?;
 
-        Ok((context, output))
+        Ok(StagedOutput {
+            context,
+            output,
+            cache_key,
+            cached_from: None,
+            interval: depth_interval,
+            intervals: intervals.into_iter().collect(),
+            row_mask,
+            parameter_conversions,
+        })
     }
 
-    /// Validate parameters against their definitions.
+    /// Validate parameters against their definitions and constraints.
+    ///
+    /// Constraints are evaluated as `check_constraints` does, skipping those
+    /// that involve a parameter already in error.
     fn validate_parameters(
         &self,
         definitions: &[Box<dyn ParameterDefinition>],
+        constraints: &[ParameterConstraint],
         values: &HashMap<String, ParameterValue>,
     ) -> Result<Vec<ValidationError>, UdfError> {
         let mut errors = Vec::new();
@@ -236,60 +633,64 @@ impl ExecutionEngine {
             }
         }
 
+        let constraint_errors = check_constraints(definitions, constraints, values, &errors);
+        errors.extend(constraint_errors);
         Ok(errors)
     }
 
-    /// Validate that a curve matches the type constraints of a curve parameter.
-    fn validate_curve_type(
+    /// Validate parameters without executing (for UI feedback).
+    pub fn validate_only(
         &self,
-        def: &dyn ParameterDefinition,
-        curve: &CurveData,
-    ) -> Result<(), UdfError> {
-        // This is a bit of a hack since we can't downcast trait objects easily.
-        // The to_json() method includes allowed_types, so we can check there.
-        let json = def.to_json();
-
-        if let Some(allowed_types) = json.get("allowed_types").and_then(|v| v.as_array()) {
-            if allowed_types.is_empty() {
-                return Ok(()); // Any type allowed
-            }
+        udf_id: &str,
+        parameters: &HashMap<String, ParameterValue>,
+    ) -> Result<Vec<ValidationError>, UdfError> {
+        let udf = self
+            .registry
+            .get_udf(udf_id)
+            .ok_or_else(|| UdfError::UdfNotFound(udf_id.to_string()))?;
 
-            let curve_type_name = curve.curve_type.display_name();
-            let is_allowed = allowed_types
-                .iter()
-                .filter_map(|v| v.as_str())
-                .any(|t| t == curve_type_name);
+        let param_defs = parameter_definitions(udf.as_ref());
+        self.validate_parameters(&param_defs, &udf.parameter_constraints(), parameters)
+    }
 
-            if !is_allowed {
-                let allowed_str = allowed_types
-                    .iter()
-                    .filter_map(|v| v.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", ");
+    /// Validate parameters and the curves bound to them without executing.
+    ///
+    /// Like `validate_only`, but also checks each curve's type, sample count
+    /// and nulls, and estimates depth compatibility, from curve metadata
+    /// alone (see `preflight`).
+    pub fn preflight(
+        &self,
+        udf_id: &str,
+        parameters: &HashMap<String, ParameterValue>,
+        curve_loader: &dyn CurveLoader,
+    ) -> Result<Vec<ValidationError>, UdfError> {
+        let udf = self
+            .registry
+            .get_udf(udf_id)
+            .ok_or_else(|| UdfError::UdfNotFound(udf_id.to_string()))?;
 
-                return Err(UdfError::CurveTypeMismatch {
-                    expected: allowed_str,
-                    actual: curve_type_name.to_string(),
-                });
+        let param_defs = parameter_definitions(udf.as_ref());
+        let mut errors =
+            self.validate_parameters(&param_defs, &udf.parameter_constraints(), parameters)?;
+
+        // Curves of parameters already in error are not checked further
+        for error in preflight::check_curves(&param_defs, parameters, curve_loader) {
+            if !errors.iter().any(|e| e.field == error.field) {
+                errors.push(error);
             }
         }
 
-        Ok(())
+        Ok(errors)
     }
 
-    /// Validate parameters without executing (for UI feedback).
-    pub fn validate_only(
-        &self,
-        udf_id: &str,
-        parameters: &HashMap<String, ParameterValue>,
-    ) -> Result<Vec<ValidationError>, UdfError> {
+    /// Get metadata (including declared outputs) for a UDF.
+    pub fn get_udf_metadata(&self, udf_id: &str) -> Result<UdfMetadata, UdfError> {
         let udf = self
             .registry
             .get_udf(udf_id)
             .ok_or_else(|| UdfError::UdfNotFound(udf_id.to_string()))?;
 
-        let param_defs = udf.parameter_definitions();
-        self.validate_parameters(&param_defs, parameters)
+        Ok(udf.metadata())
     }
 
     /// Get parameter definitions for a UDF.
@@ -302,7 +703,194 @@ impl ExecutionEngine {
             .get_udf(udf_id)
             .ok_or_else(|| UdfError::UdfNotFound(udf_id.to_string()))?;
 
-        Ok(udf.parameter_definitions().iter().map(|d| d.to_json()).collect())
+        let definitions = parameter_definitions(udf.as_ref());
+        let constraints = udf.parameter_constraints();
+
+        Ok(definitions
+            .iter()
+            .map(|d| {
+                let mut json = d.to_json();
+                let declared: Vec<serde_json::Value> = constraints
+                    .iter()
+                    .filter(|c| c.parameters().first() == Some(&d.name()))
+                    .map(|c| c.to_json(&definitions))
+                    .collect();
+                if !declared.is_empty() {
+                    json["constraints"] = serde_json::Value::Array(declared);
+                }
+                json
+            })
+            .collect())
+    }
+}
+
+/// Parameter definitions of a UDF, including those the engine adds.
+fn parameter_definitions(udf: &dyn Udf) -> Vec<Box<dyn ParameterDefinition>> {
+    alignment::with_alignment_parameters(nulls::with_null_policy_parameter(
+        interval::with_interval_parameters(udf.parameter_definitions()),
+    ))
+}
+
+/// Evaluate cross-parameter constraints on the values after defaults were
+/// applied, skipping those that involve a parameter in `errors`.
+///
+/// Quantities are compared in the unit of their parameter, so values
+/// entered in different units of a family are ordered correctly. Those of
+/// parameters without a unit (e.g. in the well's depth unit) can only be
+/// compared once converted, so their constraints are skipped until then.
+fn check_constraints(
+    definitions: &[Box<dyn ParameterDefinition>],
+    constraints: &[ParameterConstraint],
+    values: &HashMap<String, ParameterValue>,
+    errors: &[ValidationError],
+) -> Vec<ValidationError> {
+    let mut resolved = HashMap::new();
+    let mut unresolved = HashSet::new();
+    for def in definitions {
+        let value = values
+            .get(def.name())
+            .filter(|value| !value.is_null())
+            .cloned()
+            .or_else(|| def.default_value());
+        match value.map(|value| in_parameter_unit(def.as_ref(), value)) {
+            Some(Some(value)) => {
+                resolved.insert(def.name().to_string(), value);
+            }
+            Some(None) => {
+                unresolved.insert(def.name());
+            }
+            None => {}
+        }
+    }
+
+    constraints
+        .iter()
+        .filter(|constraint| {
+            !constraint
+                .parameters()
+                .iter()
+                .any(|name| unresolved.contains(name) || errors.iter().any(|e| e.field == *name))
+        })
+        .filter_map(|constraint| constraint.evaluate(definitions, &resolved).err())
+        .collect()
+}
+
+/// A value with quantities as numbers in their parameter's unit, or `None`
+/// for quantities of parameters without a unit to convert them to.
+fn in_parameter_unit(
+    def: &dyn ParameterDefinition,
+    value: ParameterValue,
+) -> Option<ParameterValue> {
+    let Some((number, unit)) = value.as_quantity() else {
+        return Some(value);
+    };
+    let own = def.value_unit()?.unit?;
+    let conversion = units::conversion(unit, &own).ok()?;
+    Some(ParameterValue::Number(
+        conversion.map_or(number, |c| c.apply(number)),
+    ))
+}
+
+/// Check the type, property and unit of a curve against its parameter.
+pub(crate) fn check_curve(
+    def: &dyn ParameterDefinition,
+    curve_type: CurveDataType,
+    property_id: Option<&str>,
+    unit: &str,
+) -> Result<(), UdfError> {
+    match def.curve_constraints() {
+        Some(constraints) => constraints.check(curve_type, property_id, unit),
+        None => Ok(()),
+    }
+}
+
+/// Load the curve bound to a curve or curve list parameter.
+///
+/// The curve is checked against the parameter's constraints, converted to
+/// the unit the parameter expects and masked to the execution's interval.
+/// Returns it with the conversion applied, if any.
+fn load_input_curve(
+    def: &dyn ParameterDefinition,
+    curve_id: Uuid,
+    curve_loader: &dyn CurveLoader,
+    depth_interval: Option<&DepthInterval>,
+) -> Result<(Arc<CurveData>, Option<UnitConversion>), UdfError> {
+    let curve = curve_loader.load_curve(curve_id)?;
+    check_curve(
+        def,
+        curve.curve_type,
+        curve.property_id.as_deref(),
+        &curve.unit,
+    )?;
+
+    let conversion = unit_conversion(def, &curve.unit)?;
+    let curve = match &conversion {
+        Some(conversion) => Arc::new(conversion.apply_to_curve(&curve)),
+        None => curve,
+    };
+
+    // Hide samples outside the interval from the UDF
+    let curve = match depth_interval {
+        Some(interval) => Arc::new(interval::mask_curve(&curve, interval)),
+        None => curve,
+    };
+
+    Ok((curve, conversion))
+}
+
+/// Conversion of a curve's values to the unit its parameter expects, if
+/// the parameter declares one and the curve is in another unit.
+fn unit_conversion(
+    def: &dyn ParameterDefinition,
+    unit: &str,
+) -> Result<Option<UnitConversion>, UdfError> {
+    match def.curve_constraints().and_then(|c| c.unit) {
+        Some(expected) => units::conversion(unit, &expected),
+        None => Ok(None),
+    }
+}
+
+/// Check that a UDF produced exactly its declared outputs.
+///
+/// UDFs without declarations may produce any set of uniquely named outputs.
+fn check_outputs(declared: &[OutputDeclaration], output: &UdfOutput) -> Result<(), UdfError> {
+    if output.outputs.is_empty() {
+        return Err(UdfError::ExecutionFailed(
+            "UDF produced no output curves".to_string(),
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    for produced in &output.outputs {
+        if !seen.insert(produced.name.as_str()) {
+            return Err(UdfError::ExecutionFailed(format!(
+                "UDF produced output '{}' more than once",
+                produced.name
+            )));
+        }
+        if !declared.is_empty() && !declared.iter().any(|d| d.name == produced.name) {
+            return Err(UdfError::ExecutionFailed(format!(
+                "UDF produced undeclared output '{}'",
+                produced.name
+            )));
+        }
+    }
+
+    if let Some(missing) = declared.iter().find(|d| !seen.contains(d.name.as_str())) {
+        return Err(UdfError::ExecutionFailed(format!(
+            "UDF did not produce declared output '{}'",
+            missing.name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Map an execution error to the status recorded for provenance.
+fn status_for_error(error: &UdfError) -> ExecutionStatus {
+    match error {
+        UdfError::Cancelled => ExecutionStatus::Cancelled,
+        _ => ExecutionStatus::Failed,
     }
 }
 
@@ -314,8 +902,51 @@ pub trait CurveLoader {
     /// Load curve data by ID.
     fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError>;
 
+    /// Load the data a curve had when its content hash was `parquet_hash`.
+    ///
+    /// Used to re-run an execution on its recorded inputs. The default
+    /// only succeeds while the curve still holds that data.
+    fn load_curve_version(
+        &self,
+        curve_id: Uuid,
+        parquet_hash: &str,
+    ) -> Result<Arc<CurveData>, UdfError> {
+        let curve = self.load_curve(curve_id)?;
+        if curve.parquet_hash != parquet_hash {
+            return Err(UdfError::CurveLoadError(format!(
+                "Curve {} no longer holds data {}",
+                curve_id, parquet_hash
+            )));
+        }
+        Ok(curve)
+    }
+
     /// Load curve metadata (type, unit, etc.) without loading values.
     fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError>;
+
+    /// List metadata for all curves of a well.
+    ///
+    /// Used to bind curve parameters per well in batch execution.
+    fn list_well_curves(&self, well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError>;
+
+    /// List the IDs of all wells in a workspace.
+    fn list_wells(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError>;
+
+    /// List the well tops (formation markers) of a well, shallowest first.
+    ///
+    /// Used to resolve zone parameters. The default has no markers, so
+    /// zones cannot be resolved.
+    fn list_well_markers(&self, _well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+        Ok(Vec::new())
+    }
+
+    /// Unit of the depths of a well's curves.
+    ///
+    /// Used to convert depth parameters. The default does not know it, so
+    /// depth parameters entered in a unit cannot be converted.
+    fn well_depth_unit(&self, _well_id: Uuid) -> Result<Option<String>, UdfError> {
+        Ok(None)
+    }
 }
 
 /// Minimal curve metadata for validation.
@@ -326,11 +957,868 @@ pub struct CurveMetadataInfo {
     pub curve_type: CurveDataType,
     pub unit: String,
     pub row_count: i64,
+    /// DataForge `curve_properties` ID (e.g., "gamma_ray"), if assigned
+    pub property_id: Option<String>,
+    /// Depth extent of the samples, if known
+    pub depth_range: Option<(f64, f64)>,
+    /// Number of null samples, if known
+    pub null_count: Option<i64>,
+}
+
+impl CurveMetadataInfo {
+    /// Metadata of already loaded curve data.
+    pub fn from_curve(curve: &CurveData) -> Self {
+        Self {
+            curve_id: curve.curve_id,
+            mnemonic: curve.mnemonic.clone(),
+            curve_type: curve.curve_type,
+            unit: curve.unit.clone(),
+            row_count: curve.len() as i64,
+            property_id: curve.property_id.clone(),
+            depth_range: curve.depth_range(),
+            null_count: Some(curve.values.iter().filter(|v| v.is_none()).count() as i64),
+        }
+    }
+}
+
+/// A well top (formation marker) as stored in DataForge.
+#[derive(Debug, Clone, Serialize)]
+pub struct WellMarker {
+    pub id: String,
+    pub name: String,
+    pub measured_depth: f64,
+    pub tvd: Option<f64>,
+    pub marker_type: Option<String>,
+    pub quality: Option<String>,
+    pub comments: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::compute::cache::CachedResult;
+    use crate::compute::parameters::NumericParameter;
+    use crate::compute::test_support::{create_engine, gr_curve, MockCurveLoader};
+    use crate::compute::types::OutputCurveData;
+    use crate::compute::UdfProvider;
+
+    fn vshale_parameters(gr_curve_id: Uuid) -> HashMap<String, ParameterValue> {
+        let mut params = HashMap::new();
+        params.insert("gr_curve".to_string(), ParameterValue::Curve(gr_curve_id));
+        params.insert("gr_min".to_string(), ParameterValue::Number(30.0));
+        params.insert("gr_max".to_string(), ParameterValue::Number(100.0));
+        params
+    }
+
+    #[test]
+    fn test_execute_completes_with_progress() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]);
+        let options = ExecutionOptions::new();
+
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                vshale_parameters(gr_id),
+                &loader,
+                &options,
+            )
+            .unwrap();
+
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        assert_eq!(result.record.inputs.len(), 1);
+        assert!(result.output.is_some());
+        assert_eq!(options.progress_state.get_progress(), 100);
+    }
+
+    #[test]
+    fn test_cancelled_execution_records_cancelled_status() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]);
+
+        let options = ExecutionOptions::new();
+        options.cancellation_token.cancel();
+
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                vshale_parameters(gr_id),
+                &loader,
+                &options,
+            )
+            .unwrap();
+
+        assert_eq!(result.record.status, ExecutionStatus::Cancelled);
+        assert!(result.output.is_none());
+    }
+
+    #[test]
+    fn test_udf_loop_observes_cancellation() {
+        let gr = Arc::new(gr_curve());
+        let token = Arc::new(CancellationToken::new());
+        let mut params = vshale_parameters(gr.curve_id);
+        params.remove("gr_curve");
+
+        let context = ExecutionContextBuilder::new(Uuid::new_v4(), Uuid::new_v4())
+            .with_parameters(ParameterValues::from_map(params))
+            .with_curve("gr_curve", gr)
+            .with_cancellation_token(token.clone())
+            .build();
+        token.cancel();
+
+        let udf = create_engine().registry.get_udf("petro:vshale_linear").unwrap();
+        assert!(matches!(udf.execute(&context), Err(UdfError::Cancelled)));
+    }
+
+    /// In-memory result cache for engine tests.
+    #[derive(Default)]
+    struct MemoryCache {
+        entries: std::sync::Mutex<HashMap<String, CachedResult>>,
+    }
+
+    impl ResultCache for MemoryCache {
+        fn lookup(&self, key: &str) -> Result<Option<CachedResult>, UdfError> {
+            Ok(self.entries.lock().unwrap().get(key).cloned())
+        }
+
+        fn store(
+            &self,
+            key: &str,
+            record: &ExecutionRecord,
+            output: &UdfOutput,
+        ) -> Result<(), UdfError> {
+            self.entries.lock().unwrap().insert(
+                key.to_string(),
+                CachedResult {
+                    execution_id: record.id,
+                    output: output.clone(),
+                },
+            );
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cached_result_is_reused_unless_forced() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]);
+        let options = ExecutionOptions::new().with_result_cache(Arc::new(MemoryCache::default()));
+        let run = |options: &ExecutionOptions| {
+            engine
+                .execute(
+                    "petro:vshale_linear",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    vshale_parameters(gr_id),
+                    &loader,
+                    options,
+                )
+                .unwrap()
+        };
+
+        let first = run(&options);
+        assert_eq!(first.record.status, ExecutionStatus::Completed);
+        assert!(first.record.cache_key.is_some());
+        assert!(first.record.cached_from.is_none());
+
+        let second = run(&options);
+        assert_eq!(second.record.status, ExecutionStatus::Completed);
+        assert_eq!(second.record.cache_key, first.record.cache_key);
+        assert_eq!(second.record.cached_from, Some(first.record.id));
+        assert_eq!(second.record.inputs.len(), 1);
+        assert_eq!(
+            second.output.unwrap().output("vshale").unwrap().values,
+            first.output.unwrap().output("vshale").unwrap().values
+        );
+
+        let forced = run(&options.clone().with_force_recompute(true));
+        assert!(forced.record.cached_from.is_none());
+
+        // The forced run refreshed the cache entry
+        let third = run(&options);
+        assert_eq!(third.record.cached_from, Some(forced.record.id));
+    }
+
+    fn output_curve(mnemonic: &str) -> OutputCurveData {
+        OutputCurveData {
+            mnemonic: mnemonic.to_string(),
+            curve_type: CurveDataType::Computed,
+            unit: "v/v".to_string(),
+            depths: vec![100.0],
+            values: vec![Some(0.5)],
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_check_outputs_against_declarations() {
+        let declared = vec![
+            OutputDeclaration::new("vshale", "Shale Volume"),
+            OutputDeclaration::new("flag", "Shale Flag"),
+        ];
+
+        let mut output = UdfOutput::new("vshale", output_curve("VSH"));
+        assert!(check_outputs(&declared, &output).is_err(), "missing flag output");
+
+        output.add_output("flag", output_curve("VSH_FLAG"));
+        assert!(check_outputs(&declared, &output).is_ok());
+        assert_eq!(output.output("flag").unwrap().mnemonic, "VSH_FLAG");
+        assert_eq!(output.primary().unwrap().name, "vshale");
+
+        output.add_output("extra", output_curve("EXTRA"));
+        assert!(check_outputs(&declared, &output).is_err(), "undeclared output");
+
+        // Without declarations any uniquely named outputs are accepted
+        let mut undeclared = UdfOutput::new("a", output_curve("A"));
+        undeclared.add_output("b", output_curve("B"));
+        assert!(check_outputs(&[], &undeclared).is_ok());
+        undeclared.add_output("a", output_curve("A2"));
+        assert!(check_outputs(&[], &undeclared).is_err(), "duplicate output name");
+    }
+
+    #[test]
+    fn test_depth_range_masks_inputs_and_outputs() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]);
+
+        let mut params = vshale_parameters(gr_id);
+        params.insert(
+            "depth_range".to_string(),
+            ParameterValue::DepthRange {
+                top: 100.5,
+                base: 101.2,
+            },
+        );
+
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                params,
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        let output = result.output.unwrap();
+        let values = &output.primary().unwrap().curve_data.values;
+        assert_eq!(values.len(), 4);
+        assert!(values[0].is_none());
+        assert!(values[1].is_some());
+        assert!(values[3].is_none());
+        assert!(output.metadata.contains_key("depth_interval"));
+    }
+
+    #[test]
+    fn test_zone_resolves_from_well_tops() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr.clone()])
+            .with_marker("Top Sand", 100.5)
+            .with_marker("Base Sand", 101.5);
+
+        let zone = |top: &str, base: &str| {
+            let mut params = vshale_parameters(gr_id);
+            params.insert(
+                "zone".to_string(),
+                ParameterValue::Zone {
+                    top_marker: top.to_string(),
+                    base_marker: base.to_string(),
+                },
+            );
+            params
+        };
+        let run = |params| {
+            engine
+                .execute(
+                    "petro:vshale_linear",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    params,
+                    &loader,
+                    &ExecutionOptions::new(),
+                )
+                .unwrap()
+        };
+
+        let result = run(zone("Top Sand", "Base Sand"));
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        let output = result.output.unwrap();
+        let values = &output.primary().unwrap().curve_data.values;
+        assert!(values[0].is_none());
+        assert!((values[3].unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(
+            result.record.depth_intervals["zone"],
+            DepthInterval::new(100.5, 101.5)
+        );
+
+        // A re-run with the recorded intervals ignores moved tops
+        let moved = MockCurveLoader::with_curves(vec![gr])
+            .with_marker("Top Sand", 100.0)
+            .with_marker("Base Sand", 100.5);
+        let rerun = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                zone("Top Sand", "Base Sand"),
+                &moved,
+                &ExecutionOptions::new().with_depth_intervals(result.record.depth_intervals),
+            )
+            .unwrap();
+        assert_eq!(
+            rerun.output.unwrap().primary().unwrap().curve_data.values,
+            *values
+        );
+
+        let missing = run(zone("Top Sand", "Base Shale"));
+        assert_eq!(missing.record.status, ExecutionStatus::Failed);
+        assert!(missing.record.error_message.unwrap().contains("Base Shale"));
+    }
+
+    #[test]
+    fn test_curve_list_members_are_loaded_and_recorded() {
+        let engine = create_engine();
+        let pass_1 = gr_curve();
+        let pass_2 = CurveData {
+            curve_id: Uuid::new_v4(),
+            values: vec![Some(40.0), Some(60.0), Some(70.0), None],
+            parquet_hash: "gr_pass_2".to_string(),
+            ..gr_curve()
+        };
+        let short = CurveData {
+            curve_id: Uuid::new_v4(),
+            depths: Arc::new(vec![100.0, 100.5]),
+            values: vec![Some(1.0), Some(2.0)],
+            ..gr_curve()
+        };
+        let ids = vec![pass_1.curve_id, pass_2.curve_id];
+        let short_id = short.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![pass_1, pass_2, short]);
+
+        let run = |curve_ids: Vec<Uuid>| {
+            let mut params = HashMap::new();
+            params.insert(
+                "input_curves".to_string(),
+                ParameterValue::CurveList(curve_ids),
+            );
+            engine
+                .execute(
+                    "core:curve_average",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    params,
+                    &loader,
+                    &ExecutionOptions::new(),
+                )
+                .unwrap()
+        };
+
+        let result = run(ids.clone());
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        let recorded: Vec<Uuid> = result.record.inputs.iter().map(|i| i.curve_id).collect();
+        assert_eq!(recorded, ids);
+        let values = &result.output.unwrap().primary().unwrap().curve_data.values;
+        assert_eq!(values[0], Some(35.0));
+        assert_eq!(values[2], Some(70.0));
+
+        // Every member is depth-checked
+        let mismatched = run(vec![ids[0], short_id]);
+        assert_eq!(mismatched.record.status, ExecutionStatus::Failed);
+        assert!(mismatched
+            .record
+            .error_message
+            .unwrap()
+            .contains("input_curves[1]"));
+    }
 
-    // Tests would go here with mock CurveLoader implementations
+    #[test]
+    fn test_curves_on_other_depth_grids_are_aligned() {
+        let engine = create_engine();
+        let fine = gr_curve();
+        let coarse = CurveData {
+            curve_id: Uuid::new_v4(),
+            depths: Arc::new(vec![100.0, 101.0]),
+            values: vec![Some(20.0), Some(80.0)],
+            parquet_hash: "gr_coarse".to_string(),
+            ..gr_curve()
+        };
+        let ids = vec![fine.curve_id, coarse.curve_id];
+        let loader = MockCurveLoader::with_curves(vec![fine, coarse]);
+
+        let run = |alignment: Option<&str>| {
+            let mut params = HashMap::new();
+            params.insert(
+                "input_curves".to_string(),
+                ParameterValue::CurveList(ids.clone()),
+            );
+            if let Some(alignment) = alignment {
+                params.insert(
+                    alignment::ALIGNMENT_PARAMETER.to_string(),
+                    ParameterValue::String(alignment.to_string()),
+                );
+            }
+            engine
+                .execute(
+                    "core:curve_average",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    params,
+                    &loader,
+                    &ExecutionOptions::new(),
+                )
+                .unwrap()
+        };
+
+        // Without an alignment the depths must match
+        assert_eq!(run(None).record.status, ExecutionStatus::Failed);
+
+        let result = run(Some("first"));
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        assert!(result.record.inputs[0].alignment.is_none());
+        let aligned = result.record.inputs[1].alignment.as_ref().unwrap();
+        assert_eq!(aligned.strategy, "first");
+        assert_eq!((aligned.source_samples, aligned.samples), (2, 4));
+        let output = result.output.unwrap();
+        let values = &output.primary().unwrap().curve_data.values;
+        // The coarse curve is interpolated to 50 at 100.5
+        assert_eq!(values[1], Some(50.0));
+        // Below its last sample only the fine curve's value is averaged
+        assert_eq!(values[3], Some(100.0));
+    }
+
+    #[test]
+    fn test_parameter_constraints_are_validated_and_exported() {
+        let engine = create_engine();
+        let mut params = vshale_parameters(Uuid::new_v4());
+        assert!(engine
+            .validate_only("petro:vshale_linear", &params)
+            .unwrap()
+            .is_empty());
+
+        params.insert("gr_max".to_string(), ParameterValue::Number(20.0));
+        let errors = engine
+            .validate_only("petro:vshale_linear", &params)
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "gr_max");
+        assert!(errors[0].suggestion.is_some());
+
+        // Constraints are exported on the parameter they report on
+        let definitions = engine
+            .get_parameter_definitions("petro:vshale_linear")
+            .unwrap();
+        let gr_max = definitions.iter().find(|d| d["name"] == "gr_max").unwrap();
+        assert_eq!(gr_max["constraints"][0]["type"], "greater_than");
+        assert_eq!(gr_max["constraints"][0]["other"], "gr_min");
+    }
+
+    #[test]
+    fn test_preflight_checks_curve_metadata() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let rt = CurveData {
+            curve_id: Uuid::new_v4(),
+            mnemonic: "RT".to_string(),
+            curve_type: CurveDataType::Resistivity,
+            ..gr_curve()
+        };
+        let (gr_id, rt_id) = (gr.curve_id, rt.curve_id);
+        let loader = MockCurveLoader::with_curves(vec![gr, rt]);
+
+        let params = vshale_parameters(gr_id);
+        assert!(engine
+            .preflight("petro:vshale_linear", &params, &loader)
+            .unwrap()
+            .is_empty());
+
+        let errors = engine
+            .preflight("petro:vshale_linear", &vshale_parameters(rt_id), &loader)
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "gr_curve");
+        assert!(errors[0].message.contains("Resistivity"));
+
+        let errors = engine
+            .preflight(
+                "petro:vshale_linear",
+                &vshale_parameters(Uuid::new_v4()),
+                &loader,
+            )
+            .unwrap();
+        assert_eq!(errors[0].field, "gr_curve");
+    }
+
+    #[test]
+    fn test_curve_units_are_checked_against_expected_unit() {
+        let engine = create_engine();
+        let gr = CurveData {
+            unit: "API".to_string(),
+            ..gr_curve()
+        };
+        let mislabeled = CurveData {
+            curve_id: Uuid::new_v4(),
+            unit: "ohm-m".to_string(),
+            ..gr_curve()
+        };
+        let (gr_id, mislabeled_id) = (gr.curve_id, mislabeled.curve_id);
+        let loader = MockCurveLoader::with_curves(vec![gr, mislabeled]);
+
+        // Spellings of the expected unit need no conversion
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                vshale_parameters(gr_id),
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        assert!(result.record.inputs[0].unit_conversion.is_none());
+
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                vshale_parameters(mislabeled_id),
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.status, ExecutionStatus::Failed);
+        assert!(result
+            .record
+            .error_message
+            .unwrap()
+            .contains("unit mismatch"));
+    }
+
+    /// UDF copying a bulk density curve it expects in g/cm³.
+    struct DensityCopyUdf;
+
+    impl Udf for DensityCopyUdf {
+        fn id(&self) -> &str {
+            "density_copy"
+        }
+
+        fn metadata(&self) -> UdfMetadata {
+            UdfMetadata {
+                name: "Density Copy".to_string(),
+                category: "Test".to_string(),
+                description: "Copy a bulk density curve".to_string(),
+                documentation: None,
+                version: "0.1.0".to_string(),
+                tags: Vec::new(),
+                outputs: Vec::new(),
+            }
+        }
+
+        fn parameter_definitions(&self) -> Vec<Box<dyn ParameterDefinition>> {
+            vec![Box::new(
+                CurveParameter::required("rhob", "Bulk Density").with_unit("g/cm3"),
+            )]
+        }
+
+        fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
+            let rhob = context.require_curve("rhob")?;
+            Ok(UdfOutput::new(
+                "rhob",
+                OutputCurveData {
+                    mnemonic: "RHOB_COPY".to_string(),
+                    curve_type: CurveDataType::Computed,
+                    unit: rhob.unit.clone(),
+                    depths: rhob.depths.as_ref().clone(),
+                    values: rhob.values.clone(),
+                    description: None,
+                },
+            ))
+        }
+    }
+
+    /// UDF copying a curve, with a window given in the well's depth unit.
+    struct DepthWindowUdf;
+
+    impl Udf for DepthWindowUdf {
+        fn id(&self) -> &str {
+            "depth_window"
+        }
+
+        fn metadata(&self) -> UdfMetadata {
+            UdfMetadata {
+                name: "Depth Window".to_string(),
+                category: "Test".to_string(),
+                description: "Copy a curve given a depth window".to_string(),
+                documentation: None,
+                version: "0.1.0".to_string(),
+                tags: Vec::new(),
+                outputs: Vec::new(),
+            }
+        }
+
+        fn parameter_definitions(&self) -> Vec<Box<dyn ParameterDefinition>> {
+            vec![
+                Box::new(CurveParameter::required("input_curve", "Input Curve")),
+                Box::new(NumericParameter::required("top", "Top").in_depth_unit()),
+                Box::new(NumericParameter::required("base", "Base").in_depth_unit()),
+            ]
+        }
+
+        fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+            vec![ParameterConstraint::greater_than("base", "top")]
+        }
+
+        fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
+            let input = context.require_curve("input_curve")?;
+            Ok(UdfOutput::new(
+                "copy",
+                OutputCurveData {
+                    mnemonic: "COPY".to_string(),
+                    curve_type: input.curve_type,
+                    unit: input.unit.clone(),
+                    depths: input.depths.as_ref().clone(),
+                    values: input.values.clone(),
+                    description: None,
+                },
+            ))
+        }
+    }
+
+    struct TestProvider;
+
+    impl UdfProvider for TestProvider {
+        fn id(&self) -> &str {
+            "test"
+        }
+        fn name(&self) -> &str {
+            "Test Provider"
+        }
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+        fn description(&self) -> &str {
+            "Test UDFs for engine tests"
+        }
+        fn load_udfs(&self) -> Vec<Arc<dyn Udf>> {
+            vec![Arc::new(DensityCopyUdf), Arc::new(DepthWindowUdf)]
+        }
+    }
+
+    /// Engine with the test UDFs registered.
+    fn create_test_engine() -> ExecutionEngine {
+        let mut registry = UdfRegistry::new();
+        registry.register_provider(Arc::new(TestProvider)).unwrap();
+        ExecutionEngine::new(Arc::new(registry), "test")
+    }
+
+    #[test]
+    fn test_curves_are_converted_to_expected_unit() {
+        let engine = create_test_engine();
+        let rhob = CurveData {
+            mnemonic: "RHOB".to_string(),
+            curve_type: CurveDataType::Density,
+            unit: "kg/m3".to_string(),
+            values: vec![Some(2650.0), Some(2450.0), None, Some(2000.0)],
+            ..gr_curve()
+        };
+        let rhob_id = rhob.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![rhob]);
+
+        let mut params = HashMap::new();
+        params.insert("rhob".to_string(), ParameterValue::Curve(rhob_id));
+        let result = engine
+            .execute(
+                "test:density_copy",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                params,
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+
+        let conversion = result.record.inputs[0].unit_conversion.as_ref().unwrap();
+        assert_eq!(conversion.from_unit, "kg/m³");
+        assert_eq!(conversion.to_unit, "g/cm³");
+        let output = result.output.unwrap();
+        let curve = &output.primary().unwrap().curve_data;
+        assert_eq!(curve.unit, "g/cm³");
+        let values: Vec<_> = curve
+            .values
+            .iter()
+            .map(|v| v.map(|v| (v * 1000.0).round() / 1000.0))
+            .collect();
+        assert_eq!(values, vec![Some(2.65), Some(2.45), None, Some(2.0)]);
+    }
+
+    #[test]
+    fn test_constraints_compare_values_in_the_converted_unit() {
+        let engine = create_test_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]).with_depth_unit("m");
+        let window = |top: ParameterValue| {
+            let mut params = HashMap::new();
+            params.insert("input_curve".to_string(), ParameterValue::Curve(gr_id));
+            params.insert("top".to_string(), top);
+            params.insert("base".to_string(), ParameterValue::Number(1000.0));
+            params
+        };
+        let feet = |value: f64| ParameterValue::Quantity {
+            value,
+            unit: "ft".to_string(),
+        };
+        let run = |params: HashMap<String, ParameterValue>| {
+            engine
+                .execute(
+                    "test:depth_window",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    params,
+                    &loader,
+                    &ExecutionOptions::new(),
+                )
+                .unwrap()
+                .record
+        };
+
+        // 3000 ft is above a base of 1000 m
+        let record = run(window(feet(3000.0)));
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        // 4000 ft is below it
+        let record = run(window(feet(4000.0)));
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert!(record
+            .error_message
+            .unwrap()
+            .contains("Base must be greater than Top"));
+
+        // Without the well's depth unit the values are not compared up front
+        assert!(engine
+            .validate_only("test:depth_window", &window(feet(3000.0)))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            engine
+                .validate_only("test:depth_window", &window(ParameterValue::Number(1200.0)))
+                .unwrap()[0]
+                .field,
+            "base"
+        );
+    }
+
+    #[test]
+    fn test_numeric_parameters_are_converted_to_target_unit() {
+        let engine = create_engine();
+        let gr = gr_curve();
+        let gr_id = gr.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![gr]).with_depth_unit("m");
+        let resample = |new_step: ParameterValue| {
+            let mut params = HashMap::new();
+            params.insert("input_curve".to_string(), ParameterValue::Curve(gr_id));
+            params.insert("new_step".to_string(), new_step);
+            engine
+                .execute(
+                    "core:depth_resample",
+                    Uuid::new_v4(),
+                    Uuid::new_v4(),
+                    params,
+                    &loader,
+                    &ExecutionOptions::new(),
+                )
+                .unwrap()
+        };
+
+        // A step in feet resamples the metre-indexed well at 0.3048 m
+        let result = resample(ParameterValue::Quantity {
+            value: 1.0,
+            unit: "ft".to_string(),
+        });
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        let conversion = &result.record.parameter_conversions[0];
+        assert_eq!(conversion.parameter, "new_step");
+        assert_eq!((conversion.value, conversion.unit.as_str()), (1.0, "ft"));
+        assert!((conversion.converted_value - 0.3048).abs() < 1e-12);
+        assert_eq!(conversion.converted_unit, "m");
+        let output = result.output.unwrap();
+        let depths = &output.primary().unwrap().curve_data.depths;
+        assert!((depths[1] - depths[0] - 0.3048).abs() < 1e-9);
+
+        // Plain numbers are in the depth unit already
+        let result = resample(ParameterValue::Number(0.25));
+        assert_eq!(result.record.status, ExecutionStatus::Completed);
+        assert!(result.record.parameter_conversions.is_empty());
+
+        // GR cutoffs cannot be entered in a length unit
+        let mut params = vshale_parameters(gr_id);
+        params.insert(
+            "gr_min".to_string(),
+            ParameterValue::Quantity {
+                value: 30.0,
+                unit: "ft".to_string(),
+            },
+        );
+        let result = engine
+            .execute(
+                "petro:vshale_linear",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                params,
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.status, ExecutionStatus::Failed);
+        assert!(result.record.error_message.unwrap().contains("gr_min"));
+    }
+
+    #[test]
+    fn test_min_length_is_enforced_after_loading() {
+        let engine = create_engine();
+        let sparse = CurveData {
+            values: vec![None, Some(50.0), None, None],
+            ..gr_curve()
+        };
+        let sparse_id = sparse.curve_id;
+        let loader = MockCurveLoader::with_curves(vec![sparse]);
+
+        let mut params = HashMap::new();
+        params.insert("input_curve".to_string(), ParameterValue::Curve(sparse_id));
+        params.insert("new_step".to_string(), ParameterValue::Number(0.25));
+
+        let result = engine
+            .execute(
+                "core:depth_resample",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                params,
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.status, ExecutionStatus::Failed);
+        assert!(result
+            .record
+            .error_message
+            .unwrap()
+            .contains("1 valid samples, at least 2 are required"));
+    }
 }
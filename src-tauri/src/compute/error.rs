@@ -22,6 +22,10 @@ pub enum UdfError {
     #[error("Curve type mismatch: expected {expected}, got {actual}")]
     CurveTypeMismatch { expected: String, actual: String },
 
+    /// Curve unit mismatch
+    #[error("Curve unit mismatch: expected {expected}, got {actual}")]
+    UnitMismatch { expected: String, actual: String },
+
     /// Required curve not provided
     #[error("Required curve not provided: {0}")]
     MissingCurve(String),
@@ -62,6 +66,14 @@ pub enum UdfError {
     #[error("Numeric error: {0}")]
     NumericError(String),
 
+    /// Workflow definition is invalid (cycles, unknown nodes, etc.)
+    #[error("Invalid workflow: {0}")]
+    InvalidWorkflow(String),
+
+    /// Mnemonic alias rule is invalid (empty pattern, bad regex)
+    #[error("Invalid mnemonic rule: {0}")]
+    InvalidMnemonicRule(String),
+
     /// Execution was cancelled by user
     #[error("Execution cancelled")]
     Cancelled,
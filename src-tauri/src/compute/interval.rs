@@ -0,0 +1,231 @@
+//! Depth interval restriction of UDF executions.
+//!
+//! Depth range and zone parameters restrict an execution to a depth
+//! window. The engine resolves them here to a single interval, masks the
+//! input curves to it before the UDF runs and the outputs after, so any
+//! UDF becomes interval-aware without code of its own. UDFs that declare
+//! no interval parameters get the optional `depth_range` and `zone`
+//! parameters added by the engine.
+
+use crate::compute::engine::{CurveLoader, WellMarker};
+use crate::compute::error::UdfError;
+use crate::compute::parameters::{
+    DepthRangeParameter, ParameterDefinition, ParameterValue, ZoneParameter,
+};
+use crate::compute::types::{CurveData, OutputCurveData};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// A depth interval, inclusive at both ends.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthInterval {
+    pub top: f64,
+    pub base: f64,
+}
+
+impl DepthInterval {
+    /// Create an interval from its top and base depths.
+    pub fn new(top: f64, base: f64) -> Self {
+        Self { top, base }
+    }
+
+    /// Check if a depth lies within the interval.
+    pub fn contains(&self, depth: f64) -> bool {
+        depth >= self.top && depth <= self.base
+    }
+
+    /// Overlap of two intervals, if any.
+    pub fn intersect(&self, other: &DepthInterval) -> Option<DepthInterval> {
+        let top = self.top.max(other.top);
+        let base = self.base.min(other.base);
+        (top <= base).then_some(DepthInterval { top, base })
+    }
+}
+
+/// Name of the depth range parameter added to UDFs by the engine.
+pub const DEPTH_RANGE_PARAMETER: &str = "depth_range";
+
+/// Name of the zone parameter added to UDFs by the engine.
+pub const ZONE_PARAMETER: &str = "zone";
+
+/// Add the engine's interval parameters to a UDF's definitions.
+///
+/// UDFs declaring their own depth range or zone parameters keep just
+/// those; names already taken by other parameters are skipped.
+pub fn with_interval_parameters(
+    mut definitions: Vec<Box<dyn ParameterDefinition>>,
+) -> Vec<Box<dyn ParameterDefinition>> {
+    let declares_interval = definitions
+        .iter()
+        .any(|d| matches!(d.param_type(), "depth_range" | "zone"));
+    if declares_interval {
+        return definitions;
+    }
+
+    let is_taken = |definitions: &[Box<dyn ParameterDefinition>], name: &str| {
+        definitions.iter().any(|d| d.name() == name)
+    };
+    if !is_taken(&definitions, DEPTH_RANGE_PARAMETER) {
+        definitions.push(Box::new(
+            DepthRangeParameter::optional(DEPTH_RANGE_PARAMETER, "Depth Range")
+                .with_description("Only compute within this depth interval"),
+        ));
+    }
+    if !is_taken(&definitions, ZONE_PARAMETER) {
+        definitions.push(Box::new(
+            ZoneParameter::optional(ZONE_PARAMETER, "Zone")
+                .with_description("Only compute between these two well tops"),
+        ));
+    }
+    definitions
+}
+
+/// Resolve the depth range and zone parameters of an execution.
+///
+/// Returns the interval of each set parameter by name. Zones are looked
+/// up in the well's markers, which are only loaded if a zone is set.
+/// Parameters with a `recorded` interval resolve to it instead, so a
+/// re-run of an execution uses the depths it ran on even if the well's
+/// tops have moved since.
+pub fn resolve_intervals(
+    definitions: &[Box<dyn ParameterDefinition>],
+    parameters: &HashMap<String, ParameterValue>,
+    recorded: &BTreeMap<String, DepthInterval>,
+    well_id: Uuid,
+    curve_loader: &dyn CurveLoader,
+) -> Result<Vec<(String, DepthInterval)>, UdfError> {
+    let mut markers: Option<Vec<WellMarker>> = None;
+    let mut intervals = Vec::new();
+
+    for def in definitions {
+        let Some(value) = parameters.get(def.name()) else {
+            continue;
+        };
+        if let Some(interval) = recorded.get(def.name()) {
+            intervals.push((def.name().to_string(), *interval));
+            continue;
+        }
+
+        let interval = match def.param_type() {
+            "depth_range" => match value.as_depth_range() {
+                Some((top, base)) => DepthInterval::new(top, base),
+                None => continue,
+            },
+            "zone" => match value.as_zone() {
+                Some((top_marker, base_marker)) => {
+                    if markers.is_none() {
+                        markers = Some(curve_loader.list_well_markers(well_id)?);
+                    }
+                    let markers = markers.as_deref().unwrap_or_default();
+                    let top = marker_depth(markers, def.name(), top_marker)?;
+                    let base = marker_depth(markers, def.name(), base_marker)?;
+                    if top >= base {
+                        return Err(UdfError::ParameterValidation(format!(
+                            "{}: well top '{}' ({}) is not above '{}' ({})",
+                            def.name(),
+                            top_marker,
+                            top,
+                            base_marker,
+                            base
+                        )));
+                    }
+                    DepthInterval::new(top, base)
+                }
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        intervals.push((def.name().to_string(), interval));
+    }
+
+    Ok(intervals)
+}
+
+/// Combine resolved intervals into the one the execution is restricted to.
+///
+/// Several interval parameters restrict the execution to their overlap.
+pub fn combine_intervals(
+    intervals: &[(String, DepthInterval)],
+) -> Result<Option<DepthInterval>, UdfError> {
+    let mut combined: Option<DepthInterval> = None;
+    for (_, interval) in intervals {
+        combined = match combined {
+            None => Some(*interval),
+            Some(current) => Some(current.intersect(interval).ok_or_else(|| {
+                UdfError::ParameterValidation(format!(
+                    "Depth intervals {} do not overlap",
+                    intervals
+                        .iter()
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })?),
+        };
+    }
+    Ok(combined)
+}
+
+/// Copy of a curve with the samples outside the interval set to null.
+pub fn mask_curve(curve: &CurveData, interval: &DepthInterval) -> CurveData {
+    let values = curve
+        .depths
+        .iter()
+        .zip(&curve.values)
+        .map(|(depth, value)| value.filter(|_| interval.contains(*depth)))
+        .collect();
+
+    CurveData {
+        values,
+        ..curve.clone()
+    }
+}
+
+/// Set the output samples outside the interval to null.
+pub fn mask_output(output: &mut OutputCurveData, interval: &DepthInterval) {
+    for (depth, value) in output.depths.iter().zip(output.values.iter_mut()) {
+        if !interval.contains(*depth) {
+            *value = None;
+        }
+    }
+}
+
+/// Depth of a well top by name.
+fn marker_depth(markers: &[WellMarker], parameter: &str, name: &str) -> Result<f64, UdfError> {
+    markers
+        .iter()
+        .find(|m| m.name == name)
+        .map(|m| m.measured_depth)
+        .ok_or_else(|| {
+            UdfError::ParameterValidation(format!(
+                "{}: well top '{}' not found in this well",
+                parameter, name
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combine_intervals() {
+        let intervals = vec![
+            ("range".to_string(), DepthInterval::new(1000.0, 1100.0)),
+            ("zone".to_string(), DepthInterval::new(1050.0, 1200.0)),
+        ];
+        assert_eq!(
+            combine_intervals(&intervals).unwrap(),
+            Some(DepthInterval::new(1050.0, 1100.0))
+        );
+        assert_eq!(combine_intervals(&[]).unwrap(), None);
+
+        let disjoint = vec![
+            ("range".to_string(), DepthInterval::new(1000.0, 1100.0)),
+            ("zone".to_string(), DepthInterval::new(1200.0, 1300.0)),
+        ];
+        assert!(combine_intervals(&disjoint).is_err());
+    }
+}
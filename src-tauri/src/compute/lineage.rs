@@ -0,0 +1,507 @@
+//! Lineage graphs of derived curves.
+//!
+//! A derived curve points through `source_execution_id` at the execution
+//! that produced it, and the execution's recorded `inputs` name the curves
+//! it read. Following those links from a curve gives its upstream lineage,
+//! back to the curves loaded from LAS files; following them in reverse
+//! gives every derived curve downstream of it. Both follow the current
+//! source of each curve, so executions superseded by a recompute are not
+//! part of the graph.
+
+use crate::compute::error::UdfError;
+use crate::compute::staleness::{
+    has_column, has_table, load_source_execution, parse_uuid, SourceExecution,
+};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// Which way to traverse from the root curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineageDirection {
+    /// Executions and curves the root was derived from
+    Upstream,
+    /// Curves derived from the root, directly or indirectly
+    Downstream,
+}
+
+/// A curve in a lineage graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveNode {
+    pub curve_id: Uuid,
+    /// Well, mnemonic and version are None if the curve no longer exists
+    pub well_id: Option<Uuid>,
+    pub mnemonic: Option<String>,
+    pub version: Option<i64>,
+    pub is_derived: bool,
+    /// True if the curve was deleted
+    pub deleted: bool,
+}
+
+/// An execution in a lineage graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionNode {
+    pub execution_id: Uuid,
+    pub udf_id: String,
+    pub udf_version: String,
+    pub parameters: serde_json::Value,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub compute_app_version: String,
+}
+
+/// How an edge connects a curve and an execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LineageRelation {
+    /// The execution (`to`) read the curve (`from`)
+    Used,
+    /// The execution (`from`) produced the curve (`to`)
+    Generated,
+}
+
+/// An edge between a curve and an execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageEdge {
+    pub from: Uuid,
+    pub to: Uuid,
+    pub relation: LineageRelation,
+    /// Parameter the input was bound to, or the output name
+    pub name: Option<String>,
+    /// Content hash of the curve data read or produced
+    pub parquet_hash: Option<String>,
+    /// Recorded version of an input curve
+    pub version: Option<i64>,
+}
+
+/// Curves and executions reachable from a root curve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageGraph {
+    pub root: Uuid,
+    pub direction: LineageDirection,
+    /// Nodes in traversal order, starting with the root curve
+    pub curves: Vec<CurveNode>,
+    pub executions: Vec<ExecutionNode>,
+    pub edges: Vec<LineageEdge>,
+}
+
+/// Build the upstream or downstream lineage graph of a curve.
+///
+/// With `max_depth` set, traversal stops after that many executions from
+/// the root; `Some(1)` gives only the execution that produced the root (or
+/// the derived curves built directly from it).
+pub fn lineage_graph(
+    db: &Connection,
+    root: Uuid,
+    direction: LineageDirection,
+    max_depth: Option<usize>,
+) -> Result<LineageGraph, UdfError> {
+    let mut builder = GraphBuilder::new(db, root, direction)?;
+
+    let root_node = builder.add_curve(root)?;
+    if root_node.well_id.is_none() {
+        return Err(UdfError::CurveLoadError(format!(
+            "Curve not found: {}",
+            root
+        )));
+    }
+
+    let mut queue = VecDeque::from([(root, 0)]);
+    while let Some((curve_id, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+        let next = match direction {
+            LineageDirection::Upstream => builder.visit_upstream(curve_id)?,
+            LineageDirection::Downstream => builder.visit_downstream(curve_id)?,
+        };
+        queue.extend(next.into_iter().map(|curve_id| (curve_id, depth + 1)));
+    }
+
+    Ok(builder.graph)
+}
+
+/// Incremental graph construction with de-duplicated nodes and edges.
+struct GraphBuilder<'a> {
+    db: &'a Connection,
+    /// False for databases where no curve has been derived yet
+    has_derived_columns: bool,
+    has_records: bool,
+    graph: LineageGraph,
+    curves: HashMap<Uuid, Option<Uuid>>,
+    executions: HashMap<Uuid, Option<SourceExecution>>,
+    edges: HashSet<(Uuid, Uuid, LineageRelation)>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    fn new(db: &'a Connection, root: Uuid, direction: LineageDirection) -> Result<Self, UdfError> {
+        Ok(Self {
+            db,
+            has_derived_columns: has_column(db, "curves", "is_derived")?,
+            has_records: has_table(db, "execution_records")?,
+            graph: LineageGraph {
+                root,
+                direction,
+                curves: Vec::new(),
+                executions: Vec::new(),
+                edges: Vec::new(),
+            },
+            curves: HashMap::new(),
+            executions: HashMap::new(),
+            edges: HashSet::new(),
+        })
+    }
+
+    /// Add the execution that produced a curve and the curves it read.
+    ///
+    /// Returns the input curves seen for the first time.
+    fn visit_upstream(&mut self, curve_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        let Some(execution_id) = self.curves.get(&curve_id).copied().flatten() else {
+            return Ok(Vec::new());
+        };
+        let first_visit = !self.executions.contains_key(&execution_id);
+        let Some(source) = self.add_execution(execution_id)? else {
+            return Ok(Vec::new());
+        };
+        self.add_generated_edge(&source, curve_id);
+        if !first_visit {
+            return Ok(Vec::new());
+        }
+
+        let mut new_curves = Vec::new();
+        for input in &source.inputs {
+            if !self.curves.contains_key(&input.curve_id) {
+                self.add_curve(input.curve_id)?;
+                new_curves.push(input.curve_id);
+            }
+            self.add_used_edge(&source, input.curve_id);
+        }
+        Ok(new_curves)
+    }
+
+    /// Add the derived curves whose current source execution read a curve.
+    ///
+    /// Returns the derived curves seen for the first time.
+    fn visit_downstream(&mut self, curve_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        if !self.has_derived_columns || !self.has_records {
+            return Ok(Vec::new());
+        }
+
+        // Inputs are stored as JSON, so match the ID textually and confirm
+        // against the parsed inputs below
+        let db = self.db;
+        let mut stmt = db.prepare(
+            r#"SELECT c.id, c.source_execution_id
+               FROM curves c
+               JOIN execution_records e ON e.id = c.source_execution_id
+               WHERE COALESCE(c.is_derived, 0) = 1 AND c.deleted_at IS NULL
+                 AND e.inputs LIKE ?1
+               ORDER BY c.mnemonic"#,
+        )?;
+        let rows = stmt
+            .query_map([format!("%{}%", curve_id)], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut new_curves = Vec::new();
+        for (derived_id, execution_id) in rows {
+            let derived_id = parse_uuid(&derived_id)?;
+            let Some(source) = self.add_execution(parse_uuid(&execution_id)?)? else {
+                continue;
+            };
+            if !source.inputs.iter().any(|input| input.curve_id == curve_id) {
+                continue;
+            }
+
+            self.add_used_edge(&source, curve_id);
+            if !self.curves.contains_key(&derived_id) {
+                self.add_curve(derived_id)?;
+                new_curves.push(derived_id);
+            }
+            self.add_generated_edge(&source, derived_id);
+        }
+        Ok(new_curves)
+    }
+
+    /// Load a curve and add it as a node.
+    fn add_curve(&mut self, curve_id: Uuid) -> Result<CurveNode, UdfError> {
+        let derived_columns = if self.has_derived_columns {
+            "COALESCE(is_derived, 0) != 0, source_execution_id"
+        } else {
+            "0, NULL"
+        };
+        let row: Option<(String, String, i64, bool, Option<String>, bool)> = self
+            .db
+            .query_row(
+                &format!(
+                    r#"SELECT well_id, mnemonic, COALESCE(version, 1), {},
+                              deleted_at IS NOT NULL
+                       FROM curves WHERE id = ?1"#,
+                    derived_columns
+                ),
+                [curve_id.to_string()],
+                |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let (node, source_execution_id) = match row {
+            Some((well_id, mnemonic, version, is_derived, source_execution_id, deleted)) => (
+                CurveNode {
+                    curve_id,
+                    well_id: Some(parse_uuid(&well_id)?),
+                    mnemonic: Some(mnemonic),
+                    version: Some(version),
+                    is_derived,
+                    deleted,
+                },
+                source_execution_id.as_deref().map(parse_uuid).transpose()?,
+            ),
+            None => (
+                CurveNode {
+                    curve_id,
+                    well_id: None,
+                    mnemonic: None,
+                    version: None,
+                    is_derived: false,
+                    deleted: true,
+                },
+                None,
+            ),
+        };
+
+        self.curves.insert(curve_id, source_execution_id);
+        self.graph.curves.push(node.clone());
+        Ok(node)
+    }
+
+    /// Load an execution record, adding it as a node on first use.
+    ///
+    /// Returns None if the record does not exist.
+    fn add_execution(&mut self, execution_id: Uuid) -> Result<Option<SourceExecution>, UdfError> {
+        if let Some(source) = self.executions.get(&execution_id) {
+            return Ok(source.clone());
+        }
+
+        let source = if self.has_records {
+            load_source_execution(self.db, &execution_id.to_string())?
+        } else {
+            None
+        };
+        match &source {
+            Some(source) => self.graph.executions.push(ExecutionNode {
+                execution_id,
+                udf_id: source.udf_id.clone(),
+                udf_version: source.udf_version.clone(),
+                parameters: source.parameters.clone(),
+                started_at: source.started_at,
+                completed_at: source.completed_at,
+                compute_app_version: source.compute_app_version.clone(),
+            }),
+            None => log::warn!("⚠️ Execution record {} not found", execution_id),
+        }
+        self.executions.insert(execution_id, source.clone());
+        Ok(source)
+    }
+
+    fn add_used_edge(&mut self, source: &SourceExecution, curve_id: Uuid) {
+        if !self
+            .edges
+            .insert((curve_id, source.execution_id, LineageRelation::Used))
+        {
+            return;
+        }
+
+        let input = source
+            .inputs
+            .iter()
+            .find(|input| input.curve_id == curve_id);
+        // Curve parameters are recorded as the curve ID, curve lists as an
+        // array of IDs whose members are named `param[index]`
+        let id = curve_id.to_string();
+        let parameter = source.parameters.as_object().and_then(|parameters| {
+            parameters.iter().find_map(|(name, value)| match value {
+                serde_json::Value::String(s) if *s == id => Some(name.clone()),
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .position(|item| item.as_str() == Some(id.as_str()))
+                    .map(|index| format!("{}[{}]", name, index)),
+                _ => None,
+            })
+        });
+        self.graph.edges.push(LineageEdge {
+            from: curve_id,
+            to: source.execution_id,
+            relation: LineageRelation::Used,
+            name: parameter,
+            parquet_hash: input.map(|input| input.parquet_hash.clone()),
+            version: input.map(|input| input.version),
+        });
+    }
+
+    fn add_generated_edge(&mut self, source: &SourceExecution, curve_id: Uuid) {
+        if !self
+            .edges
+            .insert((source.execution_id, curve_id, LineageRelation::Generated))
+        {
+            return;
+        }
+
+        let (name, parquet_hash) = match source.outputs.iter().find(|o| o.curve_id == curve_id) {
+            Some(output) => (Some(output.name.clone()), Some(output.parquet_hash.clone())),
+            None if source.output_curve_id == Some(curve_id) => {
+                (None, source.output_parquet_hash.clone())
+            }
+            None => (None, None),
+        };
+        self.graph.edges.push(LineageEdge {
+            from: source.execution_id,
+            to: curve_id,
+            relation: LineageRelation::Generated,
+            name,
+            parquet_hash,
+            version: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::data_loader::save_execution_record;
+    use crate::compute::test_support::create_dataforge_db;
+    use crate::compute::types::{
+        ExecutionRecord, ExecutionStatus, InputReference, OutputReference,
+    };
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+
+    fn insert_curve(db: &Connection, well_id: Uuid, mnemonic: &str) -> Uuid {
+        let curve_id = Uuid::new_v4();
+        db.execute(
+            "INSERT INTO curves (id, well_id, mnemonic, native_parquet_hash) VALUES (?1, ?2, ?3, ?3)",
+            rusqlite::params![curve_id.to_string(), well_id.to_string(), mnemonic],
+        )
+        .unwrap();
+        curve_id
+    }
+
+    /// Save an execution reading `inputs` and the derived curve it produced
+    fn insert_derived(db: &Connection, well_id: Uuid, mnemonic: &str, inputs: &[Uuid]) -> Uuid {
+        let curve_id = Uuid::new_v4();
+        let parameters: serde_json::Map<String, serde_json::Value> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (format!("curve_{}", i), serde_json::json!(id)))
+            .collect();
+        let record = ExecutionRecord {
+            id: Uuid::new_v4(),
+            udf_id: "core:moving_average".to_string(),
+            udf_version: "1.0.0".to_string(),
+            well_id,
+            workspace_id: Uuid::nil(),
+            inputs: inputs
+                .iter()
+                .map(|id| InputReference {
+                    curve_id: *id,
+                    version: 1,
+                    parquet_hash: format!("{}_hash", id),
+                    unit_conversion: None,
+                    alignment: None,
+                })
+                .collect(),
+            parameters: serde_json::Value::Object(parameters),
+            parameter_conversions: Vec::new(),
+            depth_intervals: BTreeMap::new(),
+            output_curve_id: Some(curve_id),
+            output_parquet_hash: Some(mnemonic.to_string()),
+            outputs: vec![OutputReference {
+                name: "output".to_string(),
+                curve_id,
+                mnemonic: mnemonic.to_string(),
+                parquet_hash: mnemonic.to_string(),
+            }],
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            compute_app_version: "test".to_string(),
+            status: ExecutionStatus::Completed,
+            error_message: None,
+            workflow: None,
+            cache_key: None,
+            cached_from: None,
+        };
+        save_execution_record(db, &record).unwrap();
+        db.execute(
+            r#"INSERT INTO curves (id, well_id, mnemonic, native_parquet_hash, is_derived, source_execution_id)
+               VALUES (?1, ?2, ?3, ?3, 1, ?4)"#,
+            rusqlite::params![
+                curve_id.to_string(),
+                well_id.to_string(),
+                mnemonic,
+                record.id.to_string()
+            ],
+        )
+        .unwrap();
+        curve_id
+    }
+
+    #[test]
+    fn test_lineage_follows_derived_of_derived_curves() {
+        let db = create_dataforge_db();
+        let well_id = Uuid::new_v4();
+
+        // GR → GR_SM → VSH ← RHOB, and GR → GR_SM2 (unrelated to VSH)
+        let gr = insert_curve(&db, well_id, "GR");
+        let rhob = insert_curve(&db, well_id, "RHOB");
+        let gr_sm = insert_derived(&db, well_id, "GR_SM", &[gr]);
+        let vsh = insert_derived(&db, well_id, "VSH", &[gr_sm, rhob]);
+        let gr_sm2 = insert_derived(&db, well_id, "GR_SM2", &[gr]);
+
+        let upstream = lineage_graph(&db, vsh, LineageDirection::Upstream, None).unwrap();
+        let curves: Vec<Uuid> = upstream.curves.iter().map(|c| c.curve_id).collect();
+        assert_eq!(curves, vec![vsh, gr_sm, rhob, gr]);
+        assert_eq!(upstream.executions.len(), 2);
+        assert_eq!(upstream.edges.len(), 5);
+        let used_gr = upstream
+            .edges
+            .iter()
+            .find(|e| e.from == gr && e.relation == LineageRelation::Used)
+            .unwrap();
+        assert_eq!(used_gr.name.as_deref(), Some("curve_0"));
+        assert_eq!(used_gr.version, Some(1));
+
+        let immediate = lineage_graph(&db, vsh, LineageDirection::Upstream, Some(1)).unwrap();
+        assert_eq!(immediate.curves.len(), 3);
+        assert_eq!(immediate.executions.len(), 1);
+
+        let downstream = lineage_graph(&db, gr, LineageDirection::Downstream, None).unwrap();
+        let mut curves: Vec<Uuid> = downstream.curves.iter().map(|c| c.curve_id).collect();
+        assert_eq!(curves.remove(0), gr);
+        curves.sort();
+        let mut expected = vec![gr_sm, gr_sm2, vsh];
+        expected.sort();
+        assert_eq!(curves, expected);
+        assert_eq!(downstream.executions.len(), 3);
+
+        // Deleted derived curves are no longer affected by their inputs
+        db.execute(
+            "UPDATE curves SET deleted_at = '2024-01-01' WHERE id = ?1",
+            [gr_sm.to_string()],
+        )
+        .unwrap();
+        let downstream = lineage_graph(&db, gr, LineageDirection::Downstream, None).unwrap();
+        assert_eq!(downstream.curves.len(), 2);
+
+        assert!(lineage_graph(&db, Uuid::new_v4(), LineageDirection::Upstream, None).is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! Mnemonic alias dictionary.
+//!
+//! Curves without a DataForge property are typed by their mnemonic. Rules
+//! match a mnemonic exactly or by a regular expression over the whole
+//! mnemonic, both case-insensitive; the rule with the highest priority
+//! wins, exact rules before regex rules at equal priority. The defaults
+//! ship with the app and include the tool mnemonics of Schlumberger,
+//! Halliburton and Baker Hughes; workspaces can add rules of their own,
+//! which are stored in the local database.
+
+use crate::compute::error::UdfError;
+use crate::compute::types::CurveDataType;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Priority of vendor tool mnemonics.
+pub const VENDOR_PRIORITY: i32 = 20;
+
+/// Priority of generic mnemonics.
+pub const GENERIC_PRIORITY: i32 = 10;
+
+/// Priority of the fallback patterns (suffixed and computed mnemonics).
+pub const FALLBACK_PRIORITY: i32 = 0;
+
+/// Suggested priority of workspace rules, above all defaults.
+pub const WORKSPACE_PRIORITY: i32 = 100;
+
+/// How a rule matches a mnemonic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// The whole mnemonic equals the pattern
+    Exact,
+    /// The whole mnemonic matches the pattern as a regular expression
+    Regex,
+}
+
+impl MatchKind {
+    /// Get the match kind as stored in the local database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchKind::Exact => "exact",
+            MatchKind::Regex => "regex",
+        }
+    }
+}
+
+/// Logging vendor a rule's mnemonics belong to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Vendor {
+    Schlumberger,
+    Halliburton,
+    BakerHughes,
+}
+
+/// A rule mapping mnemonics to a curve type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MnemonicRule {
+    /// Mnemonic or regular expression
+    pub pattern: String,
+    /// How the pattern matches
+    pub kind: MatchKind,
+    /// Curve type of matching mnemonics
+    pub curve_type: CurveDataType,
+    /// Vendor of the tool, for vendor-specific mnemonics
+    #[serde(default)]
+    pub vendor: Option<Vendor>,
+    /// Rules with a higher priority win (workspace rules saved without one
+    /// rank above all defaults)
+    #[serde(default = "workspace_priority")]
+    pub priority: i32,
+}
+
+fn workspace_priority() -> i32 {
+    WORKSPACE_PRIORITY
+}
+
+impl MnemonicRule {
+    /// Create a rule matching one mnemonic
+    pub fn exact(pattern: impl Into<String>, curve_type: CurveDataType) -> Self {
+        Self {
+            pattern: pattern.into(),
+            kind: MatchKind::Exact,
+            curve_type,
+            vendor: None,
+            priority: GENERIC_PRIORITY,
+        }
+    }
+
+    /// Create a rule matching mnemonics by a regular expression
+    pub fn regex(pattern: impl Into<String>, curve_type: CurveDataType) -> Self {
+        Self {
+            pattern: pattern.into(),
+            kind: MatchKind::Regex,
+            curve_type,
+            vendor: None,
+            priority: FALLBACK_PRIORITY,
+        }
+    }
+
+    /// Mark the rule as vendor-specific
+    pub fn with_vendor(mut self, vendor: Vendor) -> Self {
+        self.vendor = Some(vendor);
+        self
+    }
+
+    /// Set the priority
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Check the rule, compiling its pattern if it is a regex.
+    fn compile(&self) -> Result<Option<Regex>, UdfError> {
+        if self.pattern.trim().is_empty() {
+            return Err(UdfError::InvalidMnemonicRule(
+                "Pattern must not be empty".to_string(),
+            ));
+        }
+        match self.kind {
+            MatchKind::Exact => Ok(None),
+            MatchKind::Regex => Regex::new(&format!("(?i)^(?:{})$", self.pattern))
+                .map(Some)
+                .map_err(|e| UdfError::InvalidMnemonicRule(format!("'{}': {}", self.pattern, e))),
+        }
+    }
+}
+
+/// A rule of a dictionary with its compiled pattern.
+#[derive(Debug, Clone)]
+struct DictionaryRule {
+    rule: MnemonicRule,
+    regex: Option<Regex>,
+    /// Whether the rule is a workspace override
+    workspace: bool,
+}
+
+impl DictionaryRule {
+    fn matches(&self, mnemonic: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(mnemonic),
+            None => self.rule.pattern.trim().eq_ignore_ascii_case(mnemonic),
+        }
+    }
+}
+
+/// Ordered set of mnemonic rules.
+#[derive(Debug, Clone)]
+pub struct MnemonicDictionary {
+    rules: Vec<DictionaryRule>,
+}
+
+impl MnemonicDictionary {
+    /// Create a dictionary from rules.
+    pub fn new(rules: Vec<MnemonicRule>) -> Result<Self, UdfError> {
+        let mut dictionary = Self { rules: Vec::new() };
+        dictionary.add(rules, false)?;
+        Ok(dictionary)
+    }
+
+    /// The dictionary shipped with the app.
+    pub fn defaults() -> Self {
+        Self::new(default_rules()).expect("default mnemonic rules are valid")
+    }
+
+    /// Add a workspace's rules.
+    ///
+    /// A workspace rule replaces the default with the same kind and
+    /// pattern, and wins over defaults of the same priority.
+    pub fn with_overrides(mut self, overrides: Vec<MnemonicRule>) -> Result<Self, UdfError> {
+        self.add(overrides, true)?;
+        Ok(self)
+    }
+
+    fn add(&mut self, rules: Vec<MnemonicRule>, workspace: bool) -> Result<(), UdfError> {
+        for rule in rules {
+            let regex = rule.compile()?;
+            self.rules.retain(|r| {
+                !(r.rule.kind == rule.kind && r.rule.pattern.eq_ignore_ascii_case(&rule.pattern))
+            });
+            self.rules.push(DictionaryRule {
+                rule,
+                regex,
+                workspace,
+            });
+        }
+        // Stable, so rules of equal rank keep their order
+        self.rules.sort_by_key(|r| {
+            (
+                Reverse(r.rule.priority),
+                !r.workspace,
+                r.rule.kind == MatchKind::Regex,
+            )
+        });
+        Ok(())
+    }
+
+    /// The rule deciding the type of a mnemonic, if any matches.
+    pub fn matching_rule(&self, mnemonic: &str) -> Option<&MnemonicRule> {
+        let mnemonic = mnemonic.trim();
+        self.rules
+            .iter()
+            .find(|r| r.matches(mnemonic))
+            .map(|r| &r.rule)
+    }
+
+    /// Curve type of a mnemonic, if any rule matches.
+    pub fn detect(&self, mnemonic: &str) -> Option<CurveDataType> {
+        self.matching_rule(mnemonic).map(|rule| rule.curve_type)
+    }
+
+    /// Rules in match order, with whether each is a workspace override.
+    pub fn rules(&self) -> impl Iterator<Item = (&MnemonicRule, bool)> {
+        self.rules.iter().map(|r| (&r.rule, r.workspace))
+    }
+}
+
+impl Default for MnemonicDictionary {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+/// Generic mnemonics, used by most vendors and LAS exports.
+const GENERIC_MNEMONICS: &[(&str, CurveDataType)] = &[
+    ("GR", CurveDataType::GammaRay),
+    ("SGR", CurveDataType::GammaRay),
+    ("CGR", CurveDataType::GammaRay),
+    ("GAMMA", CurveDataType::GammaRay),
+    ("RHOB", CurveDataType::Density),
+    ("DEN", CurveDataType::Density),
+    ("DENS", CurveDataType::Density),
+    ("DENSITY", CurveDataType::Density),
+    ("NPHI", CurveDataType::NeutronPorosity),
+    ("NEU", CurveDataType::NeutronPorosity),
+    ("NEUT", CurveDataType::NeutronPorosity),
+    ("NEUTRON", CurveDataType::NeutronPorosity),
+    ("PHIN", CurveDataType::NeutronPorosity),
+    ("RT", CurveDataType::Resistivity),
+    ("RES", CurveDataType::Resistivity),
+    ("ILD", CurveDataType::DeepResistivity),
+    ("RILD", CurveDataType::DeepResistivity),
+    ("LLD", CurveDataType::DeepResistivity),
+    ("RDEEP", CurveDataType::DeepResistivity),
+    ("ILM", CurveDataType::MediumResistivity),
+    ("RILM", CurveDataType::MediumResistivity),
+    ("RMED", CurveDataType::MediumResistivity),
+    ("SFL", CurveDataType::ShallowResistivity),
+    ("SFLU", CurveDataType::ShallowResistivity),
+    ("LLS", CurveDataType::ShallowResistivity),
+    ("MSFL", CurveDataType::ShallowResistivity),
+    ("RXO", CurveDataType::ShallowResistivity),
+    ("RSHAL", CurveDataType::ShallowResistivity),
+    ("CALI", CurveDataType::Caliper),
+    ("CAL", CurveDataType::Caliper),
+    ("CALS", CurveDataType::Caliper),
+    ("CALIPER", CurveDataType::Caliper),
+    ("C1", CurveDataType::Caliper),
+    ("C2", CurveDataType::Caliper),
+    ("DT", CurveDataType::Sonic),
+    ("DTC", CurveDataType::Sonic),
+    ("AC", CurveDataType::Sonic),
+    ("SONIC", CurveDataType::Sonic),
+    ("DTS", CurveDataType::ShearSonic),
+    ("DTSH", CurveDataType::ShearSonic),
+    ("SP", CurveDataType::SpontaneousPotential),
+    ("SSP", CurveDataType::SpontaneousPotential),
+    ("PE", CurveDataType::PhotoelectricFactor),
+    ("PEF", CurveDataType::PhotoelectricFactor),
+    ("DEPT", CurveDataType::Depth),
+    ("DEPTH", CurveDataType::Depth),
+    ("MD", CurveDataType::Depth),
+];
+
+/// Suffixed variants of generic mnemonics (e.g., "GR_1", "RHOB.CORR")
+/// and curves computed by interpretation.
+const FALLBACK_PATTERNS: &[(&str, CurveDataType)] = &[
+    (r"GR[_.\-].+", CurveDataType::GammaRay),
+    (r"RHOB[_.\-].+", CurveDataType::Density),
+    (r"NPHI[_.\-].+", CurveDataType::NeutronPorosity),
+    (r"CALI[_.\-].+", CurveDataType::Caliper),
+    (r"DT[_.\-].+", CurveDataType::Sonic),
+    (r"DTS[_.\-].+", CurveDataType::ShearSonic),
+    (r"PEF?[_.\-].+", CurveDataType::PhotoelectricFactor),
+    (
+        r"(VSH|VCL|PHIE|PHIT|SW|SWT|SWE|BVW|PERM)([_.\-].*)?",
+        CurveDataType::Computed,
+    ),
+];
+
+/// Schlumberger tool mnemonics.
+const SCHLUMBERGER: &[(MatchKind, &str, CurveDataType)] = &[
+    (MatchKind::Exact, "HSGR", CurveDataType::GammaRay),
+    (MatchKind::Exact, "ECGR", CurveDataType::GammaRay),
+    (MatchKind::Exact, "EHGR", CurveDataType::GammaRay),
+    (MatchKind::Exact, "RHOZ", CurveDataType::Density),
+    (MatchKind::Exact, "TNPH", CurveDataType::NeutronPorosity),
+    (MatchKind::Exact, "NPOR", CurveDataType::NeutronPorosity),
+    (MatchKind::Exact, "APLC", CurveDataType::NeutronPorosity),
+    (MatchKind::Regex, "A[TFO]90", CurveDataType::DeepResistivity),
+    (
+        MatchKind::Regex,
+        "A[TFO](30|60)",
+        CurveDataType::MediumResistivity,
+    ),
+    (
+        MatchKind::Regex,
+        "A[TFO](10|20)",
+        CurveDataType::ShallowResistivity,
+    ),
+    (MatchKind::Exact, "HDRS", CurveDataType::DeepResistivity),
+    (MatchKind::Exact, "RLA5", CurveDataType::DeepResistivity),
+    (MatchKind::Exact, "HMRS", CurveDataType::MediumResistivity),
+    (
+        MatchKind::Regex,
+        "RLA[34]",
+        CurveDataType::MediumResistivity,
+    ),
+    (
+        MatchKind::Regex,
+        "RLA[12]",
+        CurveDataType::ShallowResistivity,
+    ),
+    (
+        MatchKind::Regex,
+        "RXO[Z8]",
+        CurveDataType::ShallowResistivity,
+    ),
+    (MatchKind::Exact, "DTCO", CurveDataType::Sonic),
+    (MatchKind::Exact, "DTSM", CurveDataType::ShearSonic),
+    (MatchKind::Exact, "PEFZ", CurveDataType::PhotoelectricFactor),
+    (MatchKind::Exact, "HCAL", CurveDataType::Caliper),
+];
+
+/// Halliburton tool mnemonics.
+const HALLIBURTON: &[(MatchKind, &str, CurveDataType)] = &[
+    (MatchKind::Exact, "HLLD", CurveDataType::DeepResistivity),
+    (MatchKind::Exact, "HLLS", CurveDataType::ShallowResistivity),
+    (MatchKind::Regex, "M2R[9X]", CurveDataType::DeepResistivity),
+    (
+        MatchKind::Regex,
+        "M2R[36]",
+        CurveDataType::MediumResistivity,
+    ),
+    (
+        MatchKind::Regex,
+        "M2R[12]",
+        CurveDataType::ShallowResistivity,
+    ),
+];
+
+/// Baker Hughes tool mnemonics.
+const BAKER_HUGHES: &[(MatchKind, &str, CurveDataType)] = &[
+    (MatchKind::Exact, "ZDEN", CurveDataType::Density),
+    (MatchKind::Exact, "CNC", CurveDataType::NeutronPorosity),
+    (MatchKind::Exact, "CNCF", CurveDataType::NeutronPorosity),
+    (MatchKind::Exact, "RD", CurveDataType::DeepResistivity),
+    (MatchKind::Exact, "RS", CurveDataType::ShallowResistivity),
+];
+
+/// Rules of the default dictionary.
+pub fn default_rules() -> Vec<MnemonicRule> {
+    let vendor = [
+        (Vendor::Schlumberger, SCHLUMBERGER),
+        (Vendor::Halliburton, HALLIBURTON),
+        (Vendor::BakerHughes, BAKER_HUGHES),
+    ]
+    .into_iter()
+    .flat_map(|(vendor, rules)| {
+        rules
+            .iter()
+            .map(move |(kind, pattern, curve_type)| MnemonicRule {
+                pattern: pattern.to_string(),
+                kind: *kind,
+                curve_type: *curve_type,
+                vendor: Some(vendor),
+                priority: VENDOR_PRIORITY,
+            })
+    });
+    let generic = GENERIC_MNEMONICS
+        .iter()
+        .map(|(pattern, curve_type)| MnemonicRule::exact(*pattern, *curve_type));
+    let fallback = FALLBACK_PATTERNS
+        .iter()
+        .map(|(pattern, curve_type)| MnemonicRule::regex(*pattern, *curve_type));
+
+    vendor.chain(generic).chain(fallback).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_dictionary() {
+        let dictionary = MnemonicDictionary::defaults();
+
+        assert_eq!(dictionary.detect("GR"), Some(CurveDataType::GammaRay));
+        assert_eq!(dictionary.detect("gr_edtc"), Some(CurveDataType::GammaRay));
+        assert_eq!(
+            dictionary.detect("AT90"),
+            Some(CurveDataType::DeepResistivity)
+        );
+        assert_eq!(dictionary.detect("ZDEN"), Some(CurveDataType::Density));
+        assert_eq!(dictionary.detect("PHIE_2"), Some(CurveDataType::Computed));
+
+        // Substrings of other mnemonics no longer match
+        assert_eq!(dictionary.detect("GRD"), None);
+        assert_eq!(dictionary.detect("DGR"), None);
+        assert_eq!(dictionary.detect("TEMP"), None);
+        assert_eq!(dictionary.detect("SPHI"), None);
+        assert_eq!(dictionary.detect("WRTX"), None);
+
+        let rule = dictionary.matching_rule("DTSM").unwrap();
+        assert_eq!(rule.vendor, Some(Vendor::Schlumberger));
+        assert_eq!(rule.curve_type, CurveDataType::ShearSonic);
+    }
+
+    #[test]
+    fn test_workspace_overrides() {
+        let dictionary = MnemonicDictionary::defaults()
+            .with_overrides(vec![
+                MnemonicRule::exact("GRD", CurveDataType::GammaRay),
+                // Replaces the Baker Hughes rule for RD
+                MnemonicRule::exact("rd", CurveDataType::Resistivity)
+                    .with_priority(VENDOR_PRIORITY),
+                MnemonicRule::regex("TEMP.*", CurveDataType::Unknown)
+                    .with_priority(WORKSPACE_PRIORITY),
+            ])
+            .unwrap();
+
+        assert_eq!(dictionary.detect("GRD"), Some(CurveDataType::GammaRay));
+        assert_eq!(dictionary.detect("RD"), Some(CurveDataType::Resistivity));
+        assert_eq!(dictionary.detect("TEMP2"), Some(CurveDataType::Unknown));
+        assert_eq!(
+            dictionary
+                .rules()
+                .filter(|(_, workspace)| *workspace)
+                .count(),
+            3
+        );
+
+        let invalid = MnemonicDictionary::defaults()
+            .with_overrides(vec![MnemonicRule::regex("GR(", CurveDataType::GammaRay)]);
+        assert!(matches!(invalid, Err(UdfError::InvalidMnemonicRule(_))));
+
+        // Rules saved from the UI without a priority rank as workspace rules
+        let rule: MnemonicRule = serde_json::from_value(serde_json::json!({
+            "pattern": "RD",
+            "kind": "exact",
+            "curve_type": "Density"
+        }))
+        .unwrap();
+        assert_eq!(rule.priority, WORKSPACE_PRIORITY);
+        let dictionary = MnemonicDictionary::defaults()
+            .with_overrides(vec![rule])
+            .unwrap();
+        assert_eq!(dictionary.detect("RD"), Some(CurveDataType::Density));
+    }
+}
@@ -11,26 +11,49 @@
 //! - **Append-only outputs**: Derived curves are new artifacts with provenance
 //! - **Type-safe curve parameters**: UDFs declare which curve types they accept
 
+pub mod alignment;
+pub mod batch;
+pub mod cache;
+pub mod constraints;
 pub mod context;
 pub mod data_loader;
 pub mod engine;
 pub mod error;
+pub mod interval;
+pub mod lineage;
+pub mod mnemonics;
+pub mod nulls;
 pub mod output_writer;
 pub mod parameters;
+pub mod preflight;
+pub mod prov;
 pub mod providers;
 pub mod registry;
+pub mod staleness;
+pub mod taxonomy;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod types;
+pub mod units;
+pub mod verify;
+pub mod workflow;
 
 // Re-export main types
+pub use cache::{CachedResult, ResultCache};
+pub use constraints::ParameterConstraint;
 pub use context::{CancellationToken, ExecutionContext, ProgressState};
 pub use data_loader::{DataForgeCurveLoader, init_compute_schema, save_execution_record};
 pub use engine::ExecutionEngine;
 pub use error::{UdfError, ValidationError};
-pub use parameters::{CurveParameter, NumericParameter, ParameterDefinition, ParameterValue};
+pub use parameters::{
+    BooleanParameter, ChoiceOption, ChoiceParameter, CurveListParameter, CurveParameter,
+    DepthRangeParameter, IntegerParameter, NumericParameter, ParameterDefinition, ParameterValue,
+    StringParameter, ZoneParameter,
+};
 pub use registry::UdfRegistry;
 pub use types::{
-    CurveData, CurveDataType, ExecutionRecord, ExecutionStatus, InputReference, UdfMetadata,
-    UdfOutput,
+    CurveData, CurveDataType, ExecutionRecord, ExecutionStatus, InputReference, NamedOutput,
+    OutputDeclaration, OutputReference, UdfMetadata, UdfOutput, UpstreamInput, WorkflowStep,
 };
 
 use std::sync::Arc;
@@ -77,6 +100,12 @@ pub trait Udf: Send + Sync {
     /// Parameter definitions for this UDF
     fn parameter_definitions(&self) -> Vec<Box<dyn ParameterDefinition>>;
 
+    /// Rules between parameters (ordering, mutual exclusion, required-if).
+    /// Evaluated by the engine after each parameter was validated.
+    fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+        Vec::new()
+    }
+
     /// Check if this UDF can execute given the current context.
     /// Used for dynamic availability (e.g., based on data state).
     fn can_execute(&self, context: &ExecutionContext) -> bool {
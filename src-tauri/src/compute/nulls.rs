@@ -0,0 +1,383 @@
+//! Sample count and null constraints of curve parameters.
+//!
+//! Curve parameters may require a minimum number of valid samples
+//! (`with_min_length`) and disallow nulls (`require_no_nulls`). The engine
+//! enforces both on the loaded curves before the UDF runs. For curves
+//! that disallow nulls, the `null_policy` parameter the engine adds picks
+//! what happens to null samples:
+//!
+//! - `fail`: the execution fails
+//! - `interpolate`: gaps are filled linearly by depth
+//! - `mask`: samples where any such curve is null are hidden from the UDF
+//!   and come back as nulls in the outputs
+
+use crate::compute::error::ValidationError;
+use crate::compute::interval::DepthInterval;
+use crate::compute::parameters::{ChoiceParameter, ParameterDefinition, ParameterValue};
+use crate::compute::types::{CurveData, OutputCurveData};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Name of the null policy parameter added to UDFs by the engine.
+pub const NULL_POLICY_PARAMETER: &str = "null_policy";
+
+/// What to do with null samples in curves that disallow nulls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullPolicy {
+    #[default]
+    Fail,
+    Interpolate,
+    Mask,
+}
+
+impl NullPolicy {
+    /// Policy by its parameter value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "fail" => Some(Self::Fail),
+            "interpolate" => Some(Self::Interpolate),
+            "mask" => Some(Self::Mask),
+            _ => None,
+        }
+    }
+
+    /// Policy selected by the parameters of an execution.
+    pub fn from_parameters(parameters: &HashMap<String, ParameterValue>) -> Self {
+        parameters
+            .get(NULL_POLICY_PARAMETER)
+            .and_then(|v| v.as_str())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a curve or curve list parameter accepts curves with nulls.
+pub fn allows_nulls(def: &dyn ParameterDefinition) -> bool {
+    def.curve_constraints().map_or(true, |c| c.allow_nulls)
+}
+
+/// Minimum number of valid samples required by a curve parameter.
+pub fn min_length(def: &dyn ParameterDefinition) -> Option<usize> {
+    def.curve_constraints().and_then(|c| c.min_length)
+}
+
+/// Add the engine's null policy parameter to a UDF's definitions.
+///
+/// Only UDFs with a curve parameter that disallows nulls get it.
+pub fn with_null_policy_parameter(
+    mut definitions: Vec<Box<dyn ParameterDefinition>>,
+) -> Vec<Box<dyn ParameterDefinition>> {
+    let needs_policy = definitions
+        .iter()
+        .any(|d| matches!(d.param_type(), "curve" | "curve_list") && !allows_nulls(d.as_ref()));
+    let is_taken = definitions
+        .iter()
+        .any(|d| d.name() == NULL_POLICY_PARAMETER);
+    if needs_policy && !is_taken {
+        definitions.push(Box::new(
+            ChoiceParameter::optional(NULL_POLICY_PARAMETER, "Null Samples")
+                .with_description("How to handle null samples in curves that must not have any")
+                .with_option("fail", "Fail")
+                .with_option("interpolate", "Interpolate gaps")
+                .with_option("mask", "Skip null samples")
+                .with_default("fail"),
+        ));
+    }
+    definitions
+}
+
+/// The loaded curves of a curve or curve list parameter.
+pub struct CurveInput<'a> {
+    pub def: &'a dyn ParameterDefinition,
+    pub curves: Vec<Arc<CurveData>>,
+}
+
+/// Name of the curve at `index` of a parameter, as reported in errors.
+fn curve_name(def: &dyn ParameterDefinition, index: usize) -> String {
+    if def.param_type() == "curve_list" {
+        format!("{}[{}]", def.name(), index)
+    } else {
+        def.name().to_string()
+    }
+}
+
+/// Samples hidden from the UDF by the mask policy.
+#[derive(Debug, Clone)]
+pub struct RowMask {
+    /// Depths of the curves before masking
+    depths: Arc<Vec<f64>>,
+    /// Indices of the samples the UDF saw
+    kept: Vec<usize>,
+}
+
+impl RowMask {
+    /// Number of samples hidden from the UDF.
+    pub fn masked_count(&self) -> usize {
+        self.depths.len() - self.kept.len()
+    }
+
+    /// Put an output back on the unmasked depths, with nulls at the
+    /// masked samples.
+    ///
+    /// Outputs that are not on the masked grid (e.g. resampled) are left
+    /// unchanged.
+    pub fn expand_output(&self, output: &mut OutputCurveData) {
+        if output.values.len() != self.kept.len() {
+            return;
+        }
+        let mut values = vec![None; self.depths.len()];
+        for (value, index) in output.values.iter().zip(&self.kept) {
+            values[*index] = *value;
+        }
+        output.values = values;
+        output.depths = self.depths.as_ref().clone();
+    }
+}
+
+/// Enforce the sample count and null constraints of the loaded curves.
+///
+/// Nulls outside `interval` come from interval masking and are not
+/// counted. Curves may be replaced by interpolated or masked copies; with
+/// the mask policy the returned `RowMask` restores the outputs.
+pub fn enforce(
+    inputs: &mut [CurveInput<'_>],
+    policy: NullPolicy,
+    interval: Option<&DepthInterval>,
+) -> Result<Option<RowMask>, Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    for input in inputs.iter_mut() {
+        if allows_nulls(input.def) || policy == NullPolicy::Mask {
+            continue;
+        }
+        let def = input.def;
+        for (index, curve) in input.curves.iter_mut().enumerate() {
+            let nulls = null_count(curve, interval);
+            if nulls == 0 {
+                continue;
+            }
+
+            if policy == NullPolicy::Interpolate {
+                let filled = interpolate_gaps(curve, interval);
+                let remaining = null_count(&filled, interval);
+                if remaining == 0 {
+                    *curve = Arc::new(filled);
+                    continue;
+                }
+                errors.push(
+                    ValidationError::new(
+                        curve_name(def, index),
+                        format!(
+                            "{} null samples at the ends of {} cannot be interpolated",
+                            remaining, curve.mnemonic
+                        ),
+                    )
+                    .with_suggestion("Skip null samples instead, or narrow the depth range"),
+                );
+            } else {
+                errors.push(
+                    ValidationError::new(
+                        curve_name(def, index),
+                        format!(
+                            "{} has {} null samples, but '{}' does not allow nulls",
+                            curve.mnemonic,
+                            nulls,
+                            def.label()
+                        ),
+                    )
+                    .with_suggestion("Interpolate gaps or skip null samples"),
+                );
+            }
+        }
+    }
+
+    let row_mask = if policy == NullPolicy::Mask && errors.is_empty() {
+        mask_null_rows(inputs)
+    } else {
+        None
+    };
+
+    for input in inputs.iter() {
+        let Some(required) = min_length(input.def) else {
+            continue;
+        };
+        for (index, curve) in input.curves.iter().enumerate() {
+            let valid = curve.values.iter().filter(|v| v.is_some()).count();
+            if valid < required {
+                errors.push(
+                    ValidationError::new(
+                        curve_name(input.def, index),
+                        format!(
+                            "{} has {} valid samples, at least {} are required",
+                            curve.mnemonic, valid, required
+                        ),
+                    )
+                    .with_suggestion("Select a longer curve or widen the depth range"),
+                );
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(row_mask)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Number of null samples within the interval (or the whole curve).
+fn null_count(curve: &CurveData, interval: Option<&DepthInterval>) -> usize {
+    curve
+        .iter()
+        .filter(|(depth, value)| value.is_none() && interval.map_or(true, |i| i.contains(*depth)))
+        .count()
+}
+
+/// Copy of a curve with the gaps between valid samples filled linearly by
+/// depth. Nulls before the first and after the last valid sample stay, as
+/// do samples outside the interval.
+fn interpolate_gaps(curve: &CurveData, interval: Option<&DepthInterval>) -> CurveData {
+    let mut values = curve.values.clone();
+    let mut previous: Option<(usize, f64, f64)> = None;
+
+    for (index, (depth, value)) in curve.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        if let Some((start, d0, v0)) = previous {
+            let gap = start + 1..index;
+            for (gap_depth, gap_value) in curve.depths[gap.clone()].iter().zip(&mut values[gap]) {
+                if interval.map_or(true, |i| i.contains(*gap_depth)) {
+                    *gap_value = Some(v0 + (value - v0) * (gap_depth - d0) / (depth - d0));
+                }
+            }
+        }
+        previous = Some((index, depth, value));
+    }
+
+    CurveData {
+        values,
+        ..curve.clone()
+    }
+}
+
+/// Hide the samples where any curve disallowing nulls is null from all
+/// inputs.
+///
+/// Returns `None` if nothing is masked, or if the curves are not on one
+/// depth grid (left to the depth compatibility check).
+fn mask_null_rows(inputs: &mut [CurveInput<'_>]) -> Option<RowMask> {
+    let depths = inputs.iter().flat_map(|i| &i.curves).next()?.depths.clone();
+    let on_grid = inputs
+        .iter()
+        .flat_map(|i| &i.curves)
+        .all(|c| c.len() == depths.len() && c.depths.len() == depths.len());
+    if !on_grid {
+        return None;
+    }
+
+    let kept: Vec<usize> = (0..depths.len())
+        .filter(|row| {
+            inputs
+                .iter()
+                .filter(|i| !allows_nulls(i.def))
+                .flat_map(|i| &i.curves)
+                .all(|c| c.values[*row].is_some())
+        })
+        .collect();
+    if kept.len() == depths.len() {
+        return None;
+    }
+
+    let kept_depths = Arc::new(kept.iter().map(|row| depths[*row]).collect::<Vec<_>>());
+    for input in inputs.iter_mut() {
+        for curve in input.curves.iter_mut() {
+            *curve = Arc::new(CurveData {
+                depths: kept_depths.clone(),
+                values: kept.iter().map(|row| curve.values[*row]).collect(),
+                ..curve.as_ref().clone()
+            });
+        }
+    }
+
+    Some(RowMask { depths, kept })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::parameters::CurveParameter;
+    use crate::compute::test_support::curve;
+    use crate::compute::types::CurveDataType;
+
+    /// GR curve sampled every metre from 100.
+    fn gr(values: Vec<Option<f64>>) -> Arc<CurveData> {
+        let depths = (0..values.len()).map(|i| 100.0 + i as f64).collect();
+        Arc::new(curve("GR", "gAPI", depths, values))
+    }
+
+    #[test]
+    fn test_null_policies() {
+        let strict = CurveParameter::required("gr", "Gamma Ray").require_no_nulls();
+        let lenient = CurveParameter::required("rt", "Resistivity");
+        let gappy = vec![Some(10.0), None, Some(30.0), None];
+        let inputs = || {
+            vec![
+                CurveInput {
+                    def: &strict,
+                    curves: vec![gr(gappy.clone())],
+                },
+                CurveInput {
+                    def: &lenient,
+                    curves: vec![gr(vec![Some(1.0), Some(2.0), None, Some(4.0)])],
+                },
+            ]
+        };
+
+        let errors = enforce(&mut inputs(), NullPolicy::Fail, None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "gr");
+
+        // The trailing null cannot be interpolated unless outside the interval
+        assert!(enforce(&mut inputs(), NullPolicy::Interpolate, None).is_err());
+        let mut interpolated = inputs();
+        let interval = DepthInterval::new(100.0, 102.0);
+        assert!(
+            enforce(&mut interpolated, NullPolicy::Interpolate, Some(&interval))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(interpolated[0].curves[0].values[1], Some(20.0));
+
+        let mut masked = inputs();
+        let row_mask = enforce(&mut masked, NullPolicy::Mask, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(row_mask.masked_count(), 2);
+        assert_eq!(masked[1].curves[0].values, vec![Some(1.0), None]);
+        assert_eq!(*masked[1].curves[0].depths, vec![100.0, 102.0]);
+
+        let mut output = OutputCurveData {
+            mnemonic: "OUT".to_string(),
+            curve_type: CurveDataType::Computed,
+            unit: String::new(),
+            depths: vec![100.0, 102.0],
+            values: vec![Some(1.0), Some(3.0)],
+            description: None,
+        };
+        row_mask.expand_output(&mut output);
+        assert_eq!(output.values, vec![Some(1.0), None, Some(3.0), None]);
+        assert_eq!(output.depths.len(), 4);
+    }
+
+    #[test]
+    fn test_min_length_counts_valid_samples() {
+        let def = CurveParameter::required("gr", "Gamma Ray").with_min_length(3);
+        let mut inputs = vec![CurveInput {
+            def: &def,
+            curves: vec![gr(vec![Some(1.0), None, Some(3.0), None])],
+        }];
+        let errors = enforce(&mut inputs, NullPolicy::Fail, None).unwrap_err();
+        assert!(errors[0].message.contains("2 valid samples"));
+    }
+}
@@ -1,20 +1,38 @@
 //! Output writer for persisting derived curves back to DataForge.
 //!
 //! This module handles the atomic write of UDF outputs to DataForge's
-//! blob store and database, ensuring full provenance tracking.
+//! blob store and database, ensuring full provenance tracking. Blobs use
+//! DataForge's native Parquet schema so derived curves load like any other.
 
+use crate::compute::data_loader::{init_compute_schema, save_execution_record};
 use crate::compute::error::UdfError;
-use crate::compute::types::{ExecutionRecord, OutputCurveData};
+use crate::compute::types::{ExecutionRecord, NamedOutput, OutputCurveData, OutputReference};
+use arrow::array::Float64Array;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
 use rusqlite::Connection;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Name of the depth column in DataForge's native curve blobs
+pub const DEPTH_COLUMN: &str = "DEPTH";
+
+/// Value recorded in `curves.created_by` for curves written by Compute
+const CREATED_BY: &str = "DataForge Compute";
+
 /// Result of registering an execution output.
 #[derive(Debug, Clone)]
 pub struct RegisteredOutput {
+    /// Output name as declared by the UDF
+    pub name: String,
     /// The new curve ID
     pub curve_id: Uuid,
     /// The parquet content hash
@@ -23,6 +41,127 @@ pub struct RegisteredOutput {
     pub blob_path: PathBuf,
 }
 
+/// A content-addressed blob written to the blob store.
+#[derive(Debug, Clone)]
+pub struct WrittenBlob {
+    /// SHA-256 hash of the blob content
+    pub hash: String,
+    /// Path to the blob file
+    pub path: PathBuf,
+    /// Size of the blob in bytes
+    pub size_bytes: u64,
+    /// Whether this write created the blob (false if it already existed)
+    pub created: bool,
+}
+
+/// Summary statistics stored alongside a curve in DataForge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveStatistics {
+    pub min_depth: Option<f64>,
+    pub max_depth: Option<f64>,
+    pub sample_count: i64,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub mean_value: Option<f64>,
+    pub null_count: i64,
+}
+
+impl CurveStatistics {
+    /// Compute statistics for output curve data.
+    pub fn from_output(output: &OutputCurveData) -> Self {
+        let (min_depth, max_depth) = min_max(output.depths.iter().copied());
+
+        let valid_values: Vec<f64> = output.values.iter().filter_map(|v| *v).collect();
+        let (min_value, max_value) = min_max(valid_values.iter().copied());
+        let mean_value = if valid_values.is_empty() {
+            None
+        } else {
+            Some(valid_values.iter().sum::<f64>() / valid_values.len() as f64)
+        };
+
+        Self {
+            min_depth,
+            max_depth,
+            sample_count: output.depths.len() as i64,
+            min_value,
+            max_value,
+            mean_value,
+            null_count: output.values.iter().filter(|v| v.is_none()).count() as i64,
+        }
+    }
+}
+
+/// Minimum and maximum of a sequence, or `None` if it is empty
+fn min_max(values: impl Iterator<Item = f64>) -> (Option<f64>, Option<f64>) {
+    values.fold((None, None), |(min, max), v| {
+        (
+            Some(min.map_or(v, |m: f64| m.min(v))),
+            Some(max.map_or(v, |m: f64| m.max(v))),
+        )
+    })
+}
+
+/// Create Parquet bytes from output curve data.
+///
+/// Uses DataForge's native curve schema `[DEPTH, {mnemonic}]` (both
+/// Float64, values nullable) with SNAPPY compression, so blobs can be
+/// read back by `DataForgeCurveLoader`.
+pub fn create_parquet_bytes(output: &OutputCurveData) -> Result<Vec<u8>, UdfError> {
+    if output.depths.len() != output.values.len() {
+        return Err(UdfError::IncompatibleData(format!(
+            "Output has {} depths but {} values",
+            output.depths.len(),
+            output.values.len()
+        )));
+    }
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(DEPTH_COLUMN, DataType::Float64, false),
+        Field::new(&output.mnemonic, DataType::Float64, true),
+    ]));
+
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Float64Array::from(output.depths.clone())),
+            Arc::new(Float64Array::from(output.values.clone())),
+        ],
+    )
+    .map_err(|e| UdfError::SerializationError(format!("Failed to create record batch: {}", e)))?;
+
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut buf = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buf, schema, Some(props)).map_err(|e| {
+        UdfError::SerializationError(format!("Failed to create parquet writer: {}", e))
+    })?;
+    writer
+        .write(&batch)
+        .map_err(|e| UdfError::SerializationError(format!("Failed to write parquet: {}", e)))?;
+    writer.close().map_err(|e| {
+        UdfError::SerializationError(format!("Failed to close parquet writer: {}", e))
+    })?;
+
+    Ok(buf)
+}
+
+/// Content hash an output would be stored under in the blob store.
+///
+/// Parquet encoding is deterministic, so this equals the hash of the blob
+/// written if the output is later saved.
+pub fn content_hash(output: &OutputCurveData) -> Result<String, UdfError> {
+    Ok(sha256_hex(&create_parquet_bytes(output)?))
+}
+
+/// Hex-encoded SHA-256 of blob content
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
 /// Writer for persisting UDF outputs to DataForge.
 pub struct OutputWriter {
     /// Path to the blobs directory
@@ -37,18 +176,11 @@ impl OutputWriter {
 
     /// Write output curve data to a Parquet blob.
     ///
-    /// Returns the SHA-256 hash of the content and the blob path.
-    pub fn write_parquet_blob(
-        &self,
-        output: &OutputCurveData,
-    ) -> Result<(String, PathBuf), UdfError> {
+    /// Returns the SHA-256 hash of the content, the blob path and its size.
+    pub fn write_parquet_blob(&self, output: &OutputCurveData) -> Result<WrittenBlob, UdfError> {
         // Create Parquet data in memory
-        let parquet_bytes = self.create_parquet_bytes(output)?;
-
-        // Compute SHA-256 hash
-        let mut hasher = Sha256::new();
-        hasher.update(&parquet_bytes);
-        let hash = format!("{:x}", hasher.finalize());
+        let parquet_bytes = create_parquet_bytes(output)?;
+        let hash = sha256_hex(&parquet_bytes);
 
         // Determine blob path (content-addressed)
         let blob_path = self
@@ -57,9 +189,16 @@ impl OutputWriter {
             .join(&hash[2..4])
             .join(format!("{}.parquet", hash));
 
+        let blob = WrittenBlob {
+            hash,
+            path: blob_path.clone(),
+            size_bytes: parquet_bytes.len() as u64,
+            created: false,
+        };
+
         // Check if blob already exists (content-addressed deduplication)
         if blob_path.exists() {
-            return Ok((hash, blob_path));
+            return Ok(blob);
         }
 
         // Create directories if needed
@@ -87,129 +226,278 @@ impl OutputWriter {
             UdfError::IoError(e)
         })?;
 
-        Ok((hash, blob_path))
+        Ok(WrittenBlob {
+            created: true,
+            ..blob
+        })
     }
 
-    /// Create Parquet bytes from output curve data.
-    ///
-    /// For MVP, we create a simple CSV-like format that DuckDB can read.
-    /// In production, this should use Arrow/Parquet libraries.
-    fn create_parquet_bytes(&self, output: &OutputCurveData) -> Result<Vec<u8>, UdfError> {
-        // For MVP, create a simple binary format that we can later upgrade to Parquet
-        // Format: CSV with depth,value columns (DuckDB can read this)
-        //
-        // TODO: Replace with proper Parquet writing using arrow-rs
-        let mut csv_content = String::from("depth,value\n");
-
-        for (depth, value) in output.depths.iter().zip(output.values.iter()) {
-            match value {
-                Some(v) => csv_content.push_str(&format!("{},{}\n", depth, v)),
-                None => csv_content.push_str(&format!("{},\n", depth)),
-            }
-        }
-
-        Ok(csv_content.into_bytes())
+    /// Register a written blob in DataForge's blob registry.
+    pub fn register_blob(&self, db: &Connection, blob: &WrittenBlob) -> Result<(), UdfError> {
+        db.execute(
+            "INSERT OR IGNORE INTO blob_registry (hash, size_bytes) VALUES (?1, ?2)",
+            rusqlite::params![blob.hash, blob.size_bytes as i64],
+        )?;
+        Ok(())
     }
 
     /// Register the output curve in DataForge's database.
     ///
     /// This creates a new curve record with provenance linking back to
-    /// the execution record.
+    /// the execution that produced it.
     pub fn register_curve(
         &self,
         db: &Connection,
         well_id: Uuid,
         output: &OutputCurveData,
         parquet_hash: &str,
-        execution_record: &ExecutionRecord,
+        execution_id: Uuid,
     ) -> Result<Uuid, UdfError> {
         let curve_id = Uuid::new_v4();
-        let now = chrono::Utc::now().to_rfc3339();
-
-        // Calculate statistics
-        let (min_depth, max_depth) = if output.depths.is_empty() {
-            (None, None)
-        } else {
-            (
-                output.depths.first().copied(),
-                output.depths.last().copied(),
-            )
-        };
-
-        let row_count = output.depths.len() as i64;
-
-        // Calculate value statistics
-        let valid_values: Vec<f64> = output.values.iter().filter_map(|v| *v).collect();
-        let (min_value, max_value) = if valid_values.is_empty() {
-            (None, None)
-        } else {
-            let min = valid_values.iter().cloned().fold(f64::INFINITY, f64::min);
-            let max = valid_values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-            (Some(min), Some(max))
-        };
+        let stats = CurveStatistics::from_output(output);
+        let unit = (!output.unit.is_empty()).then_some(output.unit.as_str());
 
         db.execute(
-            "INSERT INTO curves (
+            r#"
+            INSERT INTO curves (
                 id, well_id, mnemonic, unit, description,
-                min_depth, max_depth, row_count, min_value, max_value,
-                parquet_hash, version, is_derived, source_execution_id,
-                created_at, updated_at
-            ) VALUES (
-                ?1, ?2, ?3, ?4, ?5,
-                ?6, ?7, ?8, ?9, ?10,
-                ?11, ?12, ?13, ?14,
-                ?15, ?16
-            )",
+                native_top_depth, native_bottom_depth, native_sample_count,
+                min_value, max_value, mean_value, null_count,
+                native_parquet_hash, quality_flag, is_derived, source_execution_id,
+                created_by
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            "#,
             rusqlite::params![
                 curve_id.to_string(),
                 well_id.to_string(),
                 output.mnemonic,
-                output.unit,
+                unit,
                 output.description,
-                min_depth,
-                max_depth,
-                row_count,
-                min_value,
-                max_value,
+                stats.min_depth,
+                stats.max_depth,
+                stats.sample_count,
+                stats.min_value,
+                stats.max_value,
+                stats.mean_value,
+                stats.null_count,
                 parquet_hash,
-                1, // version
+                "derived",
                 true, // is_derived
-                execution_record.id.to_string(),
-                now,
-                now,
+                execution_id.to_string(),
+                CREATED_BY,
             ],
         )?;
 
         Ok(curve_id)
     }
 
+    /// Save output curve data as a new version of an existing derived curve.
+    ///
+    /// The curve keeps its ID; its data, statistics and source execution
+    /// are replaced and its version is incremented, so curves derived from
+    /// it in turn show up as stale.
+    pub fn update_curve(
+        &self,
+        db: &Connection,
+        curve_id: Uuid,
+        output: &OutputCurveData,
+        parquet_hash: &str,
+        execution_id: Uuid,
+    ) -> Result<(), UdfError> {
+        let stats = CurveStatistics::from_output(output);
+        let unit = (!output.unit.is_empty()).then_some(output.unit.as_str());
+
+        let rows_affected = db.execute(
+            r#"
+            UPDATE curves SET
+                mnemonic = ?2, unit = ?3, description = ?4,
+                native_top_depth = ?5, native_bottom_depth = ?6, native_sample_count = ?7,
+                min_value = ?8, max_value = ?9, mean_value = ?10, null_count = ?11,
+                native_parquet_hash = ?12, gridded_parquet_hash = NULL,
+                source_execution_id = ?13, version = COALESCE(version, 1) + 1
+            WHERE id = ?1 AND COALESCE(is_derived, 0) = 1
+            "#,
+            rusqlite::params![
+                curve_id.to_string(),
+                output.mnemonic,
+                unit,
+                output.description,
+                stats.min_depth,
+                stats.max_depth,
+                stats.sample_count,
+                stats.min_value,
+                stats.max_value,
+                stats.mean_value,
+                stats.null_count,
+                parquet_hash,
+                execution_id.to_string(),
+            ],
+        )?;
+
+        if rows_affected == 0 {
+            return Err(UdfError::DatabaseError(format!(
+                "Derived curve {} not found",
+                curve_id
+            )));
+        }
+        Ok(())
+    }
+
     /// Perform atomic commit of execution result.
     ///
-    /// This writes the blob and registers the curve in a single transaction.
+    /// The blobs are written first via temp file plus rename. The blob
+    /// registry rows, the curve rows and the execution record are then
+    /// written in a single transaction, so either all of them land or none
+    /// do. If anything fails, blobs written by this call that nothing
+    /// references are removed again.
+    ///
+    /// On success the execution record is updated with the saved outputs;
+    /// `output_curve_id` and `output_parquet_hash` refer to the first one.
     pub fn commit_execution(
         &self,
-        db: &Connection,
+        db: &mut Connection,
         well_id: Uuid,
-        output: &OutputCurveData,
+        outputs: &[NamedOutput],
+        execution_record: &mut ExecutionRecord,
+    ) -> Result<Vec<RegisteredOutput>, UdfError> {
+        self.commit_execution_replacing(db, well_id, outputs, &HashMap::new(), execution_record)
+    }
+
+    /// Atomic commit of a recomputed execution result.
+    ///
+    /// Like `commit_execution`, but outputs named in `replace` are saved as
+    /// a new version of the given derived curve instead of as a new curve.
+    pub fn commit_execution_replacing(
+        &self,
+        db: &mut Connection,
+        well_id: Uuid,
+        outputs: &[NamedOutput],
+        replace: &HashMap<String, Uuid>,
+        execution_record: &mut ExecutionRecord,
+    ) -> Result<Vec<RegisteredOutput>, UdfError> {
+        if outputs.is_empty() {
+            return Err(UdfError::ExecutionFailed(
+                "No outputs to commit".to_string(),
+            ));
+        }
+
+        init_compute_schema(db)?;
+        ensure_derived_curve_columns(db)?;
+
+        // Write blobs first (outside transaction - idempotent due to content addressing)
+        let mut blobs = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            match self.write_parquet_blob(&output.curve_data) {
+                Ok(blob) => blobs.push(blob),
+                Err(e) => {
+                    self.remove_orphaned_blobs(db, &blobs);
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut committed_record = execution_record.clone();
+
+        match self.commit_rows(db, well_id, outputs, &blobs, replace, &mut committed_record) {
+            Ok(registered) => {
+                *execution_record = committed_record;
+                Ok(registered)
+            }
+            Err(e) => {
+                self.remove_orphaned_blobs(db, &blobs);
+                Err(e)
+            }
+        }
+    }
+
+    /// Write the blob, curve and execution record rows in one transaction.
+    fn commit_rows(
+        &self,
+        db: &mut Connection,
+        well_id: Uuid,
+        outputs: &[NamedOutput],
+        blobs: &[WrittenBlob],
+        replace: &HashMap<String, Uuid>,
         execution_record: &mut ExecutionRecord,
-    ) -> Result<RegisteredOutput, UdfError> {
-        // Write blob first (outside transaction - idempotent due to content addressing)
-        let (parquet_hash, blob_path) = self.write_parquet_blob(output)?;
+    ) -> Result<Vec<RegisteredOutput>, UdfError> {
+        let tx = db.transaction()?;
 
-        // Update execution record with output info
-        execution_record.output_parquet_hash = Some(parquet_hash.clone());
+        let mut registered = Vec::with_capacity(outputs.len());
+        let mut references = Vec::with_capacity(outputs.len());
+        for (output, blob) in outputs.iter().zip(blobs) {
+            self.register_blob(&tx, blob)?;
+            let curve_id = match replace.get(&output.name) {
+                Some(&curve_id) => {
+                    self.update_curve(
+                        &tx,
+                        curve_id,
+                        &output.curve_data,
+                        &blob.hash,
+                        execution_record.id,
+                    )?;
+                    curve_id
+                }
+                None => self.register_curve(
+                    &tx,
+                    well_id,
+                    &output.curve_data,
+                    &blob.hash,
+                    execution_record.id,
+                )?,
+            };
 
-        // Register curve in database (should be in a transaction in production)
-        let curve_id = self.register_curve(db, well_id, output, &parquet_hash, execution_record)?;
+            references.push(OutputReference {
+                name: output.name.clone(),
+                curve_id,
+                mnemonic: output.curve_data.mnemonic.clone(),
+                parquet_hash: blob.hash.clone(),
+            });
+            registered.push(RegisteredOutput {
+                name: output.name.clone(),
+                curve_id,
+                parquet_hash: blob.hash.clone(),
+                blob_path: blob.path.clone(),
+            });
+        }
 
-        // Update execution record with curve ID
-        execution_record.output_curve_id = Some(curve_id);
+        execution_record.output_curve_id = references.first().map(|r| r.curve_id);
+        execution_record.output_parquet_hash = references.first().map(|r| r.parquet_hash.clone());
+        execution_record.outputs = references;
+        save_execution_record(&tx, execution_record)?;
 
-        Ok(RegisteredOutput {
-            curve_id,
-            parquet_hash,
-            blob_path,
-        })
+        tx.commit()?;
+        Ok(registered)
+    }
+
+    /// Remove blobs written by a failed commit if nothing references them.
+    fn remove_orphaned_blobs(&self, db: &Connection, blobs: &[WrittenBlob]) {
+        for blob in blobs {
+            self.remove_orphaned_blob(db, blob);
+        }
+    }
+
+    /// Remove a blob written by a failed commit if nothing references it.
+    ///
+    /// Blobs that already existed before the commit are left alone, as are
+    /// blobs another commit has registered in the meantime.
+    fn remove_orphaned_blob(&self, db: &Connection, blob: &WrittenBlob) {
+        if !blob.created {
+            return;
+        }
+
+        let registered: bool = db
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM blob_registry WHERE hash = ?1",
+                [&blob.hash],
+                |row| row.get(0),
+            )
+            .unwrap_or(true);
+
+        if !registered {
+            if let Err(e) = fs::remove_file(&blob.path) {
+                log::warn!("⚠️ Failed to remove orphaned blob {:?}: {}", blob.path, e);
+            }
+        }
     }
 }
 
@@ -240,3 +528,273 @@ pub fn ensure_derived_curve_columns(db: &Connection) -> Result<(), UdfError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_support::create_dataforge_db;
+    use crate::compute::types::{CurveDataType, ExecutionStatus};
+    use arrow::array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::collections::BTreeMap;
+
+    fn create_output() -> OutputCurveData {
+        OutputCurveData {
+            mnemonic: "VSH".to_string(),
+            curve_type: CurveDataType::Computed,
+            unit: "v/v".to_string(),
+            depths: vec![1000.0, 1000.5, 1001.0, 1001.5],
+            values: vec![Some(0.2), None, Some(0.6), Some(0.4)],
+            description: None,
+        }
+    }
+
+    fn temp_blobs_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("output_writer_{}", Uuid::new_v4()))
+    }
+
+    fn create_record() -> ExecutionRecord {
+        ExecutionRecord {
+            id: Uuid::new_v4(),
+            udf_id: "petro:vshale_linear".to_string(),
+            udf_version: "1.0.0".to_string(),
+            well_id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            inputs: vec![],
+            parameters: serde_json::json!({}),
+            parameter_conversions: vec![],
+            depth_intervals: BTreeMap::new(),
+            output_curve_id: None,
+            output_parquet_hash: None,
+            outputs: vec![],
+            started_at: chrono::Utc::now(),
+            completed_at: Some(chrono::Utc::now()),
+            compute_app_version: "0.1.0".to_string(),
+            status: ExecutionStatus::Completed,
+            error_message: None,
+            workflow: None,
+            cache_key: None,
+            cached_from: None,
+        }
+    }
+
+    /// VShale plus a flag curve, as a multi-output UDF would produce
+    fn create_outputs() -> Vec<NamedOutput> {
+        let flag = OutputCurveData {
+            mnemonic: "VSH_FLAG".to_string(),
+            unit: String::new(),
+            values: vec![Some(0.0), None, Some(1.0), Some(0.0)],
+            ..create_output()
+        };
+        vec![
+            NamedOutput {
+                name: "vshale".to_string(),
+                curve_data: create_output(),
+            },
+            NamedOutput {
+                name: "flag".to_string(),
+                curve_data: flag,
+            },
+        ]
+    }
+
+    fn count(db: &Connection, table: &str) -> i64 {
+        db.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_commit_execution_writes_all_rows() {
+        let blobs_dir = temp_blobs_dir();
+        let writer = OutputWriter::new(blobs_dir.clone());
+        let mut db = create_dataforge_db();
+        let mut record = create_record();
+
+        let registered = writer
+            .commit_execution(&mut db, record.well_id, &create_outputs(), &mut record)
+            .unwrap();
+
+        assert_eq!(registered.len(), 2);
+        assert!(registered.iter().all(|r| r.blob_path.exists()));
+        assert_eq!(record.output_curve_id, Some(registered[0].curve_id));
+        assert_eq!(
+            record.output_parquet_hash.as_deref(),
+            Some(registered[0].parquet_hash.as_str())
+        );
+        assert_eq!(record.outputs.len(), 2);
+        assert_eq!(record.outputs[1].name, "flag");
+        assert_eq!(record.outputs[1].curve_id, registered[1].curve_id);
+        assert_eq!(count(&db, "blob_registry"), 2);
+        assert_eq!(count(&db, "curves"), 2);
+
+        let (output_curve_id, outputs_json): (String, String) = db
+            .query_row(
+                "SELECT output_curve_id, outputs FROM execution_records WHERE id = ?1",
+                [record.id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(output_curve_id, registered[0].curve_id.to_string());
+        let outputs: Vec<OutputReference> = serde_json::from_str(&outputs_json).unwrap();
+        assert_eq!(outputs.len(), 2);
+
+        // Every output curve links back to the execution
+        let linked: i64 = db
+            .query_row(
+                "SELECT COUNT(*) FROM curves WHERE source_execution_id = ?1",
+                [record.id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(linked, 2);
+
+        fs::remove_dir_all(&blobs_dir).unwrap();
+    }
+
+    #[test]
+    fn test_recompute_saves_new_version_of_derived_curve() {
+        let blobs_dir = temp_blobs_dir();
+        let writer = OutputWriter::new(blobs_dir.clone());
+        let mut db = create_dataforge_db();
+        let mut record = create_record();
+        let registered = writer
+            .commit_execution(&mut db, record.well_id, &create_outputs(), &mut record)
+            .unwrap();
+
+        let mut recomputed = create_outputs();
+        recomputed[0].curve_data.values[0] = Some(0.3);
+        let mut replace = HashMap::new();
+        replace.insert("vshale".to_string(), registered[0].curve_id);
+        let mut rerun = create_record();
+
+        let replaced = writer
+            .commit_execution_replacing(&mut db, record.well_id, &recomputed, &replace, &mut rerun)
+            .unwrap();
+
+        // VShale updated in place, the flag output (not replaced) saved anew
+        assert_eq!(replaced[0].curve_id, registered[0].curve_id);
+        assert_ne!(replaced[0].parquet_hash, registered[0].parquet_hash);
+        assert_ne!(replaced[1].curve_id, registered[1].curve_id);
+        assert_eq!(count(&db, "curves"), 3);
+
+        let (version, hash, source): (i64, String, String) = db
+            .query_row(
+                "SELECT version, native_parquet_hash, source_execution_id FROM curves WHERE id = ?1",
+                [registered[0].curve_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(hash, replaced[0].parquet_hash);
+        assert_eq!(source, rerun.id.to_string());
+
+        // Only derived curves can be replaced
+        replace.insert("vshale".to_string(), Uuid::new_v4());
+        let result = writer.commit_execution_replacing(
+            &mut db,
+            record.well_id,
+            &recomputed,
+            &replace,
+            &mut create_record(),
+        );
+        assert!(result.is_err());
+
+        fs::remove_dir_all(&blobs_dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_commit_rolls_back_and_removes_blobs() {
+        let blobs_dir = temp_blobs_dir();
+        let writer = OutputWriter::new(blobs_dir.clone());
+        let mut db = create_dataforge_db();
+        // Missing the columns a commit writes, so the curve insert fails
+        db.execute_batch(
+            "DROP TABLE curves; CREATE TABLE curves (id TEXT PRIMARY KEY, is_derived INTEGER);",
+        )
+        .unwrap();
+        let mut record = create_record();
+
+        let result =
+            writer.commit_execution(&mut db, record.well_id, &create_outputs(), &mut record);
+        assert!(result.is_err());
+
+        // Nothing committed, record untouched, blob cleaned up
+        assert_eq!(count(&db, "blob_registry"), 0);
+        assert_eq!(count(&db, "execution_records"), 0);
+        assert_eq!(record.output_curve_id, None);
+        assert_eq!(record.output_parquet_hash, None);
+        assert!(record.outputs.is_empty());
+
+        for output in create_outputs() {
+            let rewritten = writer.write_parquet_blob(&output.curve_data).unwrap();
+            assert!(
+                rewritten.created,
+                "blob should have been removed by the failed commit"
+            );
+        }
+
+        fs::remove_dir_all(&blobs_dir).unwrap();
+    }
+
+    #[test]
+    fn test_blob_matches_dataforge_schema() {
+        let blobs_dir = temp_blobs_dir();
+        let writer = OutputWriter::new(blobs_dir.clone());
+        let blob = writer.write_parquet_blob(&create_output()).unwrap();
+
+        assert!(blob
+            .path
+            .starts_with(blobs_dir.join(&blob.hash[..2]).join(&blob.hash[2..4])));
+        assert_eq!(fs::metadata(&blob.path).unwrap().len(), blob.size_bytes);
+
+        let reader = SerializedFileReader::new(fs::File::open(&blob.path).unwrap()).unwrap();
+        let column = reader.metadata().row_group(0).column(1);
+        assert_eq!(column.compression(), Compression::SNAPPY);
+
+        let batch = ParquetRecordBatchReaderBuilder::try_new(fs::File::open(&blob.path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let schema = batch.schema();
+        assert_eq!(schema.field(0).name(), DEPTH_COLUMN);
+        assert_eq!(schema.field(1).name(), "VSH");
+
+        let values = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(values.len(), 4);
+        assert!(values.is_null(1));
+        assert_eq!(values.value(2), 0.6);
+
+        fs::remove_dir_all(&blobs_dir).unwrap();
+    }
+
+    #[test]
+    fn test_parquet_bytes_are_deterministic() {
+        let first = create_parquet_bytes(&create_output()).unwrap();
+        let second = create_parquet_bytes(&create_output()).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(content_hash(&create_output()).unwrap(), sha256_hex(&first));
+    }
+
+    #[test]
+    fn test_curve_statistics() {
+        let stats = CurveStatistics::from_output(&create_output());
+        assert_eq!(stats.min_depth, Some(1000.0));
+        assert_eq!(stats.max_depth, Some(1001.5));
+        assert_eq!(stats.sample_count, 4);
+        assert_eq!(stats.min_value, Some(0.2));
+        assert_eq!(stats.max_value, Some(0.6));
+        assert!((stats.mean_value.unwrap() - 0.4).abs() < 1e-10);
+        assert_eq!(stats.null_count, 1);
+    }
+}
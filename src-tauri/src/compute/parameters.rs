@@ -4,8 +4,9 @@
 //! to declare their inputs. Parameters support validation, default values,
 //! and constraints.
 
-use crate::compute::error::ValidationError;
-use crate::compute::types::CurveDataType;
+use crate::compute::error::{UdfError, ValidationError};
+use crate::compute::types::{CurveDataType, UnitFamily};
+use crate::compute::units;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -16,6 +17,8 @@ use std::fmt::Debug;
 pub enum ParameterValue {
     /// Curve reference by UUID
     Curve(uuid::Uuid),
+    /// List of curve references
+    CurveList(Vec<uuid::Uuid>),
     /// Numeric value (f64)
     Number(f64),
     /// Integer value
@@ -24,6 +27,15 @@ pub enum ParameterValue {
     String(String),
     /// Boolean value
     Boolean(bool),
+    /// Depth interval (top above base)
+    DepthRange { top: f64, base: f64 },
+    /// Zone between two well tops, by marker name
+    Zone {
+        top_marker: String,
+        base_marker: String,
+    },
+    /// Number entered in an explicit unit
+    Quantity { value: f64, unit: String },
     /// Optional value (None)
     Null,
 }
@@ -38,11 +50,28 @@ impl ParameterValue {
         }
     }
 
-    /// Try to get as f64
+    /// Try to get as a list of curve UUIDs
+    pub fn as_curve_list(&self) -> Option<&[uuid::Uuid]> {
+        match self {
+            ParameterValue::CurveList(ids) => Some(ids),
+            _ => None,
+        }
+    }
+
+    /// Try to get as f64 (quantities in the unit they were entered in)
     pub fn as_f64(&self) -> Option<f64> {
         match self {
             ParameterValue::Number(n) => Some(*n),
             ParameterValue::Integer(i) => Some(*i as f64),
+            ParameterValue::Quantity { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Try to get as (value, unit) quantity
+    pub fn as_quantity(&self) -> Option<(f64, &str)> {
+        match self {
+            ParameterValue::Quantity { value, unit } => Some((*value, unit)),
             _ => None,
         }
     }
@@ -72,6 +101,25 @@ impl ParameterValue {
         }
     }
 
+    /// Try to get as (top, base) depth interval
+    pub fn as_depth_range(&self) -> Option<(f64, f64)> {
+        match self {
+            ParameterValue::DepthRange { top, base } => Some((*top, *base)),
+            _ => None,
+        }
+    }
+
+    /// Try to get as (top marker, base marker) zone
+    pub fn as_zone(&self) -> Option<(&str, &str)> {
+        match self {
+            ParameterValue::Zone {
+                top_marker,
+                base_marker,
+            } => Some((top_marker, base_marker)),
+            _ => None,
+        }
+    }
+
     /// Check if value is null
     pub fn is_null(&self) -> bool {
         matches!(self, ParameterValue::Null)
@@ -103,6 +151,187 @@ pub trait ParameterDefinition: Send + Sync + Debug {
 
     /// Get parameter definition as JSON for frontend
     fn to_json(&self) -> serde_json::Value;
+
+    /// Constraints on the curves bound to the parameter (curve parameters only)
+    fn curve_constraints(&self) -> Option<CurveConstraints> {
+        None
+    }
+
+    /// Units of the parameter's value (numeric parameters only)
+    fn value_unit(&self) -> Option<ValueUnit> {
+        None
+    }
+}
+
+/// Unit a numeric parameter's value is converted to before execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitTarget {
+    /// Unit of the curve bound to the named curve parameter
+    Curve(String),
+    /// Depth unit of the well
+    Depth,
+}
+
+/// Units of a numeric parameter's value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueUnit {
+    /// Unit of values entered as plain numbers
+    pub unit: Option<String>,
+    /// Family of the units values may be entered in
+    pub family: Option<UnitFamily>,
+    /// Unit the value is converted to before execution
+    pub target: Option<UnitTarget>,
+}
+
+/// Constraints on the curves bound to a curve or curve list parameter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurveConstraints {
+    /// Allowed curve types, including their varieties (empty = any type allowed)
+    pub allowed_types: Vec<CurveDataType>,
+    /// Allowed `curve_properties` IDs, in addition to the allowed types
+    pub allowed_properties: Vec<String>,
+    /// Allowed unit families (empty = any unit allowed)
+    pub unit_families: Vec<UnitFamily>,
+    /// Unit the curve values are converted to before execution
+    pub unit: Option<String>,
+    /// Minimum number of data points required
+    pub min_length: Option<usize>,
+    /// Whether null values are allowed in the curve
+    pub allow_nulls: bool,
+}
+
+impl CurveConstraints {
+    /// Check if a curve type is allowed.
+    ///
+    /// Varieties of an allowed family are compatible with it, e.g. deep
+    /// resistivity for a resistivity input.
+    pub fn accepts_type(&self, curve_type: CurveDataType) -> bool {
+        (self.allowed_types.is_empty() && self.allowed_properties.is_empty())
+            || self
+                .allowed_types
+                .iter()
+                .any(|allowed| curve_type.satisfies(*allowed))
+    }
+
+    /// Check if a curve with the given type and property is allowed.
+    ///
+    /// A curve is allowed by its type, or by its property being listed.
+    pub fn accepts_curve(&self, curve_type: CurveDataType, property_id: Option<&str>) -> bool {
+        self.accepts_type(curve_type)
+            || property_id.is_some_and(|id| self.allowed_properties.iter().any(|p| p == id))
+    }
+
+    /// Allowed unit families, including the family of the expected unit
+    /// (empty = any unit allowed).
+    pub fn allowed_unit_families(&self) -> Vec<UnitFamily> {
+        let mut families = self.unit_families.clone();
+        if let Some(family) = self.unit.as_deref().and_then(UnitFamily::of) {
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+        families
+    }
+
+    /// Check if a unit is allowed.
+    ///
+    /// Units that are not recognized are accepted, since many curves are
+    /// loaded without a unit or with a vendor spelling.
+    pub fn accepts_unit(&self, unit: &str) -> bool {
+        let families = self.allowed_unit_families();
+        match UnitFamily::of(unit) {
+            Some(family) => families.is_empty() || families.contains(&family),
+            None => true,
+        }
+    }
+
+    /// All curve types the constraints accept.
+    pub fn accepted_types(&self) -> Vec<CurveDataType> {
+        CurveDataType::ALL
+            .into_iter()
+            .filter(|t| self.accepts_type(*t))
+            .collect()
+    }
+
+    /// Get allowed types and properties as display string
+    pub fn allowed_types_display(&self) -> String {
+        display_list(
+            self.allowed_types
+                .iter()
+                .map(|t| t.display_name())
+                .chain(self.allowed_properties.iter().map(String::as_str)),
+        )
+    }
+
+    /// Check the type, property and unit of a curve.
+    pub fn check(
+        &self,
+        curve_type: CurveDataType,
+        property_id: Option<&str>,
+        unit: &str,
+    ) -> Result<(), UdfError> {
+        if !self.accepts_curve(curve_type, property_id) {
+            // Untyped curves are better identified by their property
+            let actual = match property_id {
+                Some(id) if curve_type == CurveDataType::Unknown => id,
+                _ => curve_type.display_name(),
+            };
+            return Err(UdfError::CurveTypeMismatch {
+                expected: self.allowed_types_display(),
+                actual: actual.to_string(),
+            });
+        }
+
+        if !self.accepts_unit(unit) {
+            return Err(UdfError::UnitMismatch {
+                expected: display_list(
+                    self.allowed_unit_families()
+                        .iter()
+                        .map(|f| f.display_name()),
+                ),
+                actual: unit.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Constraint fields of the parameter JSON for the frontend.
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "allowed_types": self.allowed_types.iter().map(|t| t.display_name()).collect::<Vec<_>>(),
+            "accepted_types": self.accepted_types().iter().map(|t| t.display_name()).collect::<Vec<_>>(),
+            "allowed_properties": self.allowed_properties,
+            "unit_families": self.allowed_unit_families().iter().map(|f| f.display_name()).collect::<Vec<_>>(),
+            "unit": self.unit,
+            "min_length": self.min_length,
+            "allow_nulls": self.allow_nulls,
+        })
+    }
+}
+
+/// Join display names, or "Any" for no restriction.
+fn display_list<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let names: Vec<&str> = names.collect();
+    if names.is_empty() {
+        "Any".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Merge constraint fields into a parameter's JSON object.
+fn with_constraints_json(
+    mut json: serde_json::Value,
+    constraints: &CurveConstraints,
+) -> serde_json::Value {
+    if let (Some(object), serde_json::Value::Object(fields)) =
+        (json.as_object_mut(), constraints.to_json())
+    {
+        object.extend(fields);
+    }
+    json
 }
 
 /// Curve input parameter with type constraints.
@@ -121,6 +350,12 @@ pub struct CurveParameter {
     pub required: bool,
     /// Allowed curve types (empty = any type allowed)
     pub allowed_types: Vec<CurveDataType>,
+    /// Allowed `curve_properties` IDs, in addition to the allowed types
+    pub allowed_properties: Vec<String>,
+    /// Allowed unit families (empty = any unit allowed)
+    pub unit_families: Vec<UnitFamily>,
+    /// Unit the curve is converted to before execution
+    pub unit: Option<String>,
     /// Minimum number of data points required
     pub min_length: Option<usize>,
     /// Whether null values are allowed in the curve
@@ -136,6 +371,9 @@ impl CurveParameter {
             description: String::new(),
             required: true,
             allowed_types: Vec::new(),
+            allowed_properties: Vec::new(),
+            unit_families: Vec::new(),
+            unit: None,
             min_length: None,
             allow_nulls: true,
         }
@@ -149,6 +387,9 @@ impl CurveParameter {
             description: String::new(),
             required: false,
             allowed_types: Vec::new(),
+            allowed_properties: Vec::new(),
+            unit_families: Vec::new(),
+            unit: None,
             min_length: None,
             allow_nulls: true,
         }
@@ -166,6 +407,31 @@ impl CurveParameter {
         self
     }
 
+    /// Also allow curves of specific `curve_properties` IDs (e.g., "deep_resistivity")
+    ///
+    /// Without allowed types, only curves of these properties are allowed.
+    pub fn with_allowed_properties<S: Into<String>>(
+        mut self,
+        properties: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_properties = properties.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Restrict to units of specific families
+    pub fn with_unit_families(mut self, families: Vec<UnitFamily>) -> Self {
+        self.unit_families = families;
+        self
+    }
+
+    /// Convert the curve to a unit before execution (e.g., "g/cm³")
+    ///
+    /// Curves in units of another family are rejected.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
     /// Set minimum data length requirement
     pub fn with_min_length(mut self, min: usize) -> Self {
         self.min_length = Some(min);
@@ -180,23 +446,22 @@ impl CurveParameter {
 
     /// Check if a curve type is allowed
     pub fn is_type_allowed(&self, curve_type: CurveDataType) -> bool {
-        if self.allowed_types.is_empty() {
-            true // No restrictions
-        } else {
-            self.allowed_types.contains(&curve_type)
-        }
+        self.constraints().accepts_type(curve_type)
     }
 
     /// Get allowed types as display string
     pub fn allowed_types_display(&self) -> String {
-        if self.allowed_types.is_empty() {
-            "Any".to_string()
-        } else {
-            self.allowed_types
-                .iter()
-                .map(|t| t.display_name())
-                .collect::<Vec<_>>()
-                .join(", ")
+        self.constraints().allowed_types_display()
+    }
+
+    fn constraints(&self) -> CurveConstraints {
+        CurveConstraints {
+            allowed_types: self.allowed_types.clone(),
+            allowed_properties: self.allowed_properties.clone(),
+            unit_families: self.unit_families.clone(),
+            unit: self.unit.clone(),
+            min_length: self.min_length,
+            allow_nulls: self.allow_nulls,
         }
     }
 }
@@ -245,66 +510,86 @@ impl ParameterDefinition for CurveParameter {
     }
 
     fn to_json(&self) -> serde_json::Value {
-        serde_json::json!({
+        let json = serde_json::json!({
             "name": self.name,
             "label": self.label,
             "description": self.description,
             "type": "curve",
             "required": self.required,
-            "allowed_types": self.allowed_types.iter().map(|t| t.display_name()).collect::<Vec<_>>(),
-            "min_length": self.min_length,
-            "allow_nulls": self.allow_nulls,
-        })
+        });
+        with_constraints_json(json, &self.constraints())
+    }
+
+    fn curve_constraints(&self) -> Option<CurveConstraints> {
+        Some(self.constraints())
     }
 }
 
-/// Numeric parameter with optional range constraints.
+/// Curve list parameter binding a variable number of curves.
+///
+/// Type, length and null constraints apply to every curve in the list.
 #[derive(Debug, Clone)]
-pub struct NumericParameter {
+pub struct CurveListParameter {
     /// Parameter name
     pub name: String,
     /// Display label
     pub label: String,
     /// Description
     pub description: String,
-    /// Whether this parameter is required
+    /// Whether at least one curve is required
     pub required: bool,
-    /// Default value
-    pub default: Option<f64>,
-    /// Minimum value (inclusive)
-    pub min: Option<f64>,
-    /// Maximum value (inclusive)
-    pub max: Option<f64>,
-    /// Unit of measurement (for display)
+    /// Allowed curve types (empty = any type allowed)
+    pub allowed_types: Vec<CurveDataType>,
+    /// Allowed `curve_properties` IDs, in addition to the allowed types
+    pub allowed_properties: Vec<String>,
+    /// Allowed unit families (empty = any unit allowed)
+    pub unit_families: Vec<UnitFamily>,
+    /// Unit every curve is converted to before execution
     pub unit: Option<String>,
+    /// Minimum number of curves
+    pub min_count: usize,
+    /// Maximum number of curves
+    pub max_count: Option<usize>,
+    /// Minimum number of data points required per curve
+    pub min_length: Option<usize>,
+    /// Whether null values are allowed in the curves
+    pub allow_nulls: bool,
 }
 
-impl NumericParameter {
-    /// Create a new required numeric parameter
+impl CurveListParameter {
+    /// Create a new required curve list parameter (at least one curve)
     pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             label: label.into(),
             description: String::new(),
             required: true,
-            default: None,
-            min: None,
-            max: None,
+            allowed_types: Vec::new(),
+            allowed_properties: Vec::new(),
+            unit_families: Vec::new(),
             unit: None,
+            min_count: 1,
+            max_count: None,
+            min_length: None,
+            allow_nulls: true,
         }
     }
 
-    /// Create a new optional numeric parameter with default
-    pub fn optional(name: impl Into<String>, label: impl Into<String>, default: f64) -> Self {
+    /// Create a new optional curve list parameter
+    pub fn optional(name: impl Into<String>, label: impl Into<String>) -> Self {
         Self {
             name: name.into(),
             label: label.into(),
             description: String::new(),
             required: false,
-            default: Some(default),
-            min: None,
-            max: None,
+            allowed_types: Vec::new(),
+            allowed_properties: Vec::new(),
+            unit_families: Vec::new(),
             unit: None,
+            min_count: 0,
+            max_count: None,
+            min_length: None,
+            allow_nulls: true,
         }
     }
 
@@ -314,33 +599,86 @@ impl NumericParameter {
         self
     }
 
-    /// Set range constraints
-    pub fn with_range(mut self, min: f64, max: f64) -> Self {
-        self.min = Some(min);
-        self.max = Some(max);
+    /// Restrict every curve to specific curve types
+    pub fn with_allowed_types(mut self, types: Vec<CurveDataType>) -> Self {
+        self.allowed_types = types;
         self
     }
 
-    /// Set minimum value
-    pub fn with_min(mut self, min: f64) -> Self {
-        self.min = Some(min);
+    /// Also allow curves of specific `curve_properties` IDs (e.g., "deep_resistivity")
+    ///
+    /// Without allowed types, only curves of these properties are allowed.
+    pub fn with_allowed_properties<S: Into<String>>(
+        mut self,
+        properties: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_properties = properties.into_iter().map(Into::into).collect();
         self
     }
 
-    /// Set maximum value
-    pub fn with_max(mut self, max: f64) -> Self {
-        self.max = Some(max);
+    /// Restrict every curve to units of specific families
+    pub fn with_unit_families(mut self, families: Vec<UnitFamily>) -> Self {
+        self.unit_families = families;
         self
     }
 
-    /// Set unit for display
+    /// Convert every curve to a unit before execution
+    ///
+    /// Curves in units of another family are rejected.
     pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
         self.unit = Some(unit.into());
         self
     }
+
+    /// Set the allowed number of curves
+    pub fn with_count(mut self, min: usize, max: usize) -> Self {
+        self.min_count = min;
+        self.max_count = Some(max);
+        self
+    }
+
+    /// Set the minimum number of curves
+    pub fn with_min_count(mut self, min: usize) -> Self {
+        self.min_count = min;
+        self
+    }
+
+    /// Set the maximum number of curves
+    pub fn with_max_count(mut self, max: usize) -> Self {
+        self.max_count = Some(max);
+        self
+    }
+
+    /// Set minimum data length requirement per curve
+    pub fn with_min_length(mut self, min: usize) -> Self {
+        self.min_length = Some(min);
+        self
+    }
+
+    /// Disallow null values in the curves
+    pub fn require_no_nulls(mut self) -> Self {
+        self.allow_nulls = false;
+        self
+    }
+
+    /// Check if a curve type is allowed
+    pub fn is_type_allowed(&self, curve_type: CurveDataType) -> bool {
+        self.constraints().accepts_type(curve_type)
+    }
+
+    fn constraints(&self) -> CurveConstraints {
+        CurveConstraints {
+            allowed_types: self.allowed_types.clone(),
+            allowed_properties: self.allowed_properties.clone(),
+            unit_families: self.unit_families.clone(),
+            unit: self.unit.clone(),
+            min_length: self.min_length,
+            allow_nulls: self.allow_nulls,
+        }
+    }
 }
 
-impl ParameterDefinition for NumericParameter {
+impl ParameterDefinition for CurveListParameter {
     fn name(&self) -> &str {
         &self.name
     }
@@ -358,38 +696,51 @@ impl ParameterDefinition for NumericParameter {
     }
 
     fn default_value(&self) -> Option<ParameterValue> {
-        self.default.map(ParameterValue::Number)
+        None // Curves don't have defaults
     }
 
     fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
         if value.is_null() {
-            if self.required && self.default.is_none() {
-                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            if self.required {
+                return Err(ValidationError::new(&self.name, "Required curves not provided"));
             }
             return Ok(());
         }
 
-        let num = value.as_f64().ok_or_else(|| {
-            ValidationError::new(&self.name, "Value must be a number")
+        let ids = value.as_curve_list().ok_or_else(|| {
+            ValidationError::new(&self.name, "Value must be a list of curve UUIDs")
         })?;
 
-        if let Some(min) = self.min {
-            if num < min {
+        // An empty optional list is the same as no list
+        if ids.is_empty() && !self.required {
+            return Ok(());
+        }
+
+        let min = self.min_count.max(1);
+        if ids.len() < min {
+            return Err(ValidationError::new(
+                &self.name,
+                format!("At least {} curves required, got {}", min, ids.len()),
+            )
+            .with_suggestion("Add more curves to the list"));
+        }
+
+        if let Some(max) = self.max_count {
+            if ids.len() > max {
                 return Err(ValidationError::new(
                     &self.name,
-                    format!("Value must be >= {}", min),
+                    format!("At most {} curves allowed, got {}", max, ids.len()),
                 )
-                .with_suggestion(format!("Enter a value of {} or greater", min)));
+                .with_suggestion("Remove curves from the list"));
             }
         }
 
-        if let Some(max) = self.max {
-            if num > max {
+        for (i, id) in ids.iter().enumerate() {
+            if ids[..i].contains(id) {
                 return Err(ValidationError::new(
                     &self.name,
-                    format!("Value must be <= {}", max),
-                )
-                .with_suggestion(format!("Enter a value of {} or less", max)));
+                    format!("Curve {} is listed more than once", id),
+                ));
             }
         }
 
@@ -397,46 +748,1050 @@ impl ParameterDefinition for NumericParameter {
     }
 
     fn param_type(&self) -> &str {
-        "number"
+        "curve_list"
     }
 
     fn to_json(&self) -> serde_json::Value {
-        serde_json::json!({
+        let json = serde_json::json!({
             "name": self.name,
             "label": self.label,
             "description": self.description,
-            "type": "number",
+            "type": "curve_list",
             "required": self.required,
-            "default": self.default,
-            "min": self.min,
-            "max": self.max,
-            "unit": self.unit,
-        })
+            "min_count": self.min_count,
+            "max_count": self.max_count,
+        });
+        with_constraints_json(json, &self.constraints())
     }
-}
 
-/// Parameter collection for easy access by name.
-#[derive(Debug, Clone, Default)]
-pub struct ParameterValues {
-    values: HashMap<String, ParameterValue>,
+    fn curve_constraints(&self) -> Option<CurveConstraints> {
+        Some(self.constraints())
+    }
 }
 
-impl ParameterValues {
-    /// Create from a HashMap
-    pub fn from_map(values: HashMap<String, ParameterValue>) -> Self {
-        Self { values }
-    }
+/// Numeric parameter with optional range constraints.
+#[derive(Debug, Clone)]
+pub struct NumericParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+    /// Default value
+    pub default: Option<f64>,
+    /// Minimum value (inclusive)
+    pub min: Option<f64>,
+    /// Maximum value (inclusive)
+    pub max: Option<f64>,
+    /// Unit of plain number values, and of the range
+    pub unit: Option<String>,
+    /// Family of the units values may be entered in
+    pub unit_family: Option<UnitFamily>,
+    /// Unit the value is converted to before execution
+    pub unit_target: Option<UnitTarget>,
+}
 
-    /// Get a parameter value by name
-    pub fn get(&self, name: &str) -> Option<&ParameterValue> {
-        self.values.get(name)
+impl NumericParameter {
+    /// Create a new required numeric parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+            default: None,
+            min: None,
+            max: None,
+            unit: None,
+            unit_family: None,
+            unit_target: None,
+        }
     }
 
-    /// Get as curve UUID
-    pub fn get_curve(&self, name: &str) -> Option<uuid::Uuid> {
+    /// Create a new optional numeric parameter with default
+    pub fn optional(name: impl Into<String>, label: impl Into<String>, default: f64) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+            default: Some(default),
+            min: None,
+            max: None,
+            unit: None,
+            unit_family: None,
+            unit_target: None,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+
+    /// Set range constraints
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    /// Set minimum value
+    pub fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Set maximum value
+    pub fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Set the unit of plain number values
+    ///
+    /// Values may then also be entered in other units of its family.
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Accept values entered in any unit of a family
+    pub fn with_unit_family(mut self, family: UnitFamily) -> Self {
+        self.unit_family = Some(family);
+        self
+    }
+
+    /// Convert the value to the unit of the curve bound to a curve parameter
+    /// before execution
+    pub fn in_unit_of_curve(mut self, curve_parameter: impl Into<String>) -> Self {
+        self.unit_target = Some(UnitTarget::Curve(curve_parameter.into()));
+        self
+    }
+
+    /// Convert the value to the depth unit of the well before execution
+    ///
+    /// Plain numbers without a unit are taken to be in the depth unit.
+    pub fn in_depth_unit(mut self) -> Self {
+        self.unit_family = Some(UnitFamily::Length);
+        self.unit_target = Some(UnitTarget::Depth);
+        self
+    }
+
+    /// Family of the units values may be entered in, if known
+    pub fn family(&self) -> Option<UnitFamily> {
+        self.unit_family
+            .or_else(|| self.unit.as_deref().and_then(units::find).map(|u| u.family))
+    }
+
+    /// Value of a quantity in the parameter's unit, for the range check.
+    ///
+    /// Quantities of parameters without a unit are checked as entered.
+    fn quantity_value(&self, value: f64, unit: &str) -> Result<f64, ValidationError> {
+        let entered = units::find(unit)
+            .ok_or_else(|| ValidationError::new(&self.name, format!("Unknown unit '{}'", unit)))?;
+
+        if let Some(family) = self.family() {
+            if entered.family != family {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!(
+                        "Value must be in a {} unit",
+                        family.display_name().to_lowercase()
+                    ),
+                )
+                .with_suggestion(format!(
+                    "Use one of: {}",
+                    units::units_of(family).join(", ")
+                )));
+            }
+        }
+
+        let conversion = match &self.unit {
+            Some(own) => units::conversion(unit, own)
+                .map_err(|e| ValidationError::new(&self.name, e.to_string()))?,
+            None => None,
+        };
+        Ok(conversion.map_or(value, |c| c.apply(value)))
+    }
+}
+
+impl ParameterDefinition for NumericParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        self.default.map(ParameterValue::Number)
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required && self.default.is_none() {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        let num = match value.as_quantity() {
+            Some((num, unit)) => self.quantity_value(num, unit)?,
+            None => value
+                .as_f64()
+                .ok_or_else(|| ValidationError::new(&self.name, "Value must be a number"))?,
+        };
+
+        if let Some(min) = self.min {
+            if num < min {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!("Value must be >= {}", min),
+                )
+                .with_suggestion(format!("Enter a value of {} or greater", min)));
+            }
+        }
+
+        if let Some(max) = self.max {
+            if num > max {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!("Value must be <= {}", max),
+                )
+                .with_suggestion(format!("Enter a value of {} or less", max)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "number"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "number",
+            "required": self.required,
+            "default": self.default,
+            "min": self.min,
+            "max": self.max,
+            "unit": self.unit,
+            "unit_family": self.family().map(|f| f.display_name()),
+            "units": self.family().map(units::units_of).unwrap_or_default(),
+            "unit_target": self.unit_target,
+        })
+    }
+
+    fn value_unit(&self) -> Option<ValueUnit> {
+        let value_unit = ValueUnit {
+            unit: self.unit.clone(),
+            family: self.family(),
+            target: self.unit_target.clone(),
+        };
+        (value_unit != ValueUnit::default()).then_some(value_unit)
+    }
+}
+
+/// Integer parameter with optional range constraints.
+#[derive(Debug, Clone)]
+pub struct IntegerParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+    /// Default value
+    pub default: Option<i64>,
+    /// Minimum value (inclusive)
+    pub min: Option<i64>,
+    /// Maximum value (inclusive)
+    pub max: Option<i64>,
+    /// Unit of measurement (for display)
+    pub unit: Option<String>,
+}
+
+impl IntegerParameter {
+    /// Create a new required integer parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+            default: None,
+            min: None,
+            max: None,
+            unit: None,
+        }
+    }
+
+    /// Create a new optional integer parameter with default
+    pub fn optional(name: impl Into<String>, label: impl Into<String>, default: i64) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+            default: Some(default),
+            min: None,
+            max: None,
+            unit: None,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+
+    /// Set range constraints
+    pub fn with_range(mut self, min: i64, max: i64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    /// Set minimum value
+    pub fn with_min(mut self, min: i64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Set maximum value
+    pub fn with_max(mut self, max: i64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Set unit for display
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+}
+
+impl ParameterDefinition for IntegerParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        self.default.map(ParameterValue::Integer)
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required && self.default.is_none() {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        // Whole-valued floats are accepted since JSON does not distinguish 5 from 5.0
+        let num = match value {
+            ParameterValue::Integer(i) => *i,
+            ParameterValue::Number(n) if n.fract() == 0.0 && n.is_finite() => *n as i64,
+            ParameterValue::Number(_) => {
+                return Err(ValidationError::new(&self.name, "Value must be a whole number")
+                    .with_suggestion("Remove the fractional part"));
+            }
+            _ => return Err(ValidationError::new(&self.name, "Value must be an integer")),
+        };
+
+        if let Some(min) = self.min {
+            if num < min {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!("Value must be >= {}", min),
+                )
+                .with_suggestion(format!("Enter a value of {} or greater", min)));
+            }
+        }
+
+        if let Some(max) = self.max {
+            if num > max {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!("Value must be <= {}", max),
+                )
+                .with_suggestion(format!("Enter a value of {} or less", max)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "integer"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "integer",
+            "required": self.required,
+            "default": self.default,
+            "min": self.min,
+            "max": self.max,
+            "unit": self.unit,
+        })
+    }
+}
+
+/// Boolean (on/off) parameter.
+///
+/// Booleans always have a default, so they are never reported as missing.
+#[derive(Debug, Clone)]
+pub struct BooleanParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Default value
+    pub default: bool,
+}
+
+impl BooleanParameter {
+    /// Create a new boolean parameter with default
+    pub fn new(name: impl Into<String>, label: impl Into<String>, default: bool) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            default,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+}
+
+impl ParameterDefinition for BooleanParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        Some(ParameterValue::Boolean(self.default))
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() || value.as_bool().is_some() {
+            return Ok(());
+        }
+
+        Err(ValidationError::new(&self.name, "Value must be true or false"))
+    }
+
+    fn param_type(&self) -> &str {
+        "boolean"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "boolean",
+            "required": false,
+            "default": self.default,
+        })
+    }
+}
+
+/// Free-text parameter with optional length limit.
+#[derive(Debug, Clone)]
+pub struct StringParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+    /// Default value
+    pub default: Option<String>,
+    /// Maximum length in characters
+    pub max_length: Option<usize>,
+}
+
+impl StringParameter {
+    /// Create a new required string parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+            default: None,
+            max_length: None,
+        }
+    }
+
+    /// Create a new optional string parameter with default
+    pub fn optional(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        default: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+            default: Some(default.into()),
+            max_length: None,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+
+    /// Set maximum length
+    pub fn with_max_length(mut self, max: usize) -> Self {
+        self.max_length = Some(max);
+        self
+    }
+}
+
+impl ParameterDefinition for StringParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        self.default.clone().map(ParameterValue::String)
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required && self.default.is_none() {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        let text = value
+            .as_str()
+            .ok_or_else(|| ValidationError::new(&self.name, "Value must be text"))?;
+
+        if self.required && text.trim().is_empty() {
+            return Err(ValidationError::new(&self.name, "Value must not be empty"));
+        }
+
+        if let Some(max) = self.max_length {
+            if text.chars().count() > max {
+                return Err(ValidationError::new(
+                    &self.name,
+                    format!("Value must be at most {} characters", max),
+                )
+                .with_suggestion("Shorten the value"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "string"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "string",
+            "required": self.required,
+            "default": self.default,
+            "max_length": self.max_length,
+        })
+    }
+}
+
+/// A selectable option of a [`ChoiceParameter`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChoiceOption {
+    /// Value passed to the UDF
+    pub value: String,
+    /// Display label
+    pub label: String,
+}
+
+/// Parameter restricted to a fixed set of string values (an enum).
+#[derive(Debug, Clone)]
+pub struct ChoiceParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+    /// Available options, in display order
+    pub options: Vec<ChoiceOption>,
+    /// Default option value
+    pub default: Option<String>,
+}
+
+impl ChoiceParameter {
+    /// Create a new required choice parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+            options: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Create a new optional choice parameter (see `with_default`)
+    pub fn optional(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+            options: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+
+    /// Add a selectable option
+    pub fn with_option(mut self, value: impl Into<String>, label: impl Into<String>) -> Self {
+        self.options.push(ChoiceOption {
+            value: value.into(),
+            label: label.into(),
+        });
+        self
+    }
+
+    /// Set the default option, once the options are added
+    ///
+    /// # Panics
+    ///
+    /// If the default is not one of the options, as `validate` would then
+    /// reject it.
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        let default = default.into();
+        assert!(
+            self.is_option(&default),
+            "Default '{}' of '{}' is not one of its options ({})",
+            default,
+            self.name,
+            self.options_display()
+        );
+        self.default = Some(default);
+        self
+    }
+
+    /// Check if a value is one of the options
+    pub fn is_option(&self, value: &str) -> bool {
+        self.options.iter().any(|o| o.value == value)
+    }
+
+    /// Get option values as display string
+    pub fn options_display(&self) -> String {
+        self.options
+            .iter()
+            .map(|o| o.value.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl ParameterDefinition for ChoiceParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        self.default.clone().map(ParameterValue::String)
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required && self.default.is_none() {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        let choice = value
+            .as_str()
+            .ok_or_else(|| ValidationError::new(&self.name, "Value must be a string"))?;
+
+        if !self.is_option(choice) {
+            return Err(ValidationError::new(
+                &self.name,
+                format!("'{}' is not a valid option", choice),
+            )
+            .with_suggestion(format!("Choose one of: {}", self.options_display())));
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "choice"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "choice",
+            "required": self.required,
+            "default": self.default,
+            "options": self.options,
+        })
+    }
+}
+
+/// Depth interval parameter restricting a UDF to a depth window.
+///
+/// The engine masks input samples outside the interval before execution
+/// and output samples outside it afterwards, so UDFs need no code of
+/// their own to honour it.
+#[derive(Debug, Clone)]
+pub struct DepthRangeParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+    /// Depth unit (for display)
+    pub unit: Option<String>,
+}
+
+impl DepthRangeParameter {
+    /// Create a new required depth range parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+            unit: None,
+        }
+    }
+
+    /// Create a new optional depth range parameter (no restriction if unset)
+    pub fn optional(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+            unit: None,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+
+    /// Set unit for display
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+}
+
+impl ParameterDefinition for DepthRangeParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        None
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        let (top, base) = value.as_depth_range().ok_or_else(|| {
+            ValidationError::new(&self.name, "Value must be a depth range with top and base")
+        })?;
+
+        if !top.is_finite() || !base.is_finite() {
+            return Err(ValidationError::new(&self.name, "Top and base must be numbers"));
+        }
+
+        if top >= base {
+            return Err(ValidationError::new(
+                &self.name,
+                format!("Top ({}) must be above base ({})", top, base),
+            )
+            .with_suggestion("Enter a top depth smaller than the base depth"));
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "depth_range"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "depth_range",
+            "required": self.required,
+            "unit": self.unit,
+        })
+    }
+}
+
+/// Zone parameter: the interval between two well tops of the executed well.
+///
+/// Resolved to a depth interval by the engine from the well's markers and
+/// applied like a [`DepthRangeParameter`].
+#[derive(Debug, Clone)]
+pub struct ZoneParameter {
+    /// Parameter name
+    pub name: String,
+    /// Display label
+    pub label: String,
+    /// Description
+    pub description: String,
+    /// Whether this parameter is required
+    pub required: bool,
+}
+
+impl ZoneParameter {
+    /// Create a new required zone parameter
+    pub fn required(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: true,
+        }
+    }
+
+    /// Create a new optional zone parameter (no restriction if unset)
+    pub fn optional(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            required: false,
+        }
+    }
+
+    /// Set description
+    pub fn with_description(mut self, desc: impl Into<String>) -> Self {
+        self.description = desc.into();
+        self
+    }
+}
+
+impl ParameterDefinition for ZoneParameter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn default_value(&self) -> Option<ParameterValue> {
+        None
+    }
+
+    fn validate(&self, value: &ParameterValue) -> Result<(), ValidationError> {
+        if value.is_null() {
+            if self.required {
+                return Err(ValidationError::new(&self.name, "Required parameter not provided"));
+            }
+            return Ok(());
+        }
+
+        let (top, base) = value.as_zone().ok_or_else(|| {
+            ValidationError::new(&self.name, "Value must be a zone with top and base markers")
+        })?;
+
+        if top.trim().is_empty() || base.trim().is_empty() {
+            return Err(ValidationError::new(&self.name, "Both well tops must be selected"));
+        }
+
+        if top == base {
+            return Err(ValidationError::new(
+                &self.name,
+                "Top and base markers must be different",
+            )
+            .with_suggestion("Select the well top that closes the zone as base"));
+        }
+
+        Ok(())
+    }
+
+    fn param_type(&self) -> &str {
+        "zone"
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "label": self.label,
+            "description": self.description,
+            "type": "zone",
+            "required": self.required,
+        })
+    }
+}
+
+/// Parameter collection for easy access by name.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterValues {
+    values: HashMap<String, ParameterValue>,
+}
+
+impl ParameterValues {
+    /// Create from a HashMap
+    pub fn from_map(values: HashMap<String, ParameterValue>) -> Self {
+        Self { values }
+    }
+
+    /// Get a parameter value by name
+    pub fn get(&self, name: &str) -> Option<&ParameterValue> {
+        self.values.get(name)
+    }
+
+    /// Get as curve UUID
+    pub fn get_curve(&self, name: &str) -> Option<uuid::Uuid> {
         self.values.get(name).and_then(|v| v.as_curve())
     }
 
+    /// Get as list of curve UUIDs
+    pub fn get_curve_list(&self, name: &str) -> Option<&[uuid::Uuid]> {
+        self.values.get(name).and_then(|v| v.as_curve_list())
+    }
+
     /// Get as f64
     pub fn get_f64(&self, name: &str) -> Option<f64> {
         self.values.get(name).and_then(|v| v.as_f64())
@@ -467,6 +1822,16 @@ impl ParameterValues {
         self.get_bool(name).unwrap_or(default)
     }
 
+    /// Get as (top, base) depth interval
+    pub fn get_depth_range(&self, name: &str) -> Option<(f64, f64)> {
+        self.values.get(name).and_then(|v| v.as_depth_range())
+    }
+
+    /// Get as (top marker, base marker) zone
+    pub fn get_zone(&self, name: &str) -> Option<(&str, &str)> {
+        self.values.get(name).and_then(|v| v.as_zone())
+    }
+
     /// Check if parameter exists and is not null
     pub fn has(&self, name: &str) -> bool {
         self.values.get(name).map(|v| !v.is_null()).unwrap_or(false)
@@ -482,3 +1847,224 @@ impl ParameterValues {
         serde_json::to_value(&self.values).unwrap_or(serde_json::Value::Null)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choice_parameter_validation() {
+        let param = ChoiceParameter::optional("method", "Method")
+            .with_option("linear", "Linear")
+            .with_option("clavier", "Clavier")
+            .with_default("linear");
+
+        assert!(param
+            .validate(&ParameterValue::String("clavier".into()))
+            .is_ok());
+        assert!(param.validate(&ParameterValue::Null).is_ok());
+
+        let err = param
+            .validate(&ParameterValue::String("steiber".into()))
+            .unwrap_err();
+        assert_eq!(
+            err.suggestion.as_deref(),
+            Some("Choose one of: linear, clavier")
+        );
+
+        let json = param.to_json();
+        assert_eq!(json["type"], "choice");
+        assert_eq!(json["default"], "linear");
+        assert_eq!(json["options"][1]["value"], "clavier");
+    }
+
+    #[test]
+    #[should_panic(expected = "is not one of its options")]
+    fn test_choice_default_must_be_an_option() {
+        ChoiceParameter::optional("method", "Method")
+            .with_option("linear", "Linear")
+            .with_default("steiber");
+    }
+
+    #[test]
+    fn test_integer_parameter_validation() {
+        let param = IntegerParameter::required("window", "Window").with_range(1, 10);
+
+        assert!(param.validate(&ParameterValue::Integer(5)).is_ok());
+        assert!(param.validate(&ParameterValue::Number(5.0)).is_ok());
+        assert!(param.validate(&ParameterValue::Number(5.5)).is_err());
+        assert!(param.validate(&ParameterValue::Integer(11)).is_err());
+        assert!(param.validate(&ParameterValue::Null).is_err());
+    }
+
+    #[test]
+    fn test_curve_list_parameter_validation() {
+        let param = CurveListParameter::required("passes", "Resistivity Passes").with_count(2, 3);
+        let ids: Vec<uuid::Uuid> = (0..4).map(|_| uuid::Uuid::new_v4()).collect();
+
+        assert!(param
+            .validate(&ParameterValue::CurveList(ids[..2].to_vec()))
+            .is_ok());
+        assert!(param
+            .validate(&ParameterValue::CurveList(ids[..1].to_vec()))
+            .is_err());
+        assert!(param
+            .validate(&ParameterValue::CurveList(ids.clone()))
+            .is_err());
+        assert!(param
+            .validate(&ParameterValue::CurveList(vec![ids[0], ids[0]]))
+            .is_err());
+
+        // Lists arrive from the frontend as JSON arrays of UUID strings
+        let value: ParameterValue = serde_json::from_value(serde_json::json!(ids[..2])).unwrap();
+        assert_eq!(value.as_curve_list(), Some(&ids[..2]));
+    }
+
+    #[test]
+    fn test_boolean_and_string_parameters() {
+        let flag = BooleanParameter::new("ignore_nulls", "Ignore Nulls", true);
+        assert!(!flag.is_required());
+        assert!(matches!(
+            flag.default_value(),
+            Some(ParameterValue::Boolean(true))
+        ));
+        assert!(flag.validate(&ParameterValue::Integer(1)).is_err());
+
+        let name = StringParameter::required("suffix", "Suffix").with_max_length(4);
+        assert!(name
+            .validate(&ParameterValue::String("_NRM".into()))
+            .is_ok());
+        assert!(name.validate(&ParameterValue::String("  ".into())).is_err());
+        assert!(name
+            .validate(&ParameterValue::String("_NORM".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_curve_constraints() {
+        let param = CurveParameter::required("rt_curve", "Rt")
+            .with_allowed_types(vec![CurveDataType::Resistivity])
+            .with_unit_families(vec![UnitFamily::Resistivity])
+            .require_no_nulls();
+        let constraints = param.curve_constraints().unwrap();
+        assert!(!constraints.allow_nulls);
+
+        // Any resistivity variety is compatible with a resistivity input
+        assert!(constraints
+            .check(CurveDataType::DeepResistivity, None, "ohm.m")
+            .is_ok());
+        assert!(matches!(
+            constraints.check(CurveDataType::Sonic, None, "us/ft"),
+            Err(UdfError::CurveTypeMismatch { .. })
+        ));
+        // Only units recognized as another family are rejected
+        assert!(matches!(
+            constraints.check(CurveDataType::Resistivity, None, "g/cc"),
+            Err(UdfError::UnitMismatch { .. })
+        ));
+        assert!(constraints
+            .check(CurveDataType::Resistivity, None, "")
+            .is_ok());
+
+        let json = param.to_json();
+        assert_eq!(json["allowed_types"], serde_json::json!(["Resistivity"]));
+        assert_eq!(json["accepted_types"][1], "Deep Resistivity");
+        assert_eq!(json["unit_families"][0], "Resistivity");
+
+        // Numeric parameters have no curve constraints
+        assert!(NumericParameter::required("a", "A")
+            .curve_constraints()
+            .is_none());
+    }
+
+    #[test]
+    fn test_curve_property_constraints() {
+        // A specific property, whatever type it maps to
+        let param =
+            CurveParameter::required("t2", "T2 Distribution").with_allowed_properties(["nmr_t2"]);
+        let constraints = param.curve_constraints().unwrap();
+        assert!(constraints
+            .check(CurveDataType::Unknown, Some("nmr_t2"), "")
+            .is_ok());
+        match constraints.check(CurveDataType::Unknown, Some("nmr_t1"), "") {
+            Err(UdfError::CurveTypeMismatch { expected, actual }) => {
+                assert_eq!(expected, "nmr_t2");
+                assert_eq!(actual, "nmr_t1");
+            }
+            other => panic!("expected a type mismatch, got {:?}", other),
+        }
+        assert!(!param.is_type_allowed(CurveDataType::GammaRay));
+
+        // A family plus a property outside it
+        let param = CurveListParameter::required("passes", "Passes")
+            .with_allowed_types(vec![CurveDataType::Resistivity])
+            .with_allowed_properties(["microresistivity"]);
+        let constraints = param.curve_constraints().unwrap();
+        assert!(constraints.accepts_curve(CurveDataType::ShallowResistivity, None));
+        assert!(constraints.accepts_curve(CurveDataType::Unknown, Some("microresistivity")));
+        assert!(!constraints.accepts_curve(CurveDataType::Unknown, Some("nmr_t2")));
+        assert_eq!(param.to_json()["allowed_properties"][0], "microresistivity");
+    }
+
+    #[test]
+    fn test_curve_expected_unit() {
+        let param = CurveParameter::required("rhob", "Bulk Density")
+            .with_allowed_types(vec![CurveDataType::Density])
+            .with_unit("g/cm³");
+        let constraints = param.curve_constraints().unwrap();
+
+        // The expected unit restricts curves to its family
+        assert_eq!(
+            constraints.allowed_unit_families(),
+            vec![UnitFamily::Density]
+        );
+        assert!(constraints
+            .check(CurveDataType::Density, None, "kg/m3")
+            .is_ok());
+        assert!(matches!(
+            constraints.check(CurveDataType::Density, None, "us/ft"),
+            Err(UdfError::UnitMismatch { .. })
+        ));
+
+        let json = param.to_json();
+        assert_eq!(json["unit"], "g/cm³");
+        assert_eq!(json["unit_families"][0], "Density");
+    }
+
+    #[test]
+    fn test_numeric_quantities() {
+        let step = NumericParameter::required("step", "Step")
+            .with_min(0.1)
+            .in_depth_unit();
+        let quantity = |value: f64, unit: &str| ParameterValue::Quantity {
+            value,
+            unit: unit.to_string(),
+        };
+
+        assert!(step.validate(&quantity(0.5, "ft")).is_ok());
+        assert!(step.validate(&ParameterValue::Number(0.5)).is_ok());
+        assert!(step.validate(&quantity(0.5, "g/cc")).is_err());
+        assert!(step.validate(&quantity(0.5, "furlong")).is_err());
+
+        let value_unit = step.value_unit().unwrap();
+        assert_eq!(value_unit.family, Some(UnitFamily::Length));
+        assert_eq!(value_unit.target, Some(UnitTarget::Depth));
+
+        // The range applies in the parameter's unit
+        let cutoff = NumericParameter::required("cutoff", "Cutoff")
+            .with_range(0.0, 0.5)
+            .with_unit("v/v");
+        assert!(cutoff.validate(&quantity(30.0, "%")).is_ok());
+        assert!(cutoff.validate(&quantity(60.0, "%")).is_err());
+
+        let json = cutoff.to_json();
+        assert_eq!(json["unit_family"], "Fraction");
+        assert_eq!(json["units"][1], "%");
+
+        // Quantities deserialize from their JSON form
+        let value: ParameterValue =
+            serde_json::from_value(serde_json::json!({"value": 0.5, "unit": "ft"})).unwrap();
+        assert_eq!(value.as_quantity(), Some((0.5, "ft")));
+        assert!(NumericParameter::required("n", "N").value_unit().is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Curve-aware preflight validation.
+//!
+//! Checks the curves bound to a UDF's curve parameters before execution,
+//! using only their metadata: curve type and unit, sample count, null
+//! samples and an estimate of depth compatibility. The value arrays are not
+//! loaded, so the UI can run it on every parameter change.
+
+use crate::compute::alignment::{AlignmentStrategy, DEPTH_TOLERANCE};
+use crate::compute::engine::{check_curve, CurveLoader, CurveMetadataInfo};
+use crate::compute::error::{UdfError, ValidationError};
+use crate::compute::nulls::{self, NullPolicy};
+use crate::compute::parameters::{ParameterDefinition, ParameterValue};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Check the curves bound to curve and curve list parameters.
+///
+/// Reports at most one error per curve, on the parameter it is bound to.
+pub fn check_curves(
+    definitions: &[Box<dyn ParameterDefinition>],
+    parameters: &HashMap<String, ParameterValue>,
+    curve_loader: &dyn CurveLoader,
+) -> Vec<ValidationError> {
+    let null_policy = NullPolicy::from_parameters(parameters);
+    let mut errors = Vec::new();
+    // Curves that passed their own checks, for the depth estimate
+    let mut checked: Vec<(&str, CurveMetadataInfo)> = Vec::new();
+
+    for def in definitions {
+        let curve_ids: Vec<Uuid> = match (def.param_type(), parameters.get(def.name())) {
+            ("curve", Some(value)) => value.as_curve().into_iter().collect(),
+            ("curve_list", Some(value)) => value.as_curve_list().unwrap_or_default().to_vec(),
+            _ => continue,
+        };
+
+        for curve_id in curve_ids {
+            let metadata = match curve_loader.load_curve_metadata(curve_id) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    errors.push(
+                        ValidationError::new(def.name(), e.to_string())
+                            .with_suggestion("Select another curve"),
+                    );
+                    continue;
+                }
+            };
+
+            match check_metadata(def.as_ref(), &metadata, null_policy) {
+                Ok(()) => checked.push((def.name(), metadata)),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    // Curves on other depth grids are resampled if an alignment is picked
+    if AlignmentStrategy::from_parameters(parameters) == AlignmentStrategy::None {
+        errors.extend(check_depth_compatibility(&checked));
+    }
+    errors
+}
+
+/// Check a curve's type, unit, sample count and nulls against its parameter.
+fn check_metadata(
+    def: &dyn ParameterDefinition,
+    metadata: &CurveMetadataInfo,
+    null_policy: NullPolicy,
+) -> Result<(), ValidationError> {
+    match check_curve(
+        def,
+        metadata.curve_type,
+        metadata.property_id.as_deref(),
+        &metadata.unit,
+    ) {
+        Err(UdfError::CurveTypeMismatch { expected, actual }) => {
+            return Err(ValidationError::new(
+                def.name(),
+                format!("{} is a {} curve", metadata.mnemonic, actual),
+            )
+            .with_suggestion(format!("Select a curve of type: {}", expected)));
+        }
+        Err(UdfError::UnitMismatch { expected, actual }) => {
+            return Err(ValidationError::new(
+                def.name(),
+                format!("{} is in {}", metadata.mnemonic, actual),
+            )
+            .with_suggestion(format!("Select a curve with a unit of: {}", expected)));
+        }
+        _ => {}
+    }
+
+    // Like the engine, count valid samples, with gaps filled if the null
+    // policy interpolates them (a depth range may leave fewer at execution)
+    if let Some(min_length) = nulls::min_length(def) {
+        let interpolated = !nulls::allows_nulls(def) && null_policy == NullPolicy::Interpolate;
+        let null_count = if interpolated {
+            0
+        } else {
+            metadata.null_count.unwrap_or(0)
+        };
+        let valid = metadata.row_count - null_count;
+        if valid < min_length as i64 {
+            return Err(ValidationError::new(
+                def.name(),
+                format!(
+                    "{} has {} valid samples, at least {} are required",
+                    metadata.mnemonic, valid, min_length
+                ),
+            )
+            .with_suggestion("Select a longer curve"));
+        }
+    }
+
+    // Other null policies deal with the nulls at execution
+    let allow_nulls = nulls::allows_nulls(def) || null_policy != NullPolicy::Fail;
+    if let Some(null_count) = metadata.null_count.filter(|n| *n > 0 && !allow_nulls) {
+        return Err(ValidationError::new(
+            def.name(),
+            format!(
+                "{} has {} null samples, but '{}' does not allow nulls",
+                metadata.mnemonic,
+                null_count,
+                def.label()
+            ),
+        )
+        .with_suggestion("Fill the gaps in the curve or select another one"));
+    }
+
+    Ok(())
+}
+
+/// Estimate whether curves share a depth grid from their sample counts and
+/// depth extents.
+///
+/// Curves with an unknown extent are skipped; the engine checks the actual
+/// depths once the curves are loaded.
+fn check_depth_compatibility(curves: &[(&str, CurveMetadataInfo)]) -> Vec<ValidationError> {
+    let mut with_extent = curves
+        .iter()
+        .filter_map(|(name, m)| m.depth_range.map(|range| (*name, m, range)));
+    let Some((_, reference, (ref_top, ref_base))) = with_extent.next() else {
+        return Vec::new();
+    };
+
+    with_extent
+        .filter(|(_, m, (top, base))| {
+            m.row_count != reference.row_count
+                || (top - ref_top).abs() > DEPTH_TOLERANCE
+                || (base - ref_base).abs() > DEPTH_TOLERANCE
+        })
+        .map(|(name, m, (top, base))| {
+            ValidationError::new(
+                name,
+                format!(
+                    "{} ({} samples, {}-{}) is not on the depth grid of {} ({} samples, {}-{})",
+                    m.mnemonic,
+                    m.row_count,
+                    top,
+                    base,
+                    reference.mnemonic,
+                    reference.row_count,
+                    ref_top,
+                    ref_base
+                ),
+            )
+            .with_suggestion("Pick a depth alignment to resample the curves to common depths")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::parameters::CurveParameter;
+    use crate::compute::types::CurveDataType;
+
+    fn metadata(mnemonic: &str, curve_type: CurveDataType, row_count: i64) -> CurveMetadataInfo {
+        CurveMetadataInfo {
+            curve_id: Uuid::new_v4(),
+            mnemonic: mnemonic.to_string(),
+            curve_type,
+            unit: String::new(),
+            row_count,
+            property_id: None,
+            depth_range: Some((1000.0, 1000.0 + (row_count - 1) as f64 * 0.5)),
+            null_count: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_check_metadata() {
+        let def = CurveParameter::required("gr_curve", "Gamma Ray")
+            .with_allowed_types(vec![CurveDataType::GammaRay])
+            .with_min_length(10)
+            .require_no_nulls();
+
+        assert!(check_metadata(
+            &def,
+            &metadata("GR", CurveDataType::GammaRay, 100),
+            NullPolicy::Fail
+        )
+        .is_ok());
+
+        let err = check_metadata(
+            &def,
+            &metadata("RT", CurveDataType::Resistivity, 100),
+            NullPolicy::Fail,
+        )
+        .unwrap_err();
+        assert_eq!(err.message, "RT is a Resistivity curve");
+
+        let short = metadata("GR", CurveDataType::GammaRay, 5);
+        assert!(check_metadata(&def, &short, NullPolicy::Fail)
+            .unwrap_err()
+            .message
+            .contains("5 valid samples"));
+
+        // Nulls do not count towards the minimum length
+        let lenient = CurveParameter::required("gr_curve", "Gamma Ray").with_min_length(10);
+        let gappy = CurveMetadataInfo {
+            null_count: Some(3),
+            ..metadata("GR", CurveDataType::GammaRay, 12)
+        };
+        let err = check_metadata(&lenient, &gappy, NullPolicy::Fail).unwrap_err();
+        assert!(err.message.contains("9 valid samples"), "{}", err.message);
+        // unless the gaps are interpolated
+        assert!(check_metadata(&def, &gappy, NullPolicy::Interpolate).is_ok());
+
+        let gappy = CurveMetadataInfo {
+            null_count: Some(3),
+            ..metadata("GR", CurveDataType::GammaRay, 100)
+        };
+        assert!(check_metadata(&def, &gappy, NullPolicy::Fail).is_err());
+        assert!(check_metadata(&def, &gappy, NullPolicy::Interpolate).is_ok());
+    }
+
+    #[test]
+    fn test_depth_compatibility_estimate() {
+        let gr = metadata("GR", CurveDataType::GammaRay, 100);
+        let rhob = metadata("RHOB", CurveDataType::Density, 100);
+        let dt = metadata("DT", CurveDataType::Sonic, 200);
+        let gridded = CurveMetadataInfo {
+            depth_range: None,
+            ..metadata("NPHI", CurveDataType::NeutronPorosity, 50)
+        };
+
+        let curves = vec![("a", gr), ("b", rhob), ("c", gridded)];
+        assert!(check_depth_compatibility(&curves).is_empty());
+
+        let curves = vec![("a", curves[0].1.clone()), ("d", dt)];
+        let errors = check_depth_compatibility(&curves);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "d");
+    }
+}
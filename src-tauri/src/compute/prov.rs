@@ -0,0 +1,368 @@
+//! Export of provenance as W3C PROV-JSON.
+//!
+//! Lineage graphs map onto the PROV data model as follows:
+//!
+//! - curves are Entities, and each parquet blob an execution read or
+//!   produced is an Entity that is a specialization of its curve
+//! - executions are Activities that used input blobs and generated output
+//!   blobs, with each output derived from every input
+//! - the UDF and the Compute app version are SoftwareAgents associated
+//!   with each execution
+//!
+//! See <https://www.w3.org/submissions/prov-json/>.
+
+use crate::compute::error::UdfError;
+use crate::compute::lineage::{
+    lineage_graph, CurveNode, ExecutionNode, LineageDirection, LineageEdge, LineageGraph,
+    LineageRelation,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+
+/// Namespace of the identifiers and attributes used in exported documents.
+pub const PROV_NAMESPACE: &str = "urn:dataforge:";
+
+/// What to export provenance for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProvExportScope {
+    /// A curve and the execution that produced it
+    Curve { curve_id: Uuid },
+    /// The full upstream or downstream lineage of a curve
+    Lineage {
+        curve_id: Uuid,
+        direction: LineageDirection,
+    },
+    /// Every curve in a workspace and the executions that produced them
+    Workspace { workspace_id: Uuid },
+}
+
+/// Export the provenance of a scope as a PROV-JSON document.
+pub fn export_prov(db: &Connection, scope: &ProvExportScope) -> Result<Value, UdfError> {
+    let mut document = ProvDocument::new();
+    match scope {
+        ProvExportScope::Curve { curve_id } => {
+            document.add_graph(&lineage_graph(
+                db,
+                *curve_id,
+                LineageDirection::Upstream,
+                Some(1),
+            )?);
+        }
+        ProvExportScope::Lineage {
+            curve_id,
+            direction,
+        } => {
+            document.add_graph(&lineage_graph(db, *curve_id, *direction, None)?);
+        }
+        ProvExportScope::Workspace { workspace_id } => {
+            let mut stmt = db.prepare(
+                r#"SELECT c.id FROM curves c
+                   JOIN wells w ON c.well_id = w.id
+                   WHERE w.workspace_id = ?1 AND c.deleted_at IS NULL
+                   ORDER BY w.name, c.mnemonic"#,
+            )?;
+            let curve_ids = stmt
+                .query_map([workspace_id.to_string()], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for curve_id in curve_ids {
+                let curve_id = Uuid::parse_str(&curve_id).map_err(|e| {
+                    UdfError::DatabaseError(format!("Invalid curve UUID '{}': {}", curve_id, e))
+                })?;
+                document.add_graph(&lineage_graph(
+                    db,
+                    curve_id,
+                    LineageDirection::Upstream,
+                    Some(1),
+                )?);
+            }
+        }
+    }
+    Ok(document.to_json())
+}
+
+/// A PROV-JSON document under construction.
+///
+/// Records and relations added more than once are kept once, so graphs
+/// that overlap can be merged into one document.
+#[derive(Debug, Default)]
+pub struct ProvDocument {
+    /// Records and relations by PROV-JSON section, then identifier
+    sections: BTreeMap<&'static str, Map<String, Value>>,
+    relations: HashSet<(&'static str, String)>,
+}
+
+impl ProvDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the curves, executions and edges of a lineage graph.
+    pub fn add_graph(&mut self, graph: &LineageGraph) {
+        for curve in &graph.curves {
+            self.add_curve(curve);
+        }
+        for execution in &graph.executions {
+            self.add_execution(execution);
+        }
+        for edge in &graph.edges {
+            self.add_edge(edge);
+        }
+
+        // Every output of an execution was derived from each of its inputs
+        for generated in graph
+            .edges
+            .iter()
+            .filter(|e| e.relation == LineageRelation::Generated)
+        {
+            for used in graph
+                .edges
+                .iter()
+                .filter(|e| e.relation == LineageRelation::Used && e.to == generated.from)
+            {
+                self.add_relation(
+                    "wasDerivedFrom",
+                    json!({
+                        "prov:generatedEntity": data_ref(generated.to, &generated.parquet_hash),
+                        "prov:usedEntity": data_ref(used.from, &used.parquet_hash),
+                        "prov:activity": execution_ref(generated.from),
+                    }),
+                );
+            }
+        }
+    }
+
+    fn add_curve(&mut self, curve: &CurveNode) {
+        let mut attributes = Map::new();
+        attributes.insert("prov:type".to_string(), json!("dataforge:Curve"));
+        if let Some(mnemonic) = &curve.mnemonic {
+            attributes.insert("prov:label".to_string(), json!(mnemonic));
+        }
+        if let Some(well_id) = curve.well_id {
+            attributes.insert("dataforge:well_id".to_string(), json!(well_id));
+        }
+        if let Some(version) = curve.version {
+            attributes.insert("dataforge:version".to_string(), json!(version));
+        }
+        attributes.insert("dataforge:is_derived".to_string(), json!(curve.is_derived));
+        attributes.insert("dataforge:deleted".to_string(), json!(curve.deleted));
+        self.add_record(
+            "entity",
+            curve_ref(curve.curve_id),
+            Value::Object(attributes),
+        );
+    }
+
+    fn add_execution(&mut self, execution: &ExecutionNode) {
+        let activity = execution_ref(execution.execution_id);
+        let mut attributes = Map::new();
+        attributes.insert("prov:type".to_string(), json!("dataforge:Execution"));
+        attributes.insert(
+            "prov:startTime".to_string(),
+            json!(execution.started_at.to_rfc3339()),
+        );
+        if let Some(completed_at) = execution.completed_at {
+            attributes.insert("prov:endTime".to_string(), json!(completed_at.to_rfc3339()));
+        }
+        attributes.insert(
+            "dataforge:parameters".to_string(),
+            json!(execution.parameters.to_string()),
+        );
+        self.add_record("activity", activity.clone(), Value::Object(attributes));
+
+        let udf = format!("udf:{}_v{}", execution.udf_id, execution.udf_version);
+        self.add_record(
+            "agent",
+            udf.clone(),
+            json!({
+                "prov:type": "prov:SoftwareAgent",
+                "prov:label": execution.udf_id,
+                "dataforge:udf_version": execution.udf_version,
+            }),
+        );
+        let compute = format!("compute:v{}", execution.compute_app_version);
+        self.add_record(
+            "agent",
+            compute.clone(),
+            json!({
+                "prov:type": "prov:SoftwareAgent",
+                "prov:label": "DataForge Compute",
+                "dataforge:version": execution.compute_app_version,
+            }),
+        );
+
+        for (agent, role) in [(udf, "dataforge:udf"), (compute, "dataforge:runtime")] {
+            self.add_relation(
+                "wasAssociatedWith",
+                json!({ "prov:activity": activity, "prov:agent": agent, "prov:role": role }),
+            );
+        }
+    }
+
+    fn add_edge(&mut self, edge: &LineageEdge) {
+        let (curve, activity) = match edge.relation {
+            LineageRelation::Used => (edge.from, edge.to),
+            LineageRelation::Generated => (edge.to, edge.from),
+        };
+
+        // The data read or produced, as a specialization of the curve
+        let entity = data_ref(curve, &edge.parquet_hash);
+        if let Some(hash) = &edge.parquet_hash {
+            let mut attributes = Map::new();
+            attributes.insert("prov:type".to_string(), json!("dataforge:ParquetBlob"));
+            attributes.insert("dataforge:sha256".to_string(), json!(hash));
+            if let Some(version) = edge.version {
+                attributes.insert("dataforge:curve_version".to_string(), json!(version));
+            }
+            self.add_record("entity", entity.clone(), Value::Object(attributes));
+            self.add_relation(
+                "specializationOf",
+                json!({ "prov:specificEntity": entity, "prov:generalEntity": curve_ref(curve) }),
+            );
+        }
+
+        let mut relation = Map::new();
+        relation.insert("prov:activity".to_string(), json!(execution_ref(activity)));
+        relation.insert("prov:entity".to_string(), json!(entity));
+        if let Some(name) = &edge.name {
+            relation.insert("prov:role".to_string(), json!(name));
+        }
+        let section = match edge.relation {
+            LineageRelation::Used => "used",
+            LineageRelation::Generated => "wasGeneratedBy",
+        };
+        self.add_relation(section, Value::Object(relation));
+    }
+
+    fn add_record(&mut self, section: &'static str, id: String, attributes: Value) {
+        self.sections
+            .entry(section)
+            .or_default()
+            .insert(id, attributes);
+    }
+
+    /// Add a relation under a blank node identifier, unless already present.
+    fn add_relation(&mut self, section: &'static str, relation: Value) {
+        if !self.relations.insert((section, relation.to_string())) {
+            return;
+        }
+        let records = self.sections.entry(section).or_default();
+        let id = format!("_:{}{}", section, records.len() + 1);
+        records.insert(id, relation);
+    }
+
+    /// The document as PROV-JSON.
+    pub fn to_json(&self) -> Value {
+        let mut document = Map::new();
+        document.insert(
+            "prefix".to_string(),
+            json!({
+                "dataforge": PROV_NAMESPACE,
+                "curve": format!("{}curve:", PROV_NAMESPACE),
+                "blob": format!("{}blob:", PROV_NAMESPACE),
+                "execution": format!("{}execution:", PROV_NAMESPACE),
+                "udf": format!("{}udf:", PROV_NAMESPACE),
+                "compute": format!("{}compute:", PROV_NAMESPACE),
+            }),
+        );
+        for (section, records) in &self.sections {
+            document.insert(section.to_string(), Value::Object(records.clone()));
+        }
+        Value::Object(document)
+    }
+}
+
+fn curve_ref(curve_id: Uuid) -> String {
+    format!("curve:{}", curve_id)
+}
+
+fn execution_ref(execution_id: Uuid) -> String {
+    format!("execution:{}", execution_id)
+}
+
+/// The blob entity of curve data, or the curve itself if its hash is unknown.
+fn data_ref(curve: Uuid, parquet_hash: &Option<String>) -> String {
+    match parquet_hash {
+        Some(hash) => format!("blob:{}", hash),
+        None => curve_ref(curve),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_graph_maps_to_prov_records() {
+        let gr = Uuid::new_v4();
+        let vsh = Uuid::new_v4();
+        let execution = Uuid::new_v4();
+        let curve = |curve_id, mnemonic: &str, is_derived| CurveNode {
+            curve_id,
+            well_id: Some(Uuid::nil()),
+            mnemonic: Some(mnemonic.to_string()),
+            version: Some(1),
+            is_derived,
+            deleted: false,
+        };
+        let graph = LineageGraph {
+            root: vsh,
+            direction: LineageDirection::Upstream,
+            curves: vec![curve(vsh, "VSH", true), curve(gr, "GR", false)],
+            executions: vec![ExecutionNode {
+                execution_id: execution,
+                udf_id: "petro:vshale_linear".to_string(),
+                udf_version: "1.0.0".to_string(),
+                parameters: json!({ "gr_curve": gr, "gr_min": 30.0 }),
+                started_at: Utc::now(),
+                completed_at: Some(Utc::now()),
+                compute_app_version: "0.1.0".to_string(),
+            }],
+            edges: vec![
+                LineageEdge {
+                    from: execution,
+                    to: vsh,
+                    relation: LineageRelation::Generated,
+                    name: Some("vshale".to_string()),
+                    parquet_hash: Some("vsh_hash".to_string()),
+                    version: None,
+                },
+                LineageEdge {
+                    from: gr,
+                    to: execution,
+                    relation: LineageRelation::Used,
+                    name: Some("gr_curve".to_string()),
+                    parquet_hash: Some("gr_hash".to_string()),
+                    version: Some(1),
+                },
+            ],
+        };
+
+        let mut document = ProvDocument::new();
+        document.add_graph(&graph);
+        // Merging the same graph again adds nothing
+        document.add_graph(&graph);
+        let prov = document.to_json();
+
+        assert_eq!(prov["entity"].as_object().unwrap().len(), 4);
+        assert_eq!(prov["entity"]["blob:gr_hash"]["dataforge:curve_version"], 1);
+        assert_eq!(prov["agent"].as_object().unwrap().len(), 2);
+        assert!(prov["activity"][format!("execution:{}", execution)].is_object());
+
+        let used = prov["used"].as_object().unwrap();
+        assert_eq!(used.len(), 1);
+        assert_eq!(used["_:used1"]["prov:entity"], "blob:gr_hash");
+        assert_eq!(used["_:used1"]["prov:role"], "gr_curve");
+
+        let derived = &prov["wasDerivedFrom"]["_:wasDerivedFrom1"];
+        assert_eq!(derived["prov:generatedEntity"], "blob:vsh_hash");
+        assert_eq!(derived["prov:usedEntity"], "blob:gr_hash");
+        assert_eq!(prov["specializationOf"].as_object().unwrap().len(), 2);
+        assert_eq!(prov["wasAssociatedWith"].as_object().unwrap().len(), 2);
+    }
+}
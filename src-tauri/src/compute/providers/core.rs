@@ -3,10 +3,16 @@
 //! This provider contains basic, verifiable computations that are
 //! commonly used in well log data processing.
 
+use crate::compute::alignment::interpolate_at_depth;
+use crate::compute::constraints::ParameterConstraint;
 use crate::compute::context::ExecutionContext;
 use crate::compute::error::UdfError;
-use crate::compute::parameters::{CurveParameter, NumericParameter, ParameterDefinition};
-use crate::compute::types::{CurveDataType, OutputCurveData, UdfMetadata, UdfOutput};
+use crate::compute::parameters::{
+    BooleanParameter, CurveListParameter, CurveParameter, NumericParameter, ParameterDefinition,
+};
+use crate::compute::types::{
+    CurveDataType, OutputCurveData, OutputDeclaration, UdfMetadata, UdfOutput,
+};
 use crate::compute::{Udf, UdfProvider};
 use std::sync::Arc;
 
@@ -51,6 +57,7 @@ impl UdfProvider for CoreProvider {
             Arc::new(MovingAverageUdf::new()),
             Arc::new(LinearScaleUdf::new()),
             Arc::new(DepthResampleUdf::new()),
+            Arc::new(CurveAverageUdf::new()),
         ]
     }
 }
@@ -120,6 +127,8 @@ Null values are excluded from the average calculation.
                 "average".to_string(),
                 "noise".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("smoothed", "Smoothed Curve")
+                .with_description("Moving average of the input curve")],
         }
     }
 
@@ -169,6 +178,8 @@ Null values are excluded from the average calculation.
         let mut smoothed_values: Vec<Option<f64>> = Vec::with_capacity(input_curve.len());
 
         for i in 0..input_curve.len() {
+            context.checkpoint(i, input_curve.len())?;
+
             let start = i.saturating_sub(half_window);
             let end = (i + half_window + 1).min(input_curve.len());
 
@@ -198,7 +209,7 @@ Null values are excluded from the average calculation.
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("smoothed", output_curve);
         output.add_metadata("window_size", serde_json::json!(window_size));
         output.add_metadata("input_curve", serde_json::json!(input_curve.mnemonic));
 
@@ -268,6 +279,9 @@ output = (input - in_min) / (in_max - in_min) * (out_max - out_min) + out_min
                 "transform".to_string(),
                 "linear".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("scaled", "Scaled Curve")
+                .with_description("Linearly rescaled input curve")
+                .with_curve_type(CurveDataType::Computed)],
         }
     }
 
@@ -279,11 +293,13 @@ output = (input - in_min) / (in_max - in_min) * (out_max - out_min) + out_min
             ),
             Box::new(
                 NumericParameter::required("in_min", "Input Min")
-                    .with_description("Minimum value of input range"),
+                    .with_description("Minimum value of input range")
+                    .in_unit_of_curve("input_curve"),
             ),
             Box::new(
                 NumericParameter::required("in_max", "Input Max")
-                    .with_description("Maximum value of input range"),
+                    .with_description("Maximum value of input range")
+                    .in_unit_of_curve("input_curve"),
             ),
             Box::new(
                 NumericParameter::optional("out_min", "Output Min", 0.0)
@@ -296,25 +312,8 @@ output = (input - in_min) / (in_max - in_min) * (out_max - out_min) + out_min
         ]
     }
 
-    fn check_parameters(&self, context: &ExecutionContext) -> Result<(), Vec<crate::compute::ValidationError>> {
-        let params = context.parameters();
-        let mut errors = Vec::new();
-
-        let in_min = params.get_f64("in_min").unwrap_or(0.0);
-        let in_max = params.get_f64("in_max").unwrap_or(1.0);
-
-        if (in_max - in_min).abs() < 1e-10 {
-            errors.push(crate::compute::ValidationError::new(
-                "in_max",
-                "Input range cannot be zero (in_min == in_max)",
-            ));
-        }
-
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+    fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+        vec![ParameterConstraint::not_equal("in_max", "in_min")]
     }
 
     fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
@@ -333,16 +332,16 @@ output = (input - in_min) / (in_max - in_min) * (out_max - out_min) + out_min
         let in_range = in_max - in_min;
         let out_range = out_max - out_min;
 
-        let scaled_values: Vec<Option<f64>> = input_curve
-            .values
-            .iter()
-            .map(|v| {
-                v.map(|val| {
-                    let normalized = (val - in_min) / in_range;
-                    normalized * out_range + out_min
-                })
-            })
-            .collect();
+        let mut scaled_values: Vec<Option<f64>> = Vec::with_capacity(input_curve.len());
+
+        for (i, value) in input_curve.values.iter().enumerate() {
+            context.checkpoint(i, input_curve.len())?;
+
+            scaled_values.push(value.map(|val| {
+                let normalized = (val - in_min) / in_range;
+                normalized * out_range + out_min
+            }));
+        }
 
         let output_curve = OutputCurveData {
             mnemonic: format!("{}_SCALED", input_curve.mnemonic),
@@ -356,7 +355,7 @@ output = (input - in_min) / (in_max - in_min) * (out_max - out_min) + out_min
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("scaled", output_curve);
         output.add_metadata("in_min", serde_json::json!(in_min));
         output.add_metadata("in_max", serde_json::json!(in_max));
         output.add_metadata("out_min", serde_json::json!(out_min));
@@ -406,8 +405,13 @@ Resamples curve data to a new regular depth spacing using linear interpolation.
 
 - **Input Curve**: Curve to resample
 - **New Step**: Desired depth interval (e.g., 0.5 for half-foot sampling)
-- **Start Depth**: Optional start depth (defaults to first sample)
-- **End Depth**: Optional end depth (defaults to last sample)
+
+The step is in the well's depth unit, unless entered in another length unit,
+in which case it is converted to it (e.g., a 0.5 ft step on a metre-indexed
+well becomes 0.1524 m).
+
+The new depths span the input curve. With a depth range or zone, they span
+the part of the curve within it, starting at the top of the interval.
 
 ## Algorithm
 
@@ -428,6 +432,8 @@ Extrapolation beyond the original depth range is not performed (returns null).
                 "interpolate".to_string(),
                 "spacing".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("resampled", "Resampled Curve")
+                .with_description("Input curve interpolated onto the new depth grid")],
         }
     }
 
@@ -435,20 +441,14 @@ Extrapolation beyond the original depth range is not performed (returns null).
         vec![
             Box::new(
                 CurveParameter::required("input_curve", "Input Curve")
-                    .with_description("Curve to resample"),
+                    .with_description("Curve to resample")
+                    .with_min_length(2),
             ),
             Box::new(
                 NumericParameter::required("new_step", "New Step")
-                    .with_description("New depth interval (same units as input)")
-                    .with_min(0.001),
-            ),
-            Box::new(
-                NumericParameter::optional("start_depth", "Start Depth", f64::NAN)
-                    .with_description("Start depth (leave empty to use first sample)"),
-            ),
-            Box::new(
-                NumericParameter::optional("end_depth", "End Depth", f64::NAN)
-                    .with_description("End depth (leave empty to use last sample)"),
+                    .with_description("New depth interval (in the well's depth unit)")
+                    .with_min(0.001)
+                    .in_depth_unit(),
             ),
         ]
     }
@@ -489,12 +489,15 @@ Extrapolation beyond the original depth range is not performed (returns null).
         let orig_start = original_depths.first().copied().unwrap_or(0.0);
         let orig_end = original_depths.last().copied().unwrap_or(0.0);
 
-        let start_depth = params.get_f64("start_depth").filter(|v| !v.is_nan()).unwrap_or(orig_start);
-        let end_depth = params.get_f64("end_depth").filter(|v| !v.is_nan()).unwrap_or(orig_end);
+        // Only resample within the execution's depth interval
+        let (start_depth, end_depth) = match context.depth_interval() {
+            Some(interval) => (interval.top.max(orig_start), interval.base.min(orig_end)),
+            None => (orig_start, orig_end),
+        };
 
         if end_depth <= start_depth {
-            return Err(UdfError::ParameterValidation(
-                "End depth must be greater than start depth".to_string(),
+            return Err(UdfError::ExecutionFailed(
+                "Depth interval does not overlap the input curve".to_string(),
             ));
         }
 
@@ -509,7 +512,9 @@ Extrapolation beyond the original depth range is not performed (returns null).
         // Interpolate values
         let mut new_values: Vec<Option<f64>> = Vec::with_capacity(new_depths.len());
 
-        for &target_depth in &new_depths {
+        for (i, &target_depth) in new_depths.iter().enumerate() {
+            context.checkpoint(i, new_depths.len())?;
+
             // Find bracketing samples
             let value = interpolate_at_depth(
                 target_depth,
@@ -531,7 +536,7 @@ Extrapolation beyond the original depth range is not performed (returns null).
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("resampled", output_curve);
         output.add_metadata("new_step", serde_json::json!(new_step));
         output.add_metadata("start_depth", serde_json::json!(start_depth));
         output.add_metadata("end_depth", serde_json::json!(end_depth));
@@ -541,79 +546,177 @@ Extrapolation beyond the original depth range is not performed (returns null).
     }
 }
 
-/// Linear interpolation at a target depth.
-fn interpolate_at_depth(
-    target: f64,
-    depths: &[f64],
-    values: &[Option<f64>],
-) -> Option<f64> {
-    if depths.is_empty() {
-        return None;
-    }
+// =============================================================================
+// Curve Average UDF
+// =============================================================================
+
+/// Sample-wise average of several curves.
+///
+/// Used to combine repeat passes or runs of the same measurement.
+pub struct CurveAverageUdf;
 
-    // Check if outside range
-    if target < depths[0] || target > depths[depths.len() - 1] {
-        return None;
+impl CurveAverageUdf {
+    pub fn new() -> Self {
+        Self
     }
+}
 
-    // Find bracketing indices using binary search
-    let idx = depths.partition_point(|&d| d < target);
+impl Default for CurveAverageUdf {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    if idx == 0 {
-        // Exactly at or before first point
-        return values[0];
+impl Udf for CurveAverageUdf {
+    fn id(&self) -> &str {
+        "curve_average"
     }
 
-    if idx >= depths.len() {
-        // At or after last point
-        return values[depths.len() - 1];
+    fn metadata(&self) -> UdfMetadata {
+        UdfMetadata {
+            name: "Curve Average".to_string(),
+            category: "Transform".to_string(),
+            description: "Average several curves sample by sample".to_string(),
+            documentation: Some(
+                r#"# Curve Average
+
+Averages several curves of the same well sample by sample, e.g. repeat
+passes of a resistivity tool.
+
+## Parameters
+
+- **Input Curves**: Two or more curves on the same depth grid and in the same unit
+- **Require All Curves**: Only average samples where every curve has a value
+
+## Algorithm
+
+For each sample, the mean of the non-null values of all input curves.
+With **Require All Curves**, a null in any input gives a null output.
+
+## Output
+
+- Average curve with the type and unit of the first input curve
+"#
+                .to_string(),
+            ),
+            version: "1.0.0".to_string(),
+            tags: vec![
+                "average".to_string(),
+                "mean".to_string(),
+                "combine".to_string(),
+                "passes".to_string(),
+            ],
+            outputs: vec![OutputDeclaration::new("average", "Average Curve")
+                .with_description("Sample-wise mean of the input curves")],
+        }
     }
 
-    // Check for exact match
-    if (depths[idx] - target).abs() < 1e-10 {
-        return values[idx];
+    fn parameter_definitions(&self) -> Vec<Box<dyn ParameterDefinition>> {
+        vec![
+            Box::new(
+                CurveListParameter::required("input_curves", "Input Curves")
+                    .with_description("Curves to average (same depth grid and unit)")
+                    .with_min_count(2),
+            ),
+            Box::new(
+                BooleanParameter::new("require_all", "Require All Curves", false)
+                    .with_description("Output null where any input curve is null"),
+            ),
+        ]
     }
 
-    // Linear interpolation between idx-1 and idx
-    let d0 = depths[idx - 1];
-    let d1 = depths[idx];
-    let v0 = values[idx - 1];
-    let v1 = values[idx];
+    fn check_parameters(&self, context: &ExecutionContext) -> Result<(), Vec<crate::compute::ValidationError>> {
+        let mut errors = Vec::new();
+
+        if let Some(curves) = context.get_curve_list("input_curves") {
+            if let Some(first) = curves.first() {
+                if let Some(other) = curves.iter().find(|c| c.unit != first.unit) {
+                    errors.push(
+                        crate::compute::ValidationError::new(
+                            "input_curves",
+                            format!(
+                                "{} is in {} but {} is in {}",
+                                other.mnemonic, other.unit, first.mnemonic, first.unit
+                            ),
+                        )
+                        .with_suggestion("Average curves measured in the same unit"),
+                    );
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 
-    match (v0, v1) {
-        (Some(val0), Some(val1)) => {
-            let t = (target - d0) / (d1 - d0);
-            Some(val0 + t * (val1 - val0))
+    fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
+        let curves = context.require_curve_list("input_curves")?;
+        let require_all = context.parameters().get_bool_or("require_all", false);
+        let first = curves
+            .first()
+            .ok_or_else(|| UdfError::MissingCurve("input_curves".to_string()))?;
+
+        let mut values: Vec<Option<f64>> = Vec::with_capacity(first.len());
+        for i in 0..first.len() {
+            context.checkpoint(i, first.len())?;
+
+            let present: Vec<f64> = curves.iter().filter_map(|c| c.values[i]).collect();
+            if present.is_empty() || (require_all && present.len() < curves.len()) {
+                values.push(None);
+            } else {
+                values.push(Some(present.iter().sum::<f64>() / present.len() as f64));
+            }
         }
-        (Some(val), None) | (None, Some(val)) => Some(val), // Use available value
-        (None, None) => None,
+
+        let mnemonics: Vec<&str> = curves.iter().map(|c| c.mnemonic.as_str()).collect();
+        let same_type = curves.iter().all(|c| c.curve_type == first.curve_type);
+
+        let output_curve = OutputCurveData {
+            mnemonic: format!("{}_AVG", first.mnemonic),
+            curve_type: if same_type {
+                first.curve_type
+            } else {
+                CurveDataType::Unknown
+            },
+            unit: first.unit.clone(),
+            depths: first.depths.as_ref().clone(),
+            values,
+            description: Some(format!("Average of {}", mnemonics.join(", "))),
+        };
+
+        let mut output = UdfOutput::new("average", output_curve);
+        output.add_metadata("input_curves", serde_json::json!(mnemonics));
+        output.add_metadata("require_all", serde_json::json!(require_all));
+
+        Ok(output)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::compute::interval::DepthInterval;
     use crate::compute::parameters::ParameterValues;
+    use crate::compute::test_support::curve;
     use crate::compute::types::CurveData;
     use std::collections::HashMap;
 
     fn create_test_curve() -> Arc<CurveData> {
-        Arc::new(CurveData {
-            curve_id: uuid::Uuid::new_v4(),
-            mnemonic: "TEST".to_string(),
-            curve_type: CurveDataType::Unknown,
-            unit: "units".to_string(),
-            depths: Arc::new(vec![100.0, 100.5, 101.0, 101.5, 102.0]),
-            values: vec![
+        Arc::new(curve(
+            "TEST",
+            "units",
+            vec![100.0, 100.5, 101.0, 101.5, 102.0],
+            vec![
                 Some(10.0),
                 Some(20.0),
                 Some(30.0),
                 Some(40.0),
                 Some(50.0),
             ],
-            parquet_hash: "test_hash".to_string(),
-            version: 1,
-        })
+        ))
     }
 
     #[test]
@@ -635,10 +738,10 @@ mod tests {
         context.add_curve("input_curve".to_string(), curve);
 
         let result = udf.execute(&context).unwrap();
-        assert_eq!(result.curve_data.values.len(), 5);
+        assert_eq!(result.output("smoothed").unwrap().values.len(), 5);
 
         // Middle value should be average of 20, 30, 40 = 30
-        assert!((result.curve_data.values[2].unwrap() - 30.0).abs() < 0.01);
+        assert!((result.output("smoothed").unwrap().values[2].unwrap() - 30.0).abs() < 0.01);
     }
 
     #[test]
@@ -662,8 +765,38 @@ mod tests {
         let result = udf.execute(&context).unwrap();
 
         // 10 -> 0, 50 -> 1
-        assert!((result.curve_data.values[0].unwrap() - 0.0).abs() < 0.01);
-        assert!((result.curve_data.values[4].unwrap() - 1.0).abs() < 0.01);
+        assert!((result.output("scaled").unwrap().values[0].unwrap() - 0.0).abs() < 0.01);
+        assert!((result.output("scaled").unwrap().values[4].unwrap() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_depth_resample_within_interval() {
+        let udf = DepthResampleUdf::new();
+
+        let mut params = HashMap::new();
+        params.insert(
+            "new_step".to_string(),
+            crate::compute::ParameterValue::Number(0.25),
+        );
+
+        let context = crate::compute::context::ExecutionContextBuilder::new(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+        )
+        .with_parameters(ParameterValues::from_map(params))
+        .with_curve("input_curve", create_test_curve())
+        .with_depth_interval(Some(DepthInterval::new(100.75, 150.0)))
+        .build();
+
+        let result = udf.execute(&context).unwrap();
+        let resampled = result.output("resampled").unwrap();
+
+        // From the top of the interval to the last sample of the curve
+        assert_eq!(
+            resampled.depths,
+            vec![100.75, 101.0, 101.25, 101.5, 101.75, 102.0]
+        );
+        assert!((resampled.values[0].unwrap() - 25.0).abs() < 0.01);
     }
 
     #[test]
@@ -671,11 +804,12 @@ mod tests {
         let provider = CoreProvider::new();
         let udfs = provider.load_udfs();
 
-        assert_eq!(udfs.len(), 3);
+        assert_eq!(udfs.len(), 4);
 
         let ids: Vec<_> = udfs.iter().map(|u| u.id()).collect();
         assert!(ids.contains(&"moving_average"));
         assert!(ids.contains(&"linear_scale"));
         assert!(ids.contains(&"depth_resample"));
+        assert!(ids.contains(&"curve_average"));
     }
 }
@@ -3,10 +3,13 @@
 //! This provider contains fundamental petrophysical calculations
 //! commonly used in well log analysis.
 
+use crate::compute::constraints::ParameterConstraint;
 use crate::compute::context::ExecutionContext;
 use crate::compute::error::UdfError;
 use crate::compute::parameters::{CurveParameter, NumericParameter, ParameterDefinition};
-use crate::compute::types::{CurveDataType, OutputCurveData, UdfMetadata, UdfOutput};
+use crate::compute::types::{
+    CurveDataType, OutputCurveData, OutputDeclaration, UdfMetadata, UdfOutput,
+};
 use crate::compute::{Udf, UdfProvider};
 use std::sync::Arc;
 
@@ -130,6 +133,10 @@ Vsh = IGR
                 "vshale".to_string(),
                 "linear".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("vshale", "Shale Volume")
+                .with_description("Linear VShale from gamma ray")
+                .with_curve_type(CurveDataType::Computed)
+                .with_unit("v/v")],
         }
     }
 
@@ -138,42 +145,28 @@ Vsh = IGR
             Box::new(
                 CurveParameter::required("gr_curve", "Gamma Ray Curve")
                     .with_description("Input gamma ray log for VShale calculation")
-                    .with_allowed_types(vec![CurveDataType::GammaRay]),
+                    .with_allowed_types(vec![CurveDataType::GammaRay])
+                    .with_unit("gAPI"),
             ),
             Box::new(
                 NumericParameter::required("gr_min", "GR Clean (Min)")
                     .with_description("Gamma ray reading in clean sand zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
             Box::new(
                 NumericParameter::required("gr_max", "GR Shale (Max)")
                     .with_description("Gamma ray reading in shale zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
         ]
     }
 
-    fn check_parameters(&self, context: &ExecutionContext) -> Result<(), Vec<crate::compute::ValidationError>> {
-        let params = context.parameters();
-        let mut errors = Vec::new();
-
-        let gr_min = params.get_f64("gr_min").unwrap_or(0.0);
-        let gr_max = params.get_f64("gr_max").unwrap_or(0.0);
-
-        if gr_max <= gr_min {
-            errors.push(crate::compute::ValidationError::new(
-                "gr_max",
-                "GR Max must be greater than GR Min",
-            ));
-        }
-
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+    fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+        vec![ParameterConstraint::greater_than("gr_max", "gr_min")]
     }
 
     fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
@@ -195,7 +188,9 @@ Vsh = IGR
         let mut warnings: Vec<String> = Vec::new();
         let mut out_of_bounds_count = 0;
 
-        for value in &gr_curve.values {
+        for (i, value) in gr_curve.values.iter().enumerate() {
+            context.checkpoint(i, gr_curve.len())?;
+
             match value {
                 Some(gr) => {
                     let igr = (gr - gr_min) / gr_range;
@@ -238,7 +233,7 @@ Vsh = IGR
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("vshale", output_curve);
         for warning in warnings {
             output.add_warning(warning);
         }
@@ -314,6 +309,10 @@ method tends to overestimate shale content.
                 "vshale".to_string(),
                 "clavier".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("vshale", "Shale Volume")
+                .with_description("Clavier VShale from gamma ray")
+                .with_curve_type(CurveDataType::Computed)
+                .with_unit("v/v")],
         }
     }
 
@@ -322,42 +321,28 @@ method tends to overestimate shale content.
             Box::new(
                 CurveParameter::required("gr_curve", "Gamma Ray Curve")
                     .with_description("Input gamma ray log for VShale calculation")
-                    .with_allowed_types(vec![CurveDataType::GammaRay]),
+                    .with_allowed_types(vec![CurveDataType::GammaRay])
+                    .with_unit("gAPI"),
             ),
             Box::new(
                 NumericParameter::required("gr_min", "GR Clean (Min)")
                     .with_description("Gamma ray reading in clean sand zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
             Box::new(
                 NumericParameter::required("gr_max", "GR Shale (Max)")
                     .with_description("Gamma ray reading in shale zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
         ]
     }
 
-    fn check_parameters(&self, context: &ExecutionContext) -> Result<(), Vec<crate::compute::ValidationError>> {
-        let params = context.parameters();
-        let mut errors = Vec::new();
-
-        let gr_min = params.get_f64("gr_min").unwrap_or(0.0);
-        let gr_max = params.get_f64("gr_max").unwrap_or(0.0);
-
-        if gr_max <= gr_min {
-            errors.push(crate::compute::ValidationError::new(
-                "gr_max",
-                "GR Max must be greater than GR Min",
-            ));
-        }
-
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+    fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+        vec![ParameterConstraint::greater_than("gr_max", "gr_min")]
     }
 
     fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
@@ -369,7 +354,9 @@ method tends to overestimate shale content.
         let gr_range = gr_max - gr_min;
         let mut vsh_values: Vec<Option<f64>> = Vec::with_capacity(gr_curve.len());
 
-        for value in &gr_curve.values {
+        for (i, value) in gr_curve.values.iter().enumerate() {
+            context.checkpoint(i, gr_curve.len())?;
+
             match value {
                 Some(gr) => {
                     let igr = (gr - gr_min) / gr_range;
@@ -400,7 +387,7 @@ method tends to overestimate shale content.
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("vshale", output_curve);
         output.add_metadata("method", serde_json::json!("clavier"));
         output.add_metadata("gr_min", serde_json::json!(gr_min));
         output.add_metadata("gr_max", serde_json::json!(gr_max));
@@ -468,6 +455,10 @@ lower shale volume estimates than the linear method.
                 "vshale".to_string(),
                 "steiber".to_string(),
             ],
+            outputs: vec![OutputDeclaration::new("vshale", "Shale Volume")
+                .with_description("Steiber VShale from gamma ray")
+                .with_curve_type(CurveDataType::Computed)
+                .with_unit("v/v")],
         }
     }
 
@@ -476,42 +467,28 @@ lower shale volume estimates than the linear method.
             Box::new(
                 CurveParameter::required("gr_curve", "Gamma Ray Curve")
                     .with_description("Input gamma ray log for VShale calculation")
-                    .with_allowed_types(vec![CurveDataType::GammaRay]),
+                    .with_allowed_types(vec![CurveDataType::GammaRay])
+                    .with_unit("gAPI"),
             ),
             Box::new(
                 NumericParameter::required("gr_min", "GR Clean (Min)")
                     .with_description("Gamma ray reading in clean sand zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
             Box::new(
                 NumericParameter::required("gr_max", "GR Shale (Max)")
                     .with_description("Gamma ray reading in shale zone (API units)")
                     .with_min(0.0)
-                    .with_unit("gAPI"),
+                    .with_unit("gAPI")
+                    .in_unit_of_curve("gr_curve"),
             ),
         ]
     }
 
-    fn check_parameters(&self, context: &ExecutionContext) -> Result<(), Vec<crate::compute::ValidationError>> {
-        let params = context.parameters();
-        let mut errors = Vec::new();
-
-        let gr_min = params.get_f64("gr_min").unwrap_or(0.0);
-        let gr_max = params.get_f64("gr_max").unwrap_or(0.0);
-
-        if gr_max <= gr_min {
-            errors.push(crate::compute::ValidationError::new(
-                "gr_max",
-                "GR Max must be greater than GR Min",
-            ));
-        }
-
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
-        }
+    fn parameter_constraints(&self) -> Vec<ParameterConstraint> {
+        vec![ParameterConstraint::greater_than("gr_max", "gr_min")]
     }
 
     fn execute(&self, context: &ExecutionContext) -> Result<UdfOutput, UdfError> {
@@ -523,7 +500,9 @@ lower shale volume estimates than the linear method.
         let gr_range = gr_max - gr_min;
         let mut vsh_values: Vec<Option<f64>> = Vec::with_capacity(gr_curve.len());
 
-        for value in &gr_curve.values {
+        for (i, value) in gr_curve.values.iter().enumerate() {
+            context.checkpoint(i, gr_curve.len())?;
+
             match value {
                 Some(gr) => {
                     let igr = ((gr - gr_min) / gr_range).clamp(0.0, 1.0);
@@ -554,7 +533,7 @@ lower shale volume estimates than the linear method.
             )),
         };
 
-        let mut output = UdfOutput::new(output_curve);
+        let mut output = UdfOutput::new("vshale", output_curve);
         output.add_metadata("method", serde_json::json!("steiber"));
         output.add_metadata("gr_min", serde_json::json!(gr_min));
         output.add_metadata("gr_max", serde_json::json!(gr_max));
@@ -568,25 +547,22 @@ mod tests {
     use super::*;
     use crate::compute::types::CurveData;
     use crate::compute::parameters::ParameterValues;
+    use crate::compute::test_support::curve;
     use std::collections::HashMap;
 
     fn create_test_gr_curve() -> Arc<CurveData> {
-        Arc::new(CurveData {
-            curve_id: uuid::Uuid::new_v4(),
-            mnemonic: "GR".to_string(),
-            curve_type: CurveDataType::GammaRay,
-            unit: "gAPI".to_string(),
-            depths: Arc::new(vec![100.0, 100.5, 101.0, 101.5, 102.0]),
-            values: vec![
+        Arc::new(curve(
+            "GR",
+            "gAPI",
+            vec![100.0, 100.5, 101.0, 101.5, 102.0],
+            vec![
                 Some(30.0),  // Clean
                 Some(50.0),  // Mixed
                 Some(70.0),  // Mixed
                 Some(90.0),  // Near shale
                 Some(100.0), // Shale
             ],
-            parquet_hash: "test_hash".to_string(),
-            version: 1,
-        })
+        ))
     }
 
     #[test]
@@ -608,7 +584,7 @@ mod tests {
         let result = udf.execute(&context).unwrap();
 
         // Check output values
-        let values = &result.curve_data.values;
+        let values = &result.output("vshale").unwrap().values;
         assert_eq!(values.len(), 5);
 
         // GR=30 -> Vsh=0.0
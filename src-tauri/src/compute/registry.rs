@@ -5,6 +5,7 @@
 //! provides lookup functionality for the execution engine.
 
 use crate::compute::error::UdfError;
+use crate::compute::types::OutputDeclaration;
 use crate::compute::{Udf, UdfProvider};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -128,6 +129,7 @@ impl UdfRegistry {
                     description: metadata.description,
                     version: metadata.version,
                     tags: metadata.tags,
+                    outputs: metadata.outputs,
                 }
             })
             .collect()
@@ -201,6 +203,7 @@ pub struct UdfInfo {
     pub description: String,
     pub version: String,
     pub tags: Vec<String>,
+    pub outputs: Vec<OutputDeclaration>,
 }
 
 #[cfg(test)]
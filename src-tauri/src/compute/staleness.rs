@@ -0,0 +1,529 @@
+//! Stale derived curve detection.
+//!
+//! A derived curve points through `source_execution_id` at the execution
+//! that produced it, and that execution records the version and parquet
+//! hash of every input curve. When a source curve is edited or re-gridded
+//! in DataForge its version or hash changes, and the derived curve no
+//! longer reflects its inputs. This module compares the recorded inputs
+//! with the current `curves` rows and loads what is needed to recompute a
+//! stale curve from its recorded execution.
+
+use crate::compute::error::UdfError;
+use crate::compute::interval::DepthInterval;
+use crate::compute::types::{ExecutionRecord, InputReference, OutputReference};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// Which derived curves to check.
+#[derive(Debug, Clone, Copy)]
+pub enum StalenessScope {
+    /// Derived curves of one well
+    Well(Uuid),
+    /// Derived curves of every well in a workspace
+    Workspace(Uuid),
+}
+
+/// Why a recorded input no longer matches the current curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StaleReason {
+    /// The input curve was deleted
+    Deleted,
+    /// The input curve data changed (different parquet hash)
+    DataChanged,
+    /// The input curve was updated without a data change (e.g., its unit)
+    VersionChanged,
+}
+
+/// A recorded input that no longer matches the current curve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleInput {
+    pub curve_id: Uuid,
+    pub reason: StaleReason,
+    /// Version and hash recorded at execution time
+    pub recorded_version: i64,
+    pub recorded_hash: String,
+    /// Current version and hash (None if the curve was deleted)
+    pub current_version: Option<i64>,
+    pub current_hash: Option<String>,
+}
+
+/// Staleness of one derived curve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedCurveStatus {
+    pub curve_id: Uuid,
+    pub well_id: Uuid,
+    pub mnemonic: String,
+    /// Execution that produced the curve
+    pub execution_id: Option<Uuid>,
+    pub udf_id: Option<String>,
+    /// True if any recorded input no longer matches its curve
+    pub is_stale: bool,
+    pub stale_inputs: Vec<StaleInput>,
+    /// Why staleness could not be determined (e.g., missing execution record)
+    pub error: Option<String>,
+}
+
+/// The execution that produced a derived curve, as stored in DataForge.
+#[derive(Debug, Clone)]
+pub struct SourceExecution {
+    pub execution_id: Uuid,
+    pub udf_id: String,
+    pub udf_version: String,
+    pub parameters: serde_json::Value,
+    /// Interval each depth range or zone parameter resolved to (empty for
+    /// records from before intervals were recorded)
+    pub depth_intervals: BTreeMap<String, DepthInterval>,
+    pub inputs: Vec<InputReference>,
+    /// Saved outputs (empty for records from before multi-output support)
+    pub outputs: Vec<OutputReference>,
+    /// First saved output
+    pub output_curve_id: Option<Uuid>,
+    pub output_parquet_hash: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub compute_app_version: String,
+}
+
+impl SourceExecution {
+    /// The parts of a full execution record that DataForge stores.
+    pub fn from_record(record: &ExecutionRecord) -> Self {
+        Self {
+            execution_id: record.id,
+            udf_id: record.udf_id.clone(),
+            udf_version: record.udf_version.clone(),
+            parameters: record.parameters.clone(),
+            depth_intervals: record.depth_intervals.clone(),
+            inputs: record.inputs.clone(),
+            outputs: record.outputs.clone(),
+            output_curve_id: record.output_curve_id,
+            output_parquet_hash: record.output_parquet_hash.clone(),
+            started_at: record.started_at,
+            completed_at: record.completed_at,
+            compute_app_version: record.compute_app_version.clone(),
+        }
+    }
+}
+
+/// What is needed to recompute a derived curve.
+#[derive(Debug, Clone)]
+pub struct RecomputeTarget {
+    pub well_id: Uuid,
+    pub workspace_id: Uuid,
+    pub source: SourceExecution,
+    /// Derived curve each output was saved as, by output name
+    pub output_curves: HashMap<String, Uuid>,
+}
+
+/// Check the derived curves in a well or workspace for stale inputs.
+///
+/// Curves are returned by well name and mnemonic, stale or not.
+pub fn check_staleness(
+    db: &Connection,
+    scope: StalenessScope,
+) -> Result<Vec<DerivedCurveStatus>, UdfError> {
+    // Without the derived curve columns no curve has been derived yet
+    if !has_column(db, "curves", "is_derived")? {
+        return Ok(Vec::new());
+    }
+
+    let (filter, id) = match scope {
+        StalenessScope::Well(id) => ("w.id = ?1", id),
+        StalenessScope::Workspace(id) => ("w.workspace_id = ?1", id),
+    };
+    let mut stmt = db.prepare(&format!(
+        r#"SELECT c.id, c.well_id, c.mnemonic, c.source_execution_id
+           FROM curves c
+           JOIN wells w ON c.well_id = w.id
+           WHERE {} AND COALESCE(c.is_derived, 0) = 1 AND c.deleted_at IS NULL
+           ORDER BY w.name, c.mnemonic"#,
+        filter
+    ))?;
+
+    let rows = stmt
+        .query_map([id.to_string()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let has_records = has_table(db, "execution_records")?;
+    rows.into_iter()
+        .map(|(curve_id, well_id, mnemonic, execution_id)| {
+            let mut status = DerivedCurveStatus {
+                curve_id: parse_uuid(&curve_id)?,
+                well_id: parse_uuid(&well_id)?,
+                mnemonic,
+                execution_id: None,
+                udf_id: None,
+                is_stale: false,
+                stale_inputs: Vec::new(),
+                error: None,
+            };
+
+            let Some(execution_id) = execution_id else {
+                status.error = Some("Derived curve has no execution ID".to_string());
+                return Ok(status);
+            };
+            let source = if has_records {
+                load_source_execution(db, &execution_id)?
+            } else {
+                None
+            };
+            let Some(source) = source else {
+                status.error = Some(format!("Execution record {} not found", execution_id));
+                return Ok(status);
+            };
+
+            status.stale_inputs = stale_inputs(db, &source.inputs)?;
+            status.is_stale = !status.stale_inputs.is_empty();
+            status.execution_id = Some(source.execution_id);
+            status.udf_id = Some(source.udf_id);
+            Ok(status)
+        })
+        .collect()
+}
+
+/// Compare recorded inputs with the current `curves` rows.
+pub fn stale_inputs(
+    db: &Connection,
+    inputs: &[InputReference],
+) -> Result<Vec<StaleInput>, UdfError> {
+    let mut stale = Vec::new();
+    for input in inputs {
+        let current: Option<(i64, Option<String>, bool)> = db
+            .query_row(
+                r#"SELECT COALESCE(version, 1),
+                          COALESCE(gridded_parquet_hash, native_parquet_hash),
+                          deleted_at IS NOT NULL
+                   FROM curves WHERE id = ?1"#,
+                [input.curve_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let reason = match &current {
+            None | Some((_, _, true)) => Some(StaleReason::Deleted),
+            Some((_, hash, _)) if hash.as_deref() != Some(input.parquet_hash.as_str()) => {
+                Some(StaleReason::DataChanged)
+            }
+            Some((version, _, _)) if *version != input.version => Some(StaleReason::VersionChanged),
+            Some(_) => None,
+        };
+
+        if let Some(reason) = reason {
+            let (current_version, current_hash) = match current {
+                Some((version, hash, false)) => (Some(version), hash),
+                _ => (None, None),
+            };
+            stale.push(StaleInput {
+                curve_id: input.curve_id,
+                reason,
+                recorded_version: input.version,
+                recorded_hash: input.parquet_hash.clone(),
+                current_version,
+                current_hash,
+            });
+        }
+    }
+    Ok(stale)
+}
+
+/// Load an execution record from DataForge's `execution_records` table.
+pub fn load_source_execution(
+    db: &Connection,
+    execution_id: &str,
+) -> Result<Option<SourceExecution>, UdfError> {
+    // Tables created before multi-output support have no outputs column
+    let outputs_column = if has_column(db, "execution_records", "outputs")? {
+        "outputs"
+    } else {
+        "NULL"
+    };
+    // Nor those created before interval parameters were recorded
+    let intervals_column = if has_column(db, "execution_records", "depth_intervals")? {
+        "depth_intervals"
+    } else {
+        "NULL"
+    };
+
+    let row = db
+        .query_row(
+            &format!(
+                r#"SELECT udf_id, udf_version, parameters, inputs, output_curve_id,
+                          output_parquet_hash, {}, started_at, completed_at,
+                          compute_app_version, {}
+                   FROM execution_records WHERE id = ?1"#,
+                outputs_column, intervals_column
+            ),
+            [execution_id],
+            |row| {
+                Ok(ExecutionRow {
+                    udf_id: row.get(0)?,
+                    udf_version: row.get(1)?,
+                    parameters: row.get(2)?,
+                    inputs: row.get(3)?,
+                    output_curve_id: row.get(4)?,
+                    output_parquet_hash: row.get(5)?,
+                    outputs: row.get(6)?,
+                    started_at: row.get(7)?,
+                    completed_at: row.get(8)?,
+                    compute_app_version: row.get(9)?,
+                    depth_intervals: row.get(10)?,
+                })
+            },
+        )
+        .optional()?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(SourceExecution {
+        execution_id: parse_uuid(execution_id)?,
+        udf_id: row.udf_id,
+        udf_version: row.udf_version,
+        parameters: serde_json::from_str(&row.parameters)?,
+        depth_intervals: match row.depth_intervals {
+            Some(json) => serde_json::from_str(&json)?,
+            None => BTreeMap::new(),
+        },
+        inputs: serde_json::from_str(&row.inputs)?,
+        outputs: match row.outputs {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        },
+        output_curve_id: row.output_curve_id.as_deref().map(parse_uuid).transpose()?,
+        output_parquet_hash: row.output_parquet_hash,
+        started_at: parse_timestamp(&row.started_at)?,
+        completed_at: row
+            .completed_at
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()?,
+        compute_app_version: row.compute_app_version,
+    }))
+}
+
+/// Columns of an `execution_records` row, before parsing.
+struct ExecutionRow {
+    udf_id: String,
+    udf_version: String,
+    parameters: String,
+    inputs: String,
+    output_curve_id: Option<String>,
+    output_parquet_hash: Option<String>,
+    outputs: Option<String>,
+    started_at: String,
+    completed_at: Option<String>,
+    compute_app_version: String,
+    depth_intervals: Option<String>,
+}
+
+/// Load the execution a derived curve was produced by, for recomputation.
+pub fn recompute_target(db: &Connection, curve_id: Uuid) -> Result<RecomputeTarget, UdfError> {
+    if !has_column(db, "curves", "is_derived")? {
+        return Err(UdfError::IncompatibleData(format!(
+            "Curve {} is not a derived curve",
+            curve_id
+        )));
+    }
+
+    let (well_id, workspace_id, is_derived, execution_id): (String, String, bool, Option<String>) =
+        db.query_row(
+            r#"SELECT c.well_id, w.workspace_id, COALESCE(c.is_derived, 0) != 0,
+                      c.source_execution_id
+               FROM curves c
+               JOIN wells w ON c.well_id = w.id
+               WHERE c.id = ?1"#,
+            [curve_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| UdfError::CurveLoadError(format!("Curve not found: {}", e)))?;
+
+    let execution_id = match (is_derived, execution_id) {
+        (true, Some(execution_id)) => execution_id,
+        _ => {
+            return Err(UdfError::IncompatibleData(format!(
+                "Curve {} is not a derived curve",
+                curve_id
+            )))
+        }
+    };
+
+    let source = load_source_execution(db, &execution_id)?.ok_or_else(|| {
+        UdfError::DatabaseError(format!("Execution record {} not found", execution_id))
+    })?;
+
+    Ok(RecomputeTarget {
+        well_id: parse_uuid(&well_id)?,
+        workspace_id: parse_uuid(&workspace_id)?,
+        output_curves: source
+            .outputs
+            .iter()
+            .map(|output| (output.name.clone(), output.curve_id))
+            .collect(),
+        source,
+    })
+}
+
+pub(crate) fn parse_uuid(value: &str) -> Result<Uuid, UdfError> {
+    Uuid::parse_str(value)
+        .map_err(|e| UdfError::DatabaseError(format!("Invalid UUID '{}': {}", value, e)))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, UdfError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| UdfError::DatabaseError(format!("Invalid timestamp '{}': {}", value, e)))
+}
+
+pub(crate) fn has_table(db: &Connection, table: &str) -> Result<bool, UdfError> {
+    Ok(db.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get(0),
+    )?)
+}
+
+pub(crate) fn has_column(db: &Connection, table: &str, column: &str) -> Result<bool, UdfError> {
+    Ok(db.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::data_loader::save_execution_record;
+    use crate::compute::test_support::create_dataforge_db;
+    use crate::compute::types::ExecutionStatus;
+    use std::collections::BTreeMap;
+
+    fn insert_curve(db: &Connection, well_id: Uuid, mnemonic: &str, hash: &str) -> Uuid {
+        let curve_id = Uuid::new_v4();
+        db.execute(
+            "INSERT INTO curves (id, well_id, mnemonic, native_parquet_hash) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![curve_id.to_string(), well_id.to_string(), mnemonic, hash],
+        )
+        .unwrap();
+        curve_id
+    }
+
+    /// Save an execution over `input` and a derived curve produced by it
+    fn insert_derived(db: &Connection, well_id: Uuid, input: Uuid, hash: &str) -> Uuid {
+        let curve_id = Uuid::new_v4();
+        let record = ExecutionRecord {
+            id: Uuid::new_v4(),
+            udf_id: "petro:vshale_linear".to_string(),
+            udf_version: "1.0.0".to_string(),
+            well_id,
+            workspace_id: Uuid::nil(),
+            inputs: vec![InputReference {
+                curve_id: input,
+                version: 1,
+                parquet_hash: hash.to_string(),
+                unit_conversion: None,
+                alignment: None,
+            }],
+            parameters: serde_json::json!({ "gr_curve": input, "gr_min": 30.0 }),
+            parameter_conversions: Vec::new(),
+            depth_intervals: BTreeMap::new(),
+            output_curve_id: Some(curve_id),
+            output_parquet_hash: Some("vsh_hash".to_string()),
+            outputs: vec![OutputReference {
+                name: "vshale".to_string(),
+                curve_id,
+                mnemonic: "VSH".to_string(),
+                parquet_hash: "vsh_hash".to_string(),
+            }],
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            compute_app_version: "test".to_string(),
+            status: ExecutionStatus::Completed,
+            error_message: None,
+            workflow: None,
+            cache_key: None,
+            cached_from: None,
+        };
+        save_execution_record(db, &record).unwrap();
+        db.execute(
+            r#"INSERT INTO curves (id, well_id, mnemonic, native_parquet_hash, is_derived, source_execution_id)
+               VALUES (?1, ?2, 'VSH', 'vsh_hash', 1, ?3)"#,
+            rusqlite::params![curve_id.to_string(), well_id.to_string(), record.id.to_string()],
+        )
+        .unwrap();
+        curve_id
+    }
+
+    #[test]
+    fn test_detects_changed_and_deleted_inputs() {
+        let db = create_dataforge_db();
+        let workspace_id = Uuid::new_v4();
+        let well_id = Uuid::new_v4();
+        db.execute(
+            "INSERT INTO wells (id, workspace_id, name) VALUES (?1, ?2, 'W-1')",
+            [well_id.to_string(), workspace_id.to_string()],
+        )
+        .unwrap();
+
+        let gr = insert_curve(&db, well_id, "GR", "gr_hash");
+        let vsh = insert_derived(&db, well_id, gr, "gr_hash");
+
+        let statuses = check_staleness(&db, StalenessScope::Workspace(workspace_id)).unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].curve_id, vsh);
+        assert_eq!(statuses[0].udf_id.as_deref(), Some("petro:vshale_linear"));
+        assert!(!statuses[0].is_stale);
+
+        // A metadata edit bumps the version without changing the data
+        db.execute(
+            "UPDATE curves SET version = 2 WHERE id = ?1",
+            [gr.to_string()],
+        )
+        .unwrap();
+        let statuses = check_staleness(&db, StalenessScope::Well(well_id)).unwrap();
+        assert!(statuses[0].is_stale);
+        assert_eq!(
+            statuses[0].stale_inputs[0].reason,
+            StaleReason::VersionChanged
+        );
+
+        // Re-gridding changes the data the loader reads
+        db.execute(
+            "UPDATE curves SET gridded_parquet_hash = 'gridded' WHERE id = ?1",
+            [gr.to_string()],
+        )
+        .unwrap();
+        let stale = &check_staleness(&db, StalenessScope::Well(well_id)).unwrap()[0].stale_inputs;
+        assert_eq!(stale[0].reason, StaleReason::DataChanged);
+        assert_eq!(stale[0].current_hash.as_deref(), Some("gridded"));
+
+        db.execute(
+            "UPDATE curves SET deleted_at = '2024-01-01' WHERE id = ?1",
+            [gr.to_string()],
+        )
+        .unwrap();
+        let stale = &check_staleness(&db, StalenessScope::Well(well_id)).unwrap()[0].stale_inputs;
+        assert_eq!(stale[0].reason, StaleReason::Deleted);
+        assert_eq!(stale[0].current_version, None);
+
+        let target = recompute_target(&db, vsh).unwrap();
+        assert_eq!(target.well_id, well_id);
+        assert_eq!(target.workspace_id, workspace_id);
+        assert_eq!(target.output_curves.get("vshale"), Some(&vsh));
+        assert_eq!(target.source.parameters["gr_min"], 30.0);
+        assert!(
+            recompute_target(&db, gr).is_err(),
+            "raw curves cannot be recomputed"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Curve type taxonomy.
+//!
+//! DataForge classifies curves by their `curve_properties` row (e.g.
+//! "deep_resistivity", "shear_slowness"). The taxonomy maps each property
+//! to the `CurveDataType` UDFs ask for: properties the engine knows map to
+//! their own type, other properties to the type of their family, so a
+//! spectral gamma ray property still satisfies a gamma ray input. Curves
+//! keep their property ID, so parameters can also target a property the
+//! engine has no type for.
+
+use crate::compute::error::UdfError;
+use crate::compute::staleness::{has_column, has_table};
+use crate::compute::types::CurveDataType;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Properties with a curve type of their own.
+const KNOWN_PROPERTIES: &[(&str, CurveDataType)] = &[
+    ("gamma_ray", CurveDataType::GammaRay),
+    ("bulk_density", CurveDataType::Density),
+    ("neutron_porosity", CurveDataType::NeutronPorosity),
+    ("resistivity", CurveDataType::Resistivity),
+    ("deep_resistivity", CurveDataType::DeepResistivity),
+    ("medium_resistivity", CurveDataType::MediumResistivity),
+    ("shallow_resistivity", CurveDataType::ShallowResistivity),
+    ("caliper", CurveDataType::Caliper),
+    ("compressional_slowness", CurveDataType::Sonic),
+    ("shear_slowness", CurveDataType::ShearSonic),
+    ("spontaneous_potential", CurveDataType::SpontaneousPotential),
+    ("photoelectric", CurveDataType::PhotoelectricFactor),
+    ("depth", CurveDataType::Depth),
+];
+
+/// Curve type of each property family.
+const FAMILIES: &[(&str, CurveDataType)] = &[
+    ("gamma_ray", CurveDataType::GammaRay),
+    ("density", CurveDataType::Density),
+    ("neutron", CurveDataType::NeutronPorosity),
+    ("resistivity", CurveDataType::Resistivity),
+    ("caliper", CurveDataType::Caliper),
+    ("sonic", CurveDataType::Sonic),
+    ("acoustic", CurveDataType::Sonic),
+    ("spontaneous_potential", CurveDataType::SpontaneousPotential),
+    ("photoelectric", CurveDataType::PhotoelectricFactor),
+    ("depth", CurveDataType::Depth),
+];
+
+/// A curve property and the curve type it maps to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurveProperty {
+    /// `curve_properties` ID (e.g., "deep_resistivity")
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Family ID (e.g., "resistivity"), if known
+    pub family: Option<String>,
+    /// Curve type of the property
+    pub curve_type: CurveDataType,
+}
+
+/// Mapping of curve properties to curve types.
+#[derive(Debug, Clone)]
+pub struct CurveTaxonomy {
+    properties: HashMap<String, CurveProperty>,
+}
+
+impl CurveTaxonomy {
+    /// Taxonomy of the properties the engine knows.
+    pub fn builtin() -> Self {
+        let properties = KNOWN_PROPERTIES
+            .iter()
+            .map(|(id, curve_type)| {
+                let property = CurveProperty {
+                    id: id.to_string(),
+                    name: curve_type.display_name().to_string(),
+                    family: family_id(curve_type.family()).map(str::to_string),
+                    curve_type: *curve_type,
+                };
+                (id.to_string(), property)
+            })
+            .collect();
+        Self { properties }
+    }
+
+    /// Load the taxonomy from DataForge's `curve_properties` table.
+    ///
+    /// Rows extend the built-in properties. The `name` and `family` columns
+    /// are optional; databases without the table get the built-in taxonomy.
+    pub fn load(db: &Connection) -> Result<Self, UdfError> {
+        let mut taxonomy = Self::builtin();
+        if !has_table(db, "curve_properties")? {
+            return Ok(taxonomy);
+        }
+
+        let mut columns = Vec::new();
+        for column in ["name", "family"] {
+            let present = has_column(db, "curve_properties", column)?;
+            columns.push(if present { column } else { "NULL" });
+        }
+        let mut stmt = db.prepare(&format!(
+            "SELECT id, {} FROM curve_properties",
+            columns.join(", ")
+        ))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, name, family) in rows {
+            taxonomy.insert(id, name, family);
+        }
+        Ok(taxonomy)
+    }
+
+    /// Add or update a property.
+    ///
+    /// Known properties keep their own type; others get the type of their
+    /// family, or `Unknown` without a known family.
+    pub fn insert(&mut self, id: String, name: Option<String>, family: Option<String>) {
+        let existing = self.properties.get(&id);
+        let curve_type = known_type(&id)
+            .or_else(|| family.as_deref().and_then(family_type))
+            .unwrap_or(CurveDataType::Unknown);
+        let property = CurveProperty {
+            name: name
+                .or_else(|| existing.map(|p| p.name.clone()))
+                .unwrap_or_else(|| id.clone()),
+            family: family.or_else(|| existing.and_then(|p| p.family.clone())),
+            curve_type,
+            id: id.clone(),
+        };
+        self.properties.insert(id, property);
+    }
+
+    /// Look up a property by ID.
+    pub fn get(&self, property_id: &str) -> Option<&CurveProperty> {
+        self.properties.get(property_id)
+    }
+
+    /// All properties, sorted by ID.
+    pub fn properties(&self) -> Vec<&CurveProperty> {
+        let mut properties: Vec<&CurveProperty> = self.properties.values().collect();
+        properties.sort_by(|a, b| a.id.cmp(&b.id));
+        properties
+    }
+
+    /// Curve type of a property (`Unknown` for unmapped properties).
+    pub fn curve_type(&self, property_id: &str) -> CurveDataType {
+        self.get(property_id)
+            .map(|p| p.curve_type)
+            .unwrap_or(CurveDataType::Unknown)
+    }
+
+    /// Curve type code of a property for the frontend (e.g., "RD").
+    ///
+    /// Properties without a typed code pass through as their ID.
+    pub fn type_code(&self, property_id: &str) -> String {
+        self.curve_type(property_id)
+            .code()
+            .map(str::to_string)
+            .unwrap_or_else(|| property_id.to_string())
+    }
+}
+
+impl Default for CurveTaxonomy {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+fn known_type(property_id: &str) -> Option<CurveDataType> {
+    KNOWN_PROPERTIES
+        .iter()
+        .find(|(id, _)| *id == property_id)
+        .map(|(_, curve_type)| *curve_type)
+}
+
+fn family_type(family: &str) -> Option<CurveDataType> {
+    FAMILIES
+        .iter()
+        .find(|(id, _)| *id == family)
+        .map(|(_, curve_type)| *curve_type)
+}
+
+fn family_id(curve_type: CurveDataType) -> Option<&'static str> {
+    FAMILIES
+        .iter()
+        .find(|(_, t)| *t == curve_type)
+        .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_taxonomy() {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            r#"CREATE TABLE curve_properties (id TEXT PRIMARY KEY, name TEXT, family TEXT);
+               INSERT INTO curve_properties VALUES
+                   ('deep_resistivity', 'Deep Resistivity', 'resistivity'),
+                   ('spectral_thorium', 'Thorium', 'gamma_ray'),
+                   ('nmr_t2', 'NMR T2 Distribution', 'nmr');"#,
+        )
+        .unwrap();
+        let taxonomy = CurveTaxonomy::load(&db).unwrap();
+
+        assert_eq!(
+            taxonomy.curve_type("deep_resistivity"),
+            CurveDataType::DeepResistivity
+        );
+        assert_eq!(taxonomy.type_code("deep_resistivity"), "RD");
+        // Subtypes the engine has no type for map to their family
+        assert_eq!(
+            taxonomy.curve_type("spectral_thorium"),
+            CurveDataType::GammaRay
+        );
+        // Unknown properties pass through instead of being flattened
+        assert_eq!(taxonomy.curve_type("nmr_t2"), CurveDataType::Unknown);
+        assert_eq!(taxonomy.type_code("nmr_t2"), "nmr_t2");
+        assert_eq!(
+            taxonomy.get("nmr_t2").unwrap().family.as_deref(),
+            Some("nmr")
+        );
+
+        // Built-in properties are there without a table
+        let empty = Connection::open_in_memory().unwrap();
+        let builtin = CurveTaxonomy::load(&empty).unwrap();
+        assert_eq!(builtin.type_code("shear_slowness"), "DTS");
+    }
+}
@@ -0,0 +1,168 @@
+//! Fixtures shared by the compute tests.
+
+use crate::compute::data_loader::init_compute_schema;
+use crate::compute::engine::{CurveLoader, CurveMetadataInfo, ExecutionEngine, WellMarker};
+use crate::compute::error::UdfError;
+use crate::compute::mnemonics::MnemonicDictionary;
+use crate::compute::providers::register_builtin_providers;
+use crate::compute::registry::UdfRegistry;
+use crate::compute::types::{CurveData, CurveDataType};
+use rusqlite::Connection;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Engine with the built-in providers registered.
+pub fn create_engine() -> ExecutionEngine {
+    let mut registry = UdfRegistry::new();
+    register_builtin_providers(&mut registry).unwrap();
+    ExecutionEngine::new(Arc::new(registry), "test")
+}
+
+/// In-memory database with the subset of DataForge's schema the compute
+/// module reads and writes, and the compute tables.
+pub fn create_dataforge_db() -> Connection {
+    let db = Connection::open_in_memory().unwrap();
+    db.execute_batch(
+        r#"
+        CREATE TABLE wells (id TEXT PRIMARY KEY, workspace_id TEXT NOT NULL, name TEXT);
+        CREATE TABLE curves (
+            id TEXT PRIMARY KEY,
+            well_id TEXT NOT NULL,
+            mnemonic TEXT NOT NULL,
+            unit TEXT,
+            description TEXT,
+            native_top_depth REAL,
+            native_bottom_depth REAL,
+            native_sample_count INTEGER,
+            min_value REAL,
+            max_value REAL,
+            mean_value REAL,
+            null_count INTEGER,
+            native_parquet_hash TEXT,
+            gridded_parquet_hash TEXT,
+            version INTEGER NOT NULL DEFAULT 1,
+            quality_flag TEXT,
+            is_derived INTEGER DEFAULT 0,
+            source_execution_id TEXT,
+            created_by TEXT,
+            deleted_at TEXT
+        );
+        CREATE TABLE blob_registry (hash TEXT PRIMARY KEY, size_bytes INTEGER NOT NULL);
+        "#,
+    )
+    .unwrap();
+    init_compute_schema(&db).unwrap();
+    db
+}
+
+/// Curve with a new ID, typed from its mnemonic as on import.
+///
+/// The content hash is derived from the mnemonic. Tests set another type,
+/// hash or property with struct update syntax.
+pub fn curve(mnemonic: &str, unit: &str, depths: Vec<f64>, values: Vec<Option<f64>>) -> CurveData {
+    CurveData {
+        curve_id: Uuid::new_v4(),
+        mnemonic: mnemonic.to_string(),
+        curve_type: MnemonicDictionary::defaults()
+            .detect(mnemonic)
+            .unwrap_or(CurveDataType::Unknown),
+        property_id: None,
+        unit: unit.to_string(),
+        depths: Arc::new(depths),
+        values,
+        parquet_hash: format!("{}_hash", mnemonic),
+        version: 1,
+    }
+}
+
+/// Gamma ray curve over 100-101.5 with a null sample.
+pub fn gr_curve() -> CurveData {
+    curve(
+        "GR",
+        "gAPI",
+        vec![100.0, 100.5, 101.0, 101.5],
+        vec![Some(30.0), Some(50.0), None, Some(100.0)],
+    )
+}
+
+/// In-memory curve loader with curves grouped by well.
+#[derive(Default)]
+pub struct MockCurveLoader {
+    wells: Vec<(Uuid, Vec<Arc<CurveData>>)>,
+    markers: Vec<WellMarker>,
+    depth_unit: Option<String>,
+}
+
+impl MockCurveLoader {
+    /// Loader serving the curves of a single well.
+    pub fn with_curves<C: Into<Arc<CurveData>>>(curves: impl IntoIterator<Item = C>) -> Self {
+        Self::default().with_well(Uuid::new_v4(), curves)
+    }
+
+    /// Add a well and its curves.
+    pub fn with_well<C: Into<Arc<CurveData>>>(
+        mut self,
+        well_id: Uuid,
+        curves: impl IntoIterator<Item = C>,
+    ) -> Self {
+        self.wells
+            .push((well_id, curves.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Set the depth unit of the wells.
+    pub fn with_depth_unit(mut self, unit: &str) -> Self {
+        self.depth_unit = Some(unit.to_string());
+        self
+    }
+
+    /// Add a well top, shared by the wells.
+    pub fn with_marker(mut self, name: &str, measured_depth: f64) -> Self {
+        self.markers.push(WellMarker {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            measured_depth,
+            tvd: None,
+            marker_type: None,
+            quality: None,
+            comments: None,
+        });
+        self
+    }
+}
+
+impl CurveLoader for MockCurveLoader {
+    fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+        self.wells
+            .iter()
+            .flat_map(|(_, curves)| curves.iter())
+            .find(|c| c.curve_id == curve_id)
+            .cloned()
+            .ok_or_else(|| UdfError::CurveLoadError(format!("Curve not found: {}", curve_id)))
+    }
+
+    fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError> {
+        Ok(CurveMetadataInfo::from_curve(&self.load_curve(curve_id)?))
+    }
+
+    fn list_well_curves(&self, well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError> {
+        Ok(self
+            .wells
+            .iter()
+            .filter(|(id, _)| *id == well_id)
+            .flat_map(|(_, curves)| curves.iter().map(|c| CurveMetadataInfo::from_curve(c)))
+            .collect())
+    }
+
+    fn list_wells(&self, _workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        Ok(self.wells.iter().map(|(id, _)| *id).collect())
+    }
+
+    fn list_well_markers(&self, _well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+        Ok(self.markers.clone())
+    }
+
+    fn well_depth_unit(&self, _well_id: Uuid) -> Result<Option<String>, UdfError> {
+        Ok(self.depth_unit.clone())
+    }
+}
@@ -1,7 +1,10 @@
 //! Core types for the UDF system.
 
+use crate::compute::alignment::DepthAlignment;
+use crate::compute::interval::DepthInterval;
+use crate::compute::units::{self, ParameterConversion, UnitConversion};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -19,10 +22,18 @@ pub enum CurveDataType {
     NeutronPorosity,
     /// Resistivity - electrical resistance measurement
     Resistivity,
+    /// Deep resistivity - resistivity of the uninvaded formation
+    DeepResistivity,
+    /// Medium resistivity - resistivity of the transition zone
+    MediumResistivity,
+    /// Shallow resistivity - resistivity of the invaded zone
+    ShallowResistivity,
     /// Caliper - borehole diameter measurement
     Caliper,
     /// Sonic - acoustic travel time measurement
     Sonic,
+    /// Shear sonic - shear wave travel time measurement
+    ShearSonic,
     /// Spontaneous Potential - natural electrical potential
     SpontaneousPotential,
     /// Photo-electric Factor - lithology indicator
@@ -36,6 +47,25 @@ pub enum CurveDataType {
 }
 
 impl CurveDataType {
+    /// All curve types
+    pub const ALL: [CurveDataType; 15] = [
+        CurveDataType::GammaRay,
+        CurveDataType::Density,
+        CurveDataType::NeutronPorosity,
+        CurveDataType::Resistivity,
+        CurveDataType::DeepResistivity,
+        CurveDataType::MediumResistivity,
+        CurveDataType::ShallowResistivity,
+        CurveDataType::Caliper,
+        CurveDataType::Sonic,
+        CurveDataType::ShearSonic,
+        CurveDataType::SpontaneousPotential,
+        CurveDataType::PhotoelectricFactor,
+        CurveDataType::Depth,
+        CurveDataType::Computed,
+        CurveDataType::Unknown,
+    ];
+
     /// Get the display name for this curve type
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -43,8 +73,12 @@ impl CurveDataType {
             CurveDataType::Density => "Bulk Density",
             CurveDataType::NeutronPorosity => "Neutron Porosity",
             CurveDataType::Resistivity => "Resistivity",
+            CurveDataType::DeepResistivity => "Deep Resistivity",
+            CurveDataType::MediumResistivity => "Medium Resistivity",
+            CurveDataType::ShallowResistivity => "Shallow Resistivity",
             CurveDataType::Caliper => "Caliper",
             CurveDataType::Sonic => "Sonic",
+            CurveDataType::ShearSonic => "Shear Sonic",
             CurveDataType::SpontaneousPotential => "Spontaneous Potential",
             CurveDataType::PhotoelectricFactor => "Photo-electric Factor",
             CurveDataType::Depth => "Depth",
@@ -59,9 +93,12 @@ impl CurveDataType {
             CurveDataType::GammaRay => "gAPI",
             CurveDataType::Density => "g/cm³",
             CurveDataType::NeutronPorosity => "v/v",
-            CurveDataType::Resistivity => "ohm-m",
+            CurveDataType::Resistivity
+            | CurveDataType::DeepResistivity
+            | CurveDataType::MediumResistivity
+            | CurveDataType::ShallowResistivity => "ohm-m",
             CurveDataType::Caliper => "in",
-            CurveDataType::Sonic => "μs/ft",
+            CurveDataType::Sonic | CurveDataType::ShearSonic => "μs/ft",
             CurveDataType::SpontaneousPotential => "mV",
             CurveDataType::PhotoelectricFactor => "b/e",
             CurveDataType::Depth => "m",
@@ -77,14 +114,99 @@ impl CurveDataType {
             "RHOB" => CurveDataType::Density,
             "NPHI" => CurveDataType::NeutronPorosity,
             "RT" => CurveDataType::Resistivity,
+            "RD" => CurveDataType::DeepResistivity,
+            "RM" => CurveDataType::MediumResistivity,
+            "RS" => CurveDataType::ShallowResistivity,
             "CALI" => CurveDataType::Caliper,
             "DT" => CurveDataType::Sonic,
+            "DTS" => CurveDataType::ShearSonic,
             "SP" => CurveDataType::SpontaneousPotential,
             "PE" => CurveDataType::PhotoelectricFactor,
             "DEPTH" => CurveDataType::Depth,
             _ => CurveDataType::Unknown,
         }
     }
+
+    /// MainCurveType code of this curve type (e.g., "RD"), if it has one
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            CurveDataType::GammaRay => Some("GR"),
+            CurveDataType::Density => Some("RHOB"),
+            CurveDataType::NeutronPorosity => Some("NPHI"),
+            CurveDataType::Resistivity => Some("RT"),
+            CurveDataType::DeepResistivity => Some("RD"),
+            CurveDataType::MediumResistivity => Some("RM"),
+            CurveDataType::ShallowResistivity => Some("RS"),
+            CurveDataType::Caliper => Some("CALI"),
+            CurveDataType::Sonic => Some("DT"),
+            CurveDataType::ShearSonic => Some("DTS"),
+            CurveDataType::SpontaneousPotential => Some("SP"),
+            CurveDataType::PhotoelectricFactor => Some("PE"),
+            CurveDataType::Depth => Some("DEPTH"),
+            CurveDataType::Computed | CurveDataType::Unknown => None,
+        }
+    }
+
+    /// The family a curve type is a variety of (itself for families).
+    pub fn family(&self) -> CurveDataType {
+        match self {
+            CurveDataType::DeepResistivity
+            | CurveDataType::MediumResistivity
+            | CurveDataType::ShallowResistivity => CurveDataType::Resistivity,
+            CurveDataType::ShearSonic => CurveDataType::Sonic,
+            other => *other,
+        }
+    }
+
+    /// Check if a curve of this type can be used where `required` is
+    /// asked for: the type itself, or any variety of a required family.
+    pub fn satisfies(&self, required: CurveDataType) -> bool {
+        *self == required || self.family() == required
+    }
+}
+
+/// Family of measurement units, e.g. all density units.
+///
+/// Curve parameters may restrict the units of their curves to families.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnitFamily {
+    /// API gamma ray units
+    Radioactivity,
+    /// Mass per volume (g/cm³, kg/m³)
+    Density,
+    /// Electrical resistivity (ohm-m)
+    Resistivity,
+    /// Acoustic slowness (μs/ft, μs/m)
+    Slowness,
+    /// Length (m, ft, in)
+    Length,
+    /// Volume fraction (v/v, %, p.u.)
+    Fraction,
+    /// Electrical potential (mV)
+    Voltage,
+    /// Photoelectric absorption (b/e)
+    PhotoelectricAbsorption,
+}
+
+impl UnitFamily {
+    /// Get the display name for this unit family
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            UnitFamily::Radioactivity => "Radioactivity",
+            UnitFamily::Density => "Density",
+            UnitFamily::Resistivity => "Resistivity",
+            UnitFamily::Slowness => "Slowness",
+            UnitFamily::Length => "Length",
+            UnitFamily::Fraction => "Fraction",
+            UnitFamily::Voltage => "Voltage",
+            UnitFamily::PhotoelectricAbsorption => "Photoelectric Absorption",
+        }
+    }
+
+    /// Family of a unit string, if recognized by the unit registry.
+    pub fn of(unit: &str) -> Option<UnitFamily> {
+        units::find(unit).map(|u| u.family)
+    }
 }
 
 /// Immutable curve data for UDF inputs.
@@ -99,6 +221,8 @@ pub struct CurveData {
     pub mnemonic: String,
     /// Detected curve type
     pub curve_type: CurveDataType,
+    /// DataForge `curve_properties` ID (e.g., "deep_resistivity"), if assigned
+    pub property_id: Option<String>,
     /// Unit of measurement
     pub unit: String,
     /// Depth values (shared across curves from same well)
@@ -163,6 +287,55 @@ pub struct UdfMetadata {
     pub version: String,
     /// Tags for search/filtering
     pub tags: Vec<String>,
+    /// Named outputs this UDF produces
+    #[serde(default)]
+    pub outputs: Vec<OutputDeclaration>,
+}
+
+/// Declaration of a named output curve produced by a UDF.
+///
+/// Declarations let the UI show what an execution will produce before it
+/// runs. The engine checks that a UDF produces exactly its declared outputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputDeclaration {
+    /// Output name, unique within the UDF (e.g., "vshale")
+    pub name: String,
+    /// Human-readable label
+    pub label: String,
+    /// Description of the output
+    pub description: String,
+    /// Output curve type (None if it depends on the inputs)
+    pub curve_type: Option<CurveDataType>,
+    /// Unit of measurement (None if it depends on the inputs)
+    pub unit: Option<String>,
+}
+
+impl OutputDeclaration {
+    /// Declare an output whose type and unit depend on the inputs
+    pub fn new(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: String::new(),
+            curve_type: None,
+            unit: None,
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_curve_type(mut self, curve_type: CurveDataType) -> Self {
+        self.curve_type = Some(curve_type);
+        self
+    }
+
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
 }
 
 /// Reference to an input curve used in execution.
@@ -174,6 +347,25 @@ pub struct InputReference {
     pub version: i64,
     /// Parquet content hash
     pub parquet_hash: String,
+    /// Conversion applied to the curve's values for its parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_conversion: Option<UnitConversion>,
+    /// Resampling of the curve onto the depths of the other inputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<DepthAlignment>,
+}
+
+/// Reference to a saved output curve of an execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputReference {
+    /// Output name as declared by the UDF
+    pub name: String,
+    /// Saved curve UUID
+    pub curve_id: Uuid,
+    /// Mnemonic the curve was saved under
+    pub mnemonic: String,
+    /// Parquet content hash
+    pub parquet_hash: String,
 }
 
 /// Status of a UDF execution.
@@ -187,6 +379,27 @@ pub enum ExecutionStatus {
     Cancelled,
 }
 
+impl ExecutionStatus {
+    /// Get the status as stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionStatus::Completed => "completed",
+            ExecutionStatus::Failed => "failed",
+            ExecutionStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Parse a status as stored in the database
+    pub fn parse(status: &str) -> Option<Self> {
+        match status.to_lowercase().as_str() {
+            "completed" => Some(ExecutionStatus::Completed),
+            "failed" => Some(ExecutionStatus::Failed),
+            "cancelled" => Some(ExecutionStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
 /// Record of a UDF execution for provenance tracking.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionRecord {
@@ -196,14 +409,29 @@ pub struct ExecutionRecord {
     pub udf_id: String,
     /// UDF version at time of execution
     pub udf_version: String,
+    /// Well the execution ran against
+    pub well_id: Uuid,
+    /// Workspace containing the well
+    pub workspace_id: Uuid,
     /// Input curve references
     pub inputs: Vec<InputReference>,
     /// Parameter values used
     pub parameters: serde_json::Value,
+    /// Numeric parameter values converted to another unit before execution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameter_conversions: Vec<ParameterConversion>,
+    /// Interval each depth range or zone parameter resolved to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub depth_intervals: BTreeMap<String, DepthInterval>,
     /// Output curve ID (if successful)
+    ///
+    /// For multi-output UDFs this is the first output; see `outputs`.
     pub output_curve_id: Option<Uuid>,
     /// Output parquet content hash
     pub output_parquet_hash: Option<String>,
+    /// All saved outputs of this execution
+    #[serde(default)]
+    pub outputs: Vec<OutputReference>,
     /// Execution start time
     pub started_at: chrono::DateTime<chrono::Utc>,
     /// Execution completion time
@@ -214,21 +442,66 @@ pub struct ExecutionRecord {
     pub status: ExecutionStatus,
     /// Error message if failed
     pub error_message: Option<String>,
+    /// Workflow node this execution ran as, if part of a workflow run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<WorkflowStep>,
+    /// Key identifying the UDF version, parameters and input content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_key: Option<String>,
+    /// Execution whose cached output was reused instead of running the UDF
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_from: Option<Uuid>,
+}
+
+/// Position of an execution within a workflow run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Workflow definition ID
+    pub workflow_id: Uuid,
+    /// ID shared by all node executions of one workflow run
+    pub run_id: Uuid,
+    /// Node ID within the workflow
+    pub node_id: String,
+    /// Curve parameters fed from upstream node outputs
+    #[serde(default)]
+    pub upstream: Vec<UpstreamInput>,
+}
+
+/// A curve parameter fed from the output of an upstream workflow node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamInput {
+    /// Parameter name on this node
+    pub parameter: String,
+    /// Upstream node ID
+    pub node_id: String,
+    /// Upstream output name
+    pub output: String,
+    /// Curve ID of the output (in-memory until saved)
+    pub curve_id: Uuid,
 }
 
 /// Output from a UDF execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UdfOutput {
-    /// Output curve data
-    pub curve_data: OutputCurveData,
+    /// Named output curves, in declaration order
+    pub outputs: Vec<NamedOutput>,
     /// Optional metadata to attach to output
     pub metadata: HashMap<String, serde_json::Value>,
     /// Warnings generated during execution
     pub warnings: Vec<String>,
 }
 
+/// A named output curve of a UDF execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedOutput {
+    /// Output name as declared by the UDF
+    pub name: String,
+    /// Output curve data
+    pub curve_data: OutputCurveData,
+}
+
 /// Output curve data from a UDF.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputCurveData {
     /// Suggested mnemonic for the output curve
     pub mnemonic: String,
@@ -245,15 +518,39 @@ pub struct OutputCurveData {
 }
 
 impl UdfOutput {
-    /// Create a new UDF output with curve data
-    pub fn new(curve_data: OutputCurveData) -> Self {
+    /// Create a new UDF output with a single named output curve
+    pub fn new(name: impl Into<String>, curve_data: OutputCurveData) -> Self {
         Self {
-            curve_data,
+            outputs: vec![NamedOutput {
+                name: name.into(),
+                curve_data,
+            }],
             metadata: HashMap::new(),
             warnings: Vec::new(),
         }
     }
 
+    /// Add another named output curve
+    pub fn add_output(&mut self, name: impl Into<String>, curve_data: OutputCurveData) {
+        self.outputs.push(NamedOutput {
+            name: name.into(),
+            curve_data,
+        });
+    }
+
+    /// Get an output curve by name
+    pub fn output(&self, name: &str) -> Option<&OutputCurveData> {
+        self.outputs
+            .iter()
+            .find(|o| o.name == name)
+            .map(|o| &o.curve_data)
+    }
+
+    /// The first output curve, used where a single result is shown
+    pub fn primary(&self) -> Option<&NamedOutput> {
+        self.outputs.first()
+    }
+
     /// Add a warning message
     pub fn add_warning(&mut self, warning: impl Into<String>) {
         self.warnings.push(warning.into());
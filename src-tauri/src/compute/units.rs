@@ -0,0 +1,376 @@
+//! Unit registry and conversions.
+//!
+//! Every known unit belongs to a `UnitFamily` (its dimension) and has a
+//! factor to the family's base unit, so any two units of a family convert
+//! by a single factor. Units are looked up by their spellings in DataForge
+//! and LAS files ("G/C3", "g/cc", "gAPI", "US/F", ...), ignoring case and
+//! spaces. Curve parameters can declare the unit they expect; the engine
+//! converts bound curves to it and records the conversion on the input.
+//! Numeric parameters can be converted to the unit of a bound curve or of
+//! the well's depths; those conversions are recorded on the execution.
+
+use crate::compute::engine::CurveLoader;
+use crate::compute::error::{UdfError, ValidationError};
+use crate::compute::parameters::{ParameterDefinition, ParameterValue, UnitTarget};
+use crate::compute::types::{CurveData, UnitFamily};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A unit of measurement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    /// Canonical symbol (e.g., "g/cm³")
+    pub symbol: &'static str,
+    /// Dimension of the unit
+    pub family: UnitFamily,
+    /// Value of one unit in the base unit of its family
+    pub factor: f64,
+    /// Accepted spellings, lowercase without spaces
+    aliases: &'static [&'static str],
+}
+
+/// Known units; the base unit of each family comes first.
+const UNITS: &[Unit] = &[
+    Unit {
+        symbol: "gAPI",
+        family: UnitFamily::Radioactivity,
+        factor: 1.0,
+        aliases: &["gapi", "api"],
+    },
+    Unit {
+        symbol: "g/cm³",
+        family: UnitFamily::Density,
+        factor: 1.0,
+        aliases: &["g/cm³", "g/cm3", "g/cc", "g/c3", "gm/cc"],
+    },
+    Unit {
+        symbol: "kg/m³",
+        family: UnitFamily::Density,
+        factor: 0.001,
+        aliases: &["kg/m³", "kg/m3", "k/m3"],
+    },
+    Unit {
+        symbol: "ohm-m",
+        family: UnitFamily::Resistivity,
+        factor: 1.0,
+        aliases: &["ohm-m", "ohmm", "ohm.m", "ohm·m", "ω·m", "ωm", "ω-m"],
+    },
+    Unit {
+        symbol: "μs/ft",
+        family: UnitFamily::Slowness,
+        factor: 1.0,
+        aliases: &["μs/ft", "µs/ft", "us/ft", "us/f", "usec/ft"],
+    },
+    Unit {
+        symbol: "μs/m",
+        family: UnitFamily::Slowness,
+        factor: 0.3048,
+        aliases: &["μs/m", "µs/m", "us/m", "usec/m"],
+    },
+    Unit {
+        symbol: "m",
+        family: UnitFamily::Length,
+        factor: 1.0,
+        aliases: &["m", "meter", "meters", "metre", "metres"],
+    },
+    Unit {
+        symbol: "ft",
+        family: UnitFamily::Length,
+        factor: 0.3048,
+        aliases: &["ft", "f", "feet", "foot"],
+    },
+    Unit {
+        symbol: "in",
+        family: UnitFamily::Length,
+        factor: 0.0254,
+        aliases: &["in", "inch", "inches"],
+    },
+    Unit {
+        symbol: "cm",
+        family: UnitFamily::Length,
+        factor: 0.01,
+        aliases: &["cm"],
+    },
+    Unit {
+        symbol: "mm",
+        family: UnitFamily::Length,
+        factor: 0.001,
+        aliases: &["mm"],
+    },
+    Unit {
+        symbol: "v/v",
+        family: UnitFamily::Fraction,
+        factor: 1.0,
+        aliases: &["v/v", "frac", "fraction", "dec", "decimal", "m3/m3"],
+    },
+    Unit {
+        symbol: "%",
+        family: UnitFamily::Fraction,
+        factor: 0.01,
+        aliases: &["%", "pu", "p.u.", "percent"],
+    },
+    Unit {
+        symbol: "mV",
+        family: UnitFamily::Voltage,
+        factor: 1.0,
+        aliases: &["mv"],
+    },
+    Unit {
+        symbol: "V",
+        family: UnitFamily::Voltage,
+        factor: 1000.0,
+        aliases: &["v"],
+    },
+    Unit {
+        symbol: "b/e",
+        family: UnitFamily::PhotoelectricAbsorption,
+        factor: 1.0,
+        aliases: &["b/e", "barns/electron", "barn/e"],
+    },
+];
+
+/// Look up a unit by any of its spellings.
+pub fn find(unit: &str) -> Option<&'static Unit> {
+    let normalized = unit.trim().to_lowercase().replace(' ', "");
+    UNITS
+        .iter()
+        .find(|u| u.aliases.contains(&normalized.as_str()))
+}
+
+/// Symbols of the known units of a family, base unit first.
+pub fn units_of(family: UnitFamily) -> Vec<&'static str> {
+    UNITS
+        .iter()
+        .filter(|u| u.family == family)
+        .map(|u| u.symbol)
+        .collect()
+}
+
+/// Conversion of values from one unit to another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitConversion {
+    /// Unit the values were stored in
+    pub from_unit: String,
+    /// Unit the values were converted to
+    pub to_unit: String,
+    /// Multiplier applied to every value
+    pub factor: f64,
+}
+
+impl UnitConversion {
+    /// Convert a value.
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.factor
+    }
+
+    /// Convert the values of a curve, which then carries the target unit.
+    pub fn apply_to_curve(&self, curve: &CurveData) -> CurveData {
+        CurveData {
+            unit: self.to_unit.clone(),
+            values: curve
+                .values
+                .iter()
+                .map(|v| v.map(|v| self.apply(v)))
+                .collect(),
+            ..curve.clone()
+        }
+    }
+}
+
+/// Numeric parameter value converted to the unit the UDF works in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterConversion {
+    /// Parameter name
+    pub parameter: String,
+    /// Value as entered
+    pub value: f64,
+    /// Unit the value was entered in
+    pub unit: String,
+    /// Value passed to the UDF
+    pub converted_value: f64,
+    /// Unit of the converted value
+    pub converted_unit: String,
+}
+
+/// Conversion from one unit to another, if one is needed.
+///
+/// Returns `None` for spellings of the same unit, and when either unit is
+/// not recognized, since many curves are loaded without a unit or with a
+/// vendor spelling. Units of different families cannot be converted.
+pub fn conversion(from: &str, to: &str) -> Result<Option<UnitConversion>, UdfError> {
+    let (Some(source), Some(target)) = (find(from), find(to)) else {
+        return Ok(None);
+    };
+
+    if source.family != target.family {
+        return Err(UdfError::UnitMismatch {
+            expected: format!("{} ({})", target.symbol, target.family.display_name()),
+            actual: format!("{} ({})", from, source.family.display_name()),
+        });
+    }
+
+    if source.symbol == target.symbol {
+        return Ok(None);
+    }
+
+    Ok(Some(UnitConversion {
+        from_unit: source.symbol.to_string(),
+        to_unit: target.symbol.to_string(),
+        factor: source.factor / target.factor,
+    }))
+}
+
+/// Convert numeric parameter values to the unit their parameter targets.
+///
+/// `curve_units` holds the unit of the curves bound to each curve parameter,
+/// after their own conversion. Quantities become plain numbers in the
+/// target unit, and plain numbers are converted from the parameter's unit.
+/// The well's depth unit is only loaded if a parameter targets it. Plain
+/// numbers are passed as entered when the target unit is unknown, but
+/// quantities cannot be interpreted without it.
+pub fn convert_parameters(
+    definitions: &[Box<dyn ParameterDefinition>],
+    parameters: &HashMap<String, ParameterValue>,
+    curve_units: &HashMap<&str, &str>,
+    well_id: Uuid,
+    loader: &dyn CurveLoader,
+) -> Result<(HashMap<String, ParameterValue>, Vec<ParameterConversion>), UdfError> {
+    let mut converted = parameters.clone();
+    let mut conversions = Vec::new();
+    let mut depth_unit: Option<Option<String>> = None;
+
+    for def in definitions {
+        let (Some(value_unit), Some(value)) = (def.value_unit(), parameters.get(def.name())) else {
+            continue;
+        };
+        let is_quantity = value.as_quantity().is_some();
+        let (value, unit) = match (value.as_quantity(), &value_unit.unit, value.as_f64()) {
+            (Some((value, unit)), _, _) => (value, unit.to_string()),
+            (None, Some(unit), Some(value)) => (value, unit.clone()),
+            _ => continue,
+        };
+
+        let target = match &value_unit.target {
+            Some(UnitTarget::Curve(parameter)) => {
+                curve_units.get(parameter.as_str()).map(|u| u.to_string())
+            }
+            Some(UnitTarget::Depth) => {
+                if depth_unit.is_none() {
+                    depth_unit = Some(loader.well_depth_unit(well_id)?);
+                }
+                depth_unit.clone().flatten()
+            }
+            None => value_unit.unit.clone(),
+        };
+        let Some(target) = target.filter(|t| find(t).is_some()) else {
+            if is_quantity {
+                return Err(UdfError::ParameterValidation(
+                    ValidationError::new(
+                        def.name(),
+                        format!(
+                            "Cannot convert from {}: the unit to convert to is unknown",
+                            unit
+                        ),
+                    )
+                    .to_string(),
+                ));
+            }
+            continue;
+        };
+
+        let conversion = conversion(&unit, &target).map_err(|_| {
+            UdfError::ParameterValidation(
+                ValidationError::new(
+                    def.name(),
+                    format!("Value in {} cannot be converted to {}", unit, target),
+                )
+                .to_string(),
+            )
+        })?;
+        let value = match conversion {
+            Some(conversion) => {
+                let converted_value = conversion.apply(value);
+                conversions.push(ParameterConversion {
+                    parameter: def.name().to_string(),
+                    value,
+                    unit: conversion.from_unit,
+                    converted_value,
+                    converted_unit: conversion.to_unit,
+                });
+                converted_value
+            }
+            None => value,
+        };
+        converted.insert(def.name().to_string(), ParameterValue::Number(value));
+    }
+
+    Ok((converted, conversions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_support::curve;
+    use crate::compute::types::CurveDataType;
+
+    #[test]
+    fn test_find_aliases() {
+        assert_eq!(find("G/C3").unwrap().symbol, "g/cm³");
+        assert_eq!(find(" g/cc ").unwrap().symbol, "g/cm³");
+        assert_eq!(find("GAPI").unwrap().family, UnitFamily::Radioactivity);
+        assert_eq!(find("US/F").unwrap().symbol, "μs/ft");
+        assert_eq!(find("OHMM").unwrap().family, UnitFamily::Resistivity);
+        assert!(find("degC").is_none());
+
+        // Standard units of curve types are all known
+        for curve_type in CurveDataType::ALL {
+            let unit = curve_type.standard_unit();
+            assert!(unit.is_empty() || find(unit).is_some(), "{}", unit);
+        }
+    }
+
+    #[test]
+    fn test_conversion() {
+        let density = conversion("kg/m3", "g/cc").unwrap().unwrap();
+        assert_eq!(density.from_unit, "kg/m³");
+        assert_eq!(density.to_unit, "g/cm³");
+        assert!((density.apply(2650.0) - 2.65).abs() < 1e-9);
+
+        let slowness = conversion("us/m", "us/ft").unwrap().unwrap();
+        assert!((slowness.apply(180.0) - 54.864).abs() < 1e-9);
+
+        let porosity = conversion("p.u.", "v/v").unwrap().unwrap();
+        assert!((porosity.apply(25.0) - 0.25).abs() < 1e-12);
+
+        // Spellings of one unit and unknown units need no conversion
+        assert!(conversion("G/C3", "g/cm³").unwrap().is_none());
+        assert!(conversion("", "g/cm³").unwrap().is_none());
+
+        assert!(matches!(
+            conversion("ohm-m", "g/cm³"),
+            Err(UdfError::UnitMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_units_of_family() {
+        assert_eq!(
+            units_of(UnitFamily::Length),
+            vec!["m", "ft", "in", "cm", "mm"]
+        );
+        assert_eq!(units_of(UnitFamily::Radioactivity), vec!["gAPI"]);
+    }
+
+    #[test]
+    fn test_apply_to_curve() {
+        let curve = curve("DT", "us/m", vec![100.0, 100.5], vec![Some(300.0), None]);
+        let conversion = conversion(&curve.unit, "μs/ft").unwrap().unwrap();
+        let converted = conversion.apply_to_curve(&curve);
+
+        assert_eq!(converted.unit, "μs/ft");
+        assert!((converted.values[0].unwrap() - 91.44).abs() < 1e-9);
+        assert_eq!(converted.values[1], None);
+        assert_eq!(converted.parquet_hash, curve.parquet_hash);
+    }
+}
@@ -0,0 +1,549 @@
+//! Reproducibility verification of executions.
+//!
+//! UDFs are deterministic and output blobs are content-addressed, so an
+//! execution is reproducible if re-running its UDF on the exact input data
+//! it recorded yields outputs with the recorded content hashes. Inputs are
+//! loaded by their recorded parquet hash rather than their current data,
+//! and the result cache is bypassed. When a hash differs, the recorded
+//! output is compared with the recomputed one sample by sample.
+
+use crate::compute::engine::{
+    CurveLoader, CurveMetadataInfo, ExecutionEngine, ExecutionOptions, WellMarker,
+};
+use crate::compute::error::UdfError;
+use crate::compute::output_writer::content_hash;
+use crate::compute::parameters::ParameterValue;
+use crate::compute::staleness::SourceExecution;
+use crate::compute::types::{CurveData, OutputCurveData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Outcome of re-running an execution on its recorded inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub execution_id: Uuid,
+    pub udf_id: String,
+    /// UDF version recorded by the execution
+    pub recorded_udf_version: String,
+    /// UDF version that was re-run
+    pub current_udf_version: String,
+    /// True if every recorded output was reproduced exactly
+    pub reproducible: bool,
+    pub outputs: Vec<OutputVerification>,
+    /// Why the re-run failed, if it did
+    pub error: Option<String>,
+}
+
+/// Comparison of one recorded output with its recomputed counterpart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputVerification {
+    pub name: String,
+    pub recorded_hash: String,
+    /// Content hash of the recomputed output (None if it was not produced)
+    pub recomputed_hash: Option<String>,
+    pub matches: bool,
+    /// Sample comparison, when the hashes differ
+    pub diff: Option<SampleDiff>,
+    /// Why the samples could not be compared
+    pub error: Option<String>,
+}
+
+/// Per-sample summary of how a recomputed output differs from the recorded one.
+///
+/// Samples are compared by index, up to the length of the shorter curve.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampleDiff {
+    pub recorded_samples: usize,
+    pub recomputed_samples: usize,
+    pub compared_samples: usize,
+    /// Compared samples at different depths
+    pub depth_mismatches: usize,
+    /// Compared samples whose values differ, including null mismatches
+    pub differing_samples: usize,
+    /// Compared samples that are null in exactly one of the curves
+    pub null_mismatches: usize,
+    /// Largest and mean absolute difference of samples non-null in both
+    pub max_abs_difference: Option<f64>,
+    pub mean_abs_difference: Option<f64>,
+    /// Recorded depth of the first differing sample
+    pub first_difference_depth: Option<f64>,
+}
+
+/// Curve loader serving the recorded version of an execution's inputs.
+///
+/// Curves named in the recorded inputs are loaded with the data they had at
+/// execution time; other curves load as usual.
+pub struct RecordedInputLoader<'a> {
+    inner: &'a dyn CurveLoader,
+    /// Recorded parquet hash by curve ID
+    hashes: HashMap<Uuid, String>,
+}
+
+impl<'a> RecordedInputLoader<'a> {
+    pub fn new(inner: &'a dyn CurveLoader, source: &SourceExecution) -> Self {
+        Self {
+            inner,
+            hashes: source
+                .inputs
+                .iter()
+                .map(|input| (input.curve_id, input.parquet_hash.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl CurveLoader for RecordedInputLoader<'_> {
+    fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+        match self.hashes.get(&curve_id) {
+            Some(hash) => self.inner.load_curve_version(curve_id, hash),
+            None => self.inner.load_curve(curve_id),
+        }
+    }
+
+    fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError> {
+        self.inner.load_curve_metadata(curve_id)
+    }
+
+    fn list_well_curves(&self, well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError> {
+        self.inner.list_well_curves(well_id)
+    }
+
+    fn list_wells(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        self.inner.list_wells(workspace_id)
+    }
+
+    fn list_well_markers(&self, well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+        self.inner.list_well_markers(well_id)
+    }
+
+    fn well_depth_unit(&self, well_id: Uuid) -> Result<Option<String>, UdfError> {
+        self.inner.well_depth_unit(well_id)
+    }
+}
+
+/// A saved output of the recorded execution.
+struct RecordedOutput {
+    /// Output name (None for records from before multi-output support,
+    /// which only saved the first output)
+    name: Option<String>,
+    curve_id: Option<Uuid>,
+    parquet_hash: String,
+}
+
+/// Re-run an execution on its recorded inputs and compare the outputs.
+///
+/// `parameters` are the recorded parameters. Only outputs that were saved
+/// have a recorded hash, so executions that saved nothing cannot be
+/// verified.
+pub fn verify_execution(
+    engine: &ExecutionEngine,
+    source: &SourceExecution,
+    well_id: Uuid,
+    workspace_id: Uuid,
+    parameters: HashMap<String, ParameterValue>,
+    loader: &dyn CurveLoader,
+) -> Result<VerificationReport, UdfError> {
+    let recorded: Vec<RecordedOutput> = if !source.outputs.is_empty() {
+        source
+            .outputs
+            .iter()
+            .map(|output| RecordedOutput {
+                name: Some(output.name.clone()),
+                curve_id: Some(output.curve_id),
+                parquet_hash: output.parquet_hash.clone(),
+            })
+            .collect()
+    } else if let Some(hash) = &source.output_parquet_hash {
+        vec![RecordedOutput {
+            name: None,
+            curve_id: source.output_curve_id,
+            parquet_hash: hash.clone(),
+        }]
+    } else {
+        return Err(UdfError::IncompatibleData(format!(
+            "Execution {} has no saved output to verify",
+            source.execution_id
+        )));
+    };
+
+    // Without a result cache the UDF always runs. Interval parameters keep
+    // the depths they were recorded with, wherever the well's tops are now.
+    let result = engine.execute(
+        &source.udf_id,
+        well_id,
+        workspace_id,
+        parameters,
+        &RecordedInputLoader::new(loader, source),
+        &ExecutionOptions::new().with_depth_intervals(source.depth_intervals.clone()),
+    )?;
+
+    let outputs: Vec<OutputVerification> = recorded
+        .iter()
+        .map(|recorded| {
+            let recomputed = result
+                .output
+                .as_ref()
+                .and_then(|output| match &recorded.name {
+                    Some(name) => output.outputs.iter().find(|o| &o.name == name),
+                    None => output.primary(),
+                });
+            let name = recorded
+                .name
+                .clone()
+                .or_else(|| recomputed.map(|o| o.name.clone()))
+                .unwrap_or_default();
+            verify_output(name, recorded, recomputed.map(|o| &o.curve_data), loader)
+        })
+        .collect();
+
+    Ok(VerificationReport {
+        execution_id: source.execution_id,
+        udf_id: source.udf_id.clone(),
+        recorded_udf_version: source.udf_version.clone(),
+        current_udf_version: result.record.udf_version,
+        reproducible: result.output.is_some() && outputs.iter().all(|o| o.matches),
+        outputs,
+        error: result.record.error_message,
+    })
+}
+
+/// Compare one recorded output, loading the recorded data if hashes differ.
+fn verify_output(
+    name: String,
+    recorded: &RecordedOutput,
+    recomputed: Option<&OutputCurveData>,
+    loader: &dyn CurveLoader,
+) -> OutputVerification {
+    let mut verification = OutputVerification {
+        name,
+        recorded_hash: recorded.parquet_hash.clone(),
+        recomputed_hash: None,
+        matches: false,
+        diff: None,
+        error: None,
+    };
+
+    let Some(recomputed) = recomputed else {
+        verification.error = Some("Output was not produced".to_string());
+        return verification;
+    };
+
+    let hash = match content_hash(recomputed) {
+        Ok(hash) => hash,
+        Err(e) => {
+            verification.error = Some(e.to_string());
+            return verification;
+        }
+    };
+    verification.matches = hash == recorded.parquet_hash;
+    verification.recomputed_hash = Some(hash);
+    if verification.matches {
+        return verification;
+    }
+
+    let recorded_data = recorded
+        .curve_id
+        .ok_or_else(|| UdfError::CurveLoadError("Output curve was not recorded".to_string()))
+        .and_then(|curve_id| loader.load_curve_version(curve_id, &recorded.parquet_hash));
+    match recorded_data {
+        Ok(curve) => verification.diff = Some(diff_samples(&curve, recomputed)),
+        Err(e) => verification.error = Some(format!("Recorded output unavailable: {}", e)),
+    }
+    verification
+}
+
+/// Summarize the per-sample differences between two versions of a curve.
+pub fn diff_samples(recorded: &CurveData, recomputed: &OutputCurveData) -> SampleDiff {
+    let mut diff = SampleDiff {
+        recorded_samples: recorded.values.len(),
+        recomputed_samples: recomputed.values.len(),
+        ..SampleDiff::default()
+    };
+
+    let mut abs_sum = 0.0;
+    let mut abs_count = 0;
+    let samples = recorded
+        .depths
+        .iter()
+        .zip(&recorded.values)
+        .zip(recomputed.depths.iter().zip(&recomputed.values));
+
+    for ((depth, old), (new_depth, new)) in samples {
+        diff.compared_samples += 1;
+        if depth != new_depth {
+            diff.depth_mismatches += 1;
+        }
+
+        let differs = match (old, new) {
+            (Some(old), Some(new)) => {
+                let abs = (old - new).abs();
+                abs_sum += abs;
+                abs_count += 1;
+                diff.max_abs_difference = Some(diff.max_abs_difference.map_or(abs, |m| m.max(abs)));
+                // Compare bits so NaN samples that are unchanged do not count
+                old.to_bits() != new.to_bits()
+            }
+            (None, None) => false,
+            _ => {
+                diff.null_mismatches += 1;
+                true
+            }
+        };
+
+        if differs {
+            diff.differing_samples += 1;
+            diff.first_difference_depth.get_or_insert(*depth);
+        }
+    }
+
+    if abs_count > 0 {
+        diff.mean_abs_difference = Some(abs_sum / abs_count as f64);
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_support::{create_engine, curve};
+    use crate::compute::types::{CurveDataType, InputReference, OutputReference};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+
+    /// Loader holding every stored version of each curve.
+    struct VersionedLoader {
+        versions: Vec<Arc<CurveData>>,
+        depth_unit: Option<String>,
+    }
+
+    impl CurveLoader for VersionedLoader {
+        fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+            // The last version is the current one
+            self.versions
+                .iter()
+                .rev()
+                .find(|c| c.curve_id == curve_id)
+                .cloned()
+                .ok_or_else(|| UdfError::CurveLoadError(format!("Curve not found: {}", curve_id)))
+        }
+
+        fn load_curve_version(
+            &self,
+            curve_id: Uuid,
+            parquet_hash: &str,
+        ) -> Result<Arc<CurveData>, UdfError> {
+            self.versions
+                .iter()
+                .find(|c| c.curve_id == curve_id && c.parquet_hash == parquet_hash)
+                .cloned()
+                .ok_or_else(|| UdfError::CurveLoadError(format!("No data {}", parquet_hash)))
+        }
+
+        fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError> {
+            Ok(CurveMetadataInfo::from_curve(&self.load_curve(curve_id)?))
+        }
+
+        fn list_well_curves(&self, _well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError> {
+            Ok(Vec::new())
+        }
+
+        fn list_wells(&self, _workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+            Ok(Vec::new())
+        }
+
+        fn well_depth_unit(&self, _well_id: Uuid) -> Result<Option<String>, UdfError> {
+            Ok(self.depth_unit.clone())
+        }
+    }
+
+    /// A stored version of a curve.
+    fn stored(
+        curve_id: Uuid,
+        mnemonic: &str,
+        hash: &str,
+        values: Vec<Option<f64>>,
+    ) -> Arc<CurveData> {
+        Arc::new(CurveData {
+            curve_id,
+            parquet_hash: hash.to_string(),
+            ..curve(mnemonic, "gAPI", vec![100.0, 101.0, 102.0], values)
+        })
+    }
+
+    fn output(values: Vec<Option<f64>>) -> OutputCurveData {
+        OutputCurveData {
+            mnemonic: "VSH_LIN".to_string(),
+            curve_type: CurveDataType::Computed,
+            unit: "v/v".to_string(),
+            depths: vec![100.0, 101.0, 102.0],
+            values,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_samples() {
+        let recorded = stored(
+            Uuid::new_v4(),
+            "VSH_LIN",
+            "a",
+            vec![Some(0.5), None, Some(0.25)],
+        );
+        let diff = diff_samples(&recorded, &output(vec![Some(0.5), Some(0.1), Some(0.75)]));
+
+        assert_eq!(diff.compared_samples, 3);
+        assert_eq!(diff.depth_mismatches, 0);
+        assert_eq!(diff.differing_samples, 2);
+        assert_eq!(diff.null_mismatches, 1);
+        assert_eq!(diff.max_abs_difference, Some(0.5));
+        assert_eq!(diff.mean_abs_difference, Some(0.25));
+        assert_eq!(diff.first_difference_depth, Some(101.0));
+    }
+
+    #[test]
+    fn test_verify_uses_recorded_inputs() {
+        let engine = create_engine();
+
+        let gr_id = Uuid::new_v4();
+        let vsh_id = Uuid::new_v4();
+        let recorded_gr = stored(
+            gr_id,
+            "GR",
+            "gr_v1",
+            vec![Some(30.0), Some(65.0), Some(100.0)],
+        );
+        let vsh = output(vec![Some(0.0), Some(0.5), Some(1.0)]);
+        let vsh_hash = content_hash(&vsh).unwrap();
+
+        let mut parameters = HashMap::new();
+        parameters.insert("gr_curve".to_string(), ParameterValue::Curve(gr_id));
+        parameters.insert("gr_min".to_string(), ParameterValue::Number(30.0));
+        parameters.insert("gr_max".to_string(), ParameterValue::Number(100.0));
+
+        let source = |output_hash: &str| SourceExecution {
+            execution_id: Uuid::new_v4(),
+            udf_id: "petro:vshale_linear".to_string(),
+            udf_version: "1.0.0".to_string(),
+            parameters: serde_json::to_value(&parameters).unwrap(),
+            depth_intervals: BTreeMap::new(),
+            inputs: vec![InputReference {
+                curve_id: gr_id,
+                version: 1,
+                parquet_hash: "gr_v1".to_string(),
+                unit_conversion: None,
+                alignment: None,
+            }],
+            outputs: vec![OutputReference {
+                name: "vshale".to_string(),
+                curve_id: vsh_id,
+                mnemonic: "VSH_LIN".to_string(),
+                parquet_hash: output_hash.to_string(),
+            }],
+            output_curve_id: Some(vsh_id),
+            output_parquet_hash: Some(output_hash.to_string()),
+            started_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            compute_app_version: "test".to_string(),
+        };
+
+        // GR was edited after the execution; verification uses the recorded data
+        let loader = VersionedLoader {
+            versions: vec![
+                recorded_gr,
+                stored(
+                    gr_id,
+                    "GR",
+                    "gr_v2",
+                    vec![Some(40.0), Some(40.0), Some(40.0)],
+                ),
+                stored(
+                    vsh_id,
+                    "VSH_LIN",
+                    "vsh_v0",
+                    vec![Some(0.0), Some(0.5), Some(0.9)],
+                ),
+            ],
+            depth_unit: None,
+        };
+        let verify = |source: &SourceExecution| {
+            let parameters = parameters.clone();
+            verify_execution(
+                &engine,
+                source,
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                parameters,
+                &loader,
+            )
+            .unwrap()
+        };
+
+        let report = verify(&source(&vsh_hash));
+        assert!(report.reproducible, "{:?}", report);
+        assert_eq!(
+            report.outputs[0].recomputed_hash.as_deref(),
+            Some(vsh_hash.as_str())
+        );
+        assert!(report.outputs[0].diff.is_none());
+
+        // A record whose output does not match reports where it differs
+        let report = verify(&source("vsh_v0"));
+        assert!(!report.reproducible);
+        let diff = report.outputs[0].diff.as_ref().unwrap();
+        assert_eq!(diff.differing_samples, 1);
+        assert_eq!(diff.first_difference_depth, Some(102.0));
+    }
+
+    #[test]
+    fn test_verify_converts_depth_parameters() {
+        let engine = create_engine();
+        let gr_id = Uuid::new_v4();
+        let loader = VersionedLoader {
+            versions: vec![stored(
+                gr_id,
+                "GR",
+                "gr_v1",
+                vec![Some(30.0), Some(65.0), Some(100.0)],
+            )],
+            depth_unit: Some("m".to_string()),
+        };
+
+        // A step of 0.5 m, entered in centimetres
+        let mut parameters = HashMap::new();
+        parameters.insert("input_curve".to_string(), ParameterValue::Curve(gr_id));
+        parameters.insert(
+            "new_step".to_string(),
+            ParameterValue::Quantity {
+                value: 50.0,
+                unit: "cm".to_string(),
+            },
+        );
+        let (well_id, workspace_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let result = engine
+            .execute(
+                "core:depth_resample",
+                well_id,
+                workspace_id,
+                parameters.clone(),
+                &loader,
+                &ExecutionOptions::new(),
+            )
+            .unwrap();
+        assert_eq!(result.record.parameter_conversions.len(), 1);
+
+        let resampled = result.output.as_ref().unwrap().primary().unwrap();
+        let mut source = SourceExecution::from_record(&result.record);
+        source.outputs = vec![OutputReference {
+            name: resampled.name.clone(),
+            curve_id: Uuid::new_v4(),
+            mnemonic: resampled.curve_data.mnemonic.clone(),
+            parquet_hash: content_hash(&resampled.curve_data).unwrap(),
+        }];
+
+        let report =
+            verify_execution(&engine, &source, well_id, workspace_id, parameters, &loader).unwrap();
+        assert!(report.reproducible, "{:?}", report);
+    }
+}
@@ -0,0 +1,700 @@
+//! Workflows: DAGs of UDF nodes run as one unit.
+//!
+//! A workflow chains UDFs by feeding the named output of one node into a
+//! curve parameter of another, e.g. Depth Resample → Moving Average →
+//! VShale Linear. Nodes run in topological order against a single well.
+//! Intermediate outputs stay in memory and are served to downstream nodes
+//! as if they were stored curves, so nothing has to be saved between steps.
+//!
+//! Every node gets its own execution record, tagged with a `WorkflowStep`
+//! naming the workflow run and the upstream outputs it consumed.
+
+use crate::compute::engine::{
+    CurveLoader, CurveMetadataInfo, ExecutionEngine, ExecutionOptions, ExecutionResult, WellMarker,
+};
+use crate::compute::error::UdfError;
+use crate::compute::output_writer::content_hash;
+use crate::compute::parameters::ParameterValue;
+use crate::compute::types::{
+    CurveData, ExecutionRecord, ExecutionStatus, OutputCurveData, UpstreamInput, WorkflowStep,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// A reference to a named output of an upstream node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeOutputRef {
+    /// Upstream node ID
+    pub node_id: String,
+    /// Output name as declared by the upstream UDF
+    pub output: String,
+}
+
+/// One UDF invocation within a workflow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowNode {
+    /// Node ID, unique within the workflow (e.g., "smooth")
+    pub id: String,
+    /// Full UDF identifier (provider:udf_id)
+    pub udf_id: String,
+    /// Fixed parameter values, including curve parameters bound to stored curves
+    #[serde(default)]
+    pub parameters: HashMap<String, ParameterValue>,
+    /// Curve parameters fed from upstream node outputs, by parameter name
+    #[serde(default)]
+    pub inputs: HashMap<String, NodeOutputRef>,
+}
+
+/// A declarative workflow definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    /// Workflow ID
+    pub id: Uuid,
+    /// Human-readable name
+    pub name: String,
+    /// Description of what the workflow computes
+    #[serde(default)]
+    pub description: String,
+    /// Nodes of the DAG
+    pub nodes: Vec<WorkflowNode>,
+    /// Creation time
+    pub created_at: DateTime<Utc>,
+    /// Last modification time
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WorkflowDefinition {
+    /// Get a node by ID
+    pub fn node(&self, node_id: &str) -> Option<&WorkflowNode> {
+        self.nodes.iter().find(|n| n.id == node_id)
+    }
+}
+
+/// Outcome of one node of a workflow run.
+#[derive(Debug)]
+pub struct NodeRun {
+    /// Node ID within the workflow
+    pub node_id: String,
+    /// Status of this node's run
+    pub status: ExecutionStatus,
+    /// Execution result, if the UDF ran
+    pub result: Option<ExecutionResult>,
+    /// In-memory curve ID of each output, by output name
+    pub output_curves: HashMap<String, Uuid>,
+    /// Error message if the node failed or was cancelled
+    pub error: Option<String>,
+}
+
+impl NodeRun {
+    fn not_run(node_id: &str, status: ExecutionStatus, error: String) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            status,
+            result: None,
+            output_curves: HashMap::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Outcome of a workflow run, one entry per node in run order.
+#[derive(Debug)]
+pub struct WorkflowResult {
+    /// ID shared by the execution records of this run
+    pub run_id: Uuid,
+    pub nodes: Vec<NodeRun>,
+}
+
+impl WorkflowResult {
+    /// Number of nodes with the given status
+    pub fn count(&self, status: ExecutionStatus) -> usize {
+        self.nodes.iter().filter(|n| n.status == status).count()
+    }
+}
+
+impl ExecutionEngine {
+    /// Validate a workflow and return its node indices in run order.
+    ///
+    /// Checks that node IDs are unique, every UDF exists, every input
+    /// points at a declared output of an existing node and feeds a curve
+    /// parameter, and that the graph has no cycles. Nodes with no ordering
+    /// constraint between them keep their definition order.
+    pub fn validate_workflow(&self, workflow: &WorkflowDefinition) -> Result<Vec<usize>, UdfError> {
+        if workflow.nodes.is_empty() {
+            return Err(UdfError::InvalidWorkflow(
+                "Workflow has no nodes".to_string(),
+            ));
+        }
+
+        let mut index_of = HashMap::new();
+        for (index, node) in workflow.nodes.iter().enumerate() {
+            if index_of.insert(node.id.as_str(), index).is_some() {
+                return Err(UdfError::InvalidWorkflow(format!(
+                    "Duplicate node ID '{}'",
+                    node.id
+                )));
+            }
+        }
+
+        let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); workflow.nodes.len()];
+        for (index, node) in workflow.nodes.iter().enumerate() {
+            let curve_params: HashSet<String> = self
+                .get_parameter_definitions(&node.udf_id)?
+                .iter()
+                .filter(|d| d["type"] == "curve")
+                .filter_map(|d| d["name"].as_str().map(str::to_string))
+                .collect();
+
+            for (parameter, source) in &node.inputs {
+                if !curve_params.contains(parameter) {
+                    return Err(UdfError::InvalidWorkflow(format!(
+                        "Node '{}': '{}' is not a curve parameter of {}",
+                        node.id, parameter, node.udf_id
+                    )));
+                }
+                if node.parameters.contains_key(parameter) {
+                    return Err(UdfError::InvalidWorkflow(format!(
+                        "Node '{}': '{}' has both a value and an upstream input",
+                        node.id, parameter
+                    )));
+                }
+
+                let upstream = *index_of.get(source.node_id.as_str()).ok_or_else(|| {
+                    UdfError::InvalidWorkflow(format!(
+                        "Node '{}' reads from unknown node '{}'",
+                        node.id, source.node_id
+                    ))
+                })?;
+
+                let declared = self
+                    .get_udf_metadata(&workflow.nodes[upstream].udf_id)?
+                    .outputs;
+                if !declared.is_empty() && !declared.iter().any(|d| d.name == source.output) {
+                    return Err(UdfError::InvalidWorkflow(format!(
+                        "Node '{}' has no output '{}'",
+                        source.node_id, source.output
+                    )));
+                }
+
+                dependencies[index].insert(upstream);
+            }
+        }
+
+        // Kahn's algorithm, always taking the first ready node in
+        // definition order so the run order is deterministic
+        let mut order = Vec::with_capacity(workflow.nodes.len());
+        let mut done = vec![false; workflow.nodes.len()];
+        while order.len() < workflow.nodes.len() {
+            let next = (0..workflow.nodes.len())
+                .find(|&i| !done[i] && dependencies[i].iter().all(|&d| done[d]));
+
+            match next {
+                Some(i) => {
+                    done[i] = true;
+                    order.push(i);
+                }
+                None => {
+                    let cyclic: Vec<&str> = (0..workflow.nodes.len())
+                        .filter(|&i| !done[i])
+                        .map(|i| workflow.nodes[i].id.as_str())
+                        .collect();
+                    return Err(UdfError::InvalidWorkflow(format!(
+                        "Cycle between nodes: {}",
+                        cyclic.join(", ")
+                    )));
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Execute a workflow against one well.
+    ///
+    /// Nodes run in topological order. Outputs are kept in memory and bound
+    /// to downstream curve parameters; a node whose upstream did not
+    /// complete is recorded as failed without running. Each executed node's
+    /// record carries a `WorkflowStep` for provenance.
+    ///
+    /// The run is recorded under `options.execution_id` when set, so callers
+    /// can hand out the run ID before it starts. `on_node` is called as each
+    /// node finishes, so callers can persist or report results while the
+    /// rest of the workflow runs.
+    pub fn execute_workflow(
+        &self,
+        workflow: &WorkflowDefinition,
+        well_id: Uuid,
+        workspace_id: Uuid,
+        curve_loader: &dyn CurveLoader,
+        options: &ExecutionOptions,
+        mut on_node: impl FnMut(&mut NodeRun),
+    ) -> Result<WorkflowResult, UdfError> {
+        let order = self.validate_workflow(workflow)?;
+
+        let run_id = options.execution_id.unwrap_or_else(Uuid::new_v4);
+        let total = order.len();
+        let mut loader = WorkflowCurveLoader::new(curve_loader);
+        let mut result = WorkflowResult {
+            run_id,
+            nodes: Vec::with_capacity(total),
+        };
+
+        for (step, index) in order.into_iter().enumerate() {
+            let node = &workflow.nodes[index];
+
+            let mut run = if options.cancellation_token.is_cancelled() {
+                NodeRun::not_run(
+                    &node.id,
+                    ExecutionStatus::Cancelled,
+                    "Workflow cancelled before this node ran".to_string(),
+                )
+            } else {
+                options.progress_state.set_progress_with_message(
+                    step as f64 / total as f64 * 100.0,
+                    format!("Node '{}' ({} of {})", node.id, step + 1, total),
+                );
+                self.run_node(
+                    workflow,
+                    node,
+                    run_id,
+                    well_id,
+                    workspace_id,
+                    &mut loader,
+                    &result.nodes,
+                    options,
+                    step,
+                    total,
+                )
+            };
+
+            on_node(&mut run);
+            result.nodes.push(run);
+        }
+
+        options
+            .progress_state
+            .set_progress_with_message(100.0, "Workflow finished");
+
+        Ok(result)
+    }
+
+    /// Bind upstream outputs and run one node of a workflow.
+    #[allow(clippy::too_many_arguments)]
+    fn run_node(
+        &self,
+        workflow: &WorkflowDefinition,
+        node: &WorkflowNode,
+        run_id: Uuid,
+        well_id: Uuid,
+        workspace_id: Uuid,
+        loader: &mut WorkflowCurveLoader<'_>,
+        finished: &[NodeRun],
+        options: &ExecutionOptions,
+        step: usize,
+        total: usize,
+    ) -> NodeRun {
+        let mut parameters = node.parameters.clone();
+        let mut upstream = Vec::with_capacity(node.inputs.len());
+
+        // Sorted for a deterministic provenance order
+        let inputs: BTreeMap<&String, &NodeOutputRef> = node.inputs.iter().collect();
+        for (parameter, source) in inputs {
+            let curve_id = finished
+                .iter()
+                .find(|n| n.node_id == source.node_id)
+                .filter(|n| n.status == ExecutionStatus::Completed)
+                .and_then(|n| n.output_curves.get(&source.output));
+
+            let Some(&curve_id) = curve_id else {
+                return NodeRun::not_run(
+                    &node.id,
+                    ExecutionStatus::Failed,
+                    format!(
+                        "Upstream output '{}.{}' is not available",
+                        source.node_id, source.output
+                    ),
+                );
+            };
+
+            parameters.insert(parameter.clone(), ParameterValue::Curve(curve_id));
+            upstream.push(UpstreamInput {
+                parameter: parameter.clone(),
+                node_id: source.node_id.clone(),
+                output: source.output.clone(),
+                curve_id,
+            });
+        }
+
+        let node_options = options.for_step(step, total);
+
+        let mut result = match self.execute(
+            &node.udf_id,
+            well_id,
+            workspace_id,
+            parameters,
+            &*loader,
+            &node_options,
+        ) {
+            Ok(result) => result,
+            Err(e) => return NodeRun::not_run(&node.id, ExecutionStatus::Failed, e.to_string()),
+        };
+
+        result.record.workflow = Some(WorkflowStep {
+            workflow_id: workflow.id,
+            run_id,
+            node_id: node.id.clone(),
+            upstream,
+        });
+
+        let mut output_curves = HashMap::new();
+        if let Some(output) = &result.output {
+            for named in &output.outputs {
+                match loader.add_output(&named.curve_data) {
+                    Ok(curve_id) => {
+                        output_curves.insert(named.name.clone(), curve_id);
+                    }
+                    Err(e) => {
+                        return NodeRun::not_run(&node.id, ExecutionStatus::Failed, e.to_string())
+                    }
+                }
+            }
+        }
+
+        NodeRun {
+            node_id: node.id.clone(),
+            status: result.record.status,
+            error: result.record.error_message.clone(),
+            result: Some(result),
+            output_curves,
+        }
+    }
+}
+
+/// Point a record's inputs at saved curves instead of in-memory outputs.
+///
+/// When a workflow's outputs are saved, downstream records still refer to
+/// the in-memory curve IDs they ran against; `saved` maps those IDs to the
+/// IDs the curves were saved under. Curve parameter values are remapped too,
+/// so the recorded execution can be re-run against the saved curves.
+pub fn remap_saved_inputs(record: &mut ExecutionRecord, saved: &HashMap<Uuid, Uuid>) {
+    for input in &mut record.inputs {
+        if let Some(curve_id) = saved.get(&input.curve_id) {
+            input.curve_id = *curve_id;
+        }
+    }
+    if let Some(parameters) = record.parameters.as_object_mut() {
+        for value in parameters.values_mut() {
+            let saved_id = value
+                .as_str()
+                .and_then(|s| Uuid::parse_str(s).ok())
+                .and_then(|id| saved.get(&id));
+            if let Some(curve_id) = saved_id {
+                *value = serde_json::Value::String(curve_id.to_string());
+            }
+        }
+    }
+    if let Some(step) = &mut record.workflow {
+        for input in &mut step.upstream {
+            if let Some(curve_id) = saved.get(&input.curve_id) {
+                input.curve_id = *curve_id;
+            }
+        }
+    }
+}
+
+/// Curve loader that serves in-memory workflow outputs on top of stored curves.
+struct WorkflowCurveLoader<'a> {
+    base: &'a dyn CurveLoader,
+    outputs: HashMap<Uuid, Arc<CurveData>>,
+}
+
+impl<'a> WorkflowCurveLoader<'a> {
+    fn new(base: &'a dyn CurveLoader) -> Self {
+        Self {
+            base,
+            outputs: HashMap::new(),
+        }
+    }
+
+    /// Keep an output in memory, returning the curve ID it is served under.
+    ///
+    /// The content hash is the one the output would be stored under if
+    /// saved, so downstream provenance stays valid after a save.
+    fn add_output(&mut self, output: &OutputCurveData) -> Result<Uuid, UdfError> {
+        let curve_id = Uuid::new_v4();
+        self.outputs.insert(
+            curve_id,
+            Arc::new(CurveData {
+                curve_id,
+                mnemonic: output.mnemonic.clone(),
+                curve_type: output.curve_type,
+                property_id: None,
+                unit: output.unit.clone(),
+                depths: Arc::new(output.depths.clone()),
+                values: output.values.clone(),
+                parquet_hash: content_hash(output)?,
+                version: 1,
+            }),
+        );
+        Ok(curve_id)
+    }
+}
+
+impl CurveLoader for WorkflowCurveLoader<'_> {
+    fn load_curve(&self, curve_id: Uuid) -> Result<Arc<CurveData>, UdfError> {
+        match self.outputs.get(&curve_id) {
+            Some(curve) => Ok(curve.clone()),
+            None => self.base.load_curve(curve_id),
+        }
+    }
+
+    fn load_curve_metadata(&self, curve_id: Uuid) -> Result<CurveMetadataInfo, UdfError> {
+        match self.outputs.get(&curve_id) {
+            Some(curve) => Ok(CurveMetadataInfo::from_curve(curve)),
+            None => self.base.load_curve_metadata(curve_id),
+        }
+    }
+
+    fn list_well_curves(&self, well_id: Uuid) -> Result<Vec<CurveMetadataInfo>, UdfError> {
+        self.base.list_well_curves(well_id)
+    }
+
+    fn list_wells(&self, workspace_id: Uuid) -> Result<Vec<Uuid>, UdfError> {
+        self.base.list_wells(workspace_id)
+    }
+
+    fn list_well_markers(&self, well_id: Uuid) -> Result<Vec<WellMarker>, UdfError> {
+        self.base.list_well_markers(well_id)
+    }
+
+    fn well_depth_unit(&self, well_id: Uuid) -> Result<Option<String>, UdfError> {
+        self.base.well_depth_unit(well_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::test_support::{create_engine, gr_curve, MockCurveLoader};
+
+    fn node(id: &str, udf_id: &str) -> WorkflowNode {
+        WorkflowNode {
+            id: id.to_string(),
+            udf_id: udf_id.to_string(),
+            parameters: HashMap::new(),
+            inputs: HashMap::new(),
+        }
+    }
+
+    fn from(node_id: &str, output: &str) -> NodeOutputRef {
+        NodeOutputRef {
+            node_id: node_id.to_string(),
+            output: output.to_string(),
+        }
+    }
+
+    /// Depth Resample → Moving Average → VShale Linear, listed out of order
+    fn create_chain(gr_id: Uuid) -> WorkflowDefinition {
+        let mut resample = node("resample", "core:depth_resample");
+        resample
+            .parameters
+            .insert("input_curve".to_string(), ParameterValue::Curve(gr_id));
+        resample
+            .parameters
+            .insert("new_step".to_string(), ParameterValue::Number(0.5));
+
+        let mut smooth = node("smooth", "core:moving_average");
+        smooth
+            .parameters
+            .insert("window_size".to_string(), ParameterValue::Number(3.0));
+        smooth
+            .inputs
+            .insert("input_curve".to_string(), from("resample", "resampled"));
+
+        let mut vshale = node("vshale", "petro:vshale_linear");
+        vshale
+            .parameters
+            .insert("gr_min".to_string(), ParameterValue::Number(30.0));
+        vshale
+            .parameters
+            .insert("gr_max".to_string(), ParameterValue::Number(100.0));
+        vshale
+            .inputs
+            .insert("gr_curve".to_string(), from("smooth", "smoothed"));
+
+        WorkflowDefinition {
+            id: Uuid::new_v4(),
+            name: "Smoothed VShale".to_string(),
+            description: String::new(),
+            nodes: vec![vshale, resample, smooth],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validate_orders_nodes_and_rejects_bad_graphs() {
+        let engine = create_engine();
+        let mut workflow = create_chain(Uuid::new_v4());
+        assert_eq!(engine.validate_workflow(&workflow).unwrap(), vec![1, 2, 0]);
+
+        // Cycle: resample reads from vshale
+        workflow.nodes[1].parameters.remove("input_curve");
+        workflow.nodes[1]
+            .inputs
+            .insert("input_curve".to_string(), from("vshale", "vshale"));
+        let err = engine.validate_workflow(&workflow).unwrap_err();
+        assert!(err.to_string().contains("Cycle"), "{}", err);
+
+        let mut unknown_output = create_chain(Uuid::new_v4());
+        unknown_output.nodes[2]
+            .inputs
+            .insert("input_curve".to_string(), from("resample", "nope"));
+        assert!(engine.validate_workflow(&unknown_output).is_err());
+
+        let mut not_a_curve = create_chain(Uuid::new_v4());
+        not_a_curve.nodes[2]
+            .inputs
+            .insert("window_size".to_string(), from("resample", "resampled"));
+        assert!(engine.validate_workflow(&not_a_curve).is_err());
+    }
+
+    #[test]
+    fn test_execute_chain_keeps_intermediates_in_memory() {
+        let engine = create_engine();
+        let gr = Arc::new(gr_curve());
+        let loader = MockCurveLoader::with_curves([gr.clone()]);
+        let workflow = create_chain(gr.curve_id);
+        let options = ExecutionOptions::new();
+
+        let mut reported = Vec::new();
+        let result = engine
+            .execute_workflow(
+                &workflow,
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                &loader,
+                &options,
+                |run| reported.push(run.node_id.clone()),
+            )
+            .unwrap();
+
+        assert_eq!(reported, vec!["resample", "smooth", "vshale"]);
+        assert_eq!(result.count(ExecutionStatus::Completed), 3);
+        assert_eq!(options.progress_state.get_progress(), 100);
+
+        let resample = &result.nodes[0];
+        let vshale = &result.nodes[2];
+        let vshale_record = &vshale.result.as_ref().unwrap().record;
+        let step = vshale_record.workflow.as_ref().unwrap();
+        assert_eq!(step.workflow_id, workflow.id);
+        assert_eq!(step.run_id, result.run_id);
+        assert_eq!(step.upstream[0].node_id, "smooth");
+        assert_eq!(vshale_record.inputs[0].curve_id, step.upstream[0].curve_id);
+
+        // Resampled at 0.5 over 100-101.5, so every downstream curve has 4 samples
+        let values = &vshale
+            .result
+            .as_ref()
+            .unwrap()
+            .output
+            .as_ref()
+            .unwrap()
+            .outputs[0]
+            .curve_data
+            .values;
+        assert_eq!(values.len(), 4);
+
+        // The first node read the stored curve, not an in-memory one
+        let resample_record = &resample.result.as_ref().unwrap().record;
+        assert_eq!(resample_record.inputs[0].curve_id, gr.curve_id);
+        assert!(resample_record
+            .workflow
+            .as_ref()
+            .unwrap()
+            .upstream
+            .is_empty());
+
+        // Saving the resampled curve rewrites the smoothing node's input
+        let saved_id = Uuid::new_v4();
+        let mut smooth_record = result.nodes[1].result.as_ref().unwrap().record.clone();
+        let in_memory = resample.output_curves["resampled"];
+        remap_saved_inputs(&mut smooth_record, &HashMap::from([(in_memory, saved_id)]));
+        assert_eq!(smooth_record.inputs[0].curve_id, saved_id);
+        assert_eq!(
+            smooth_record.parameters["input_curve"],
+            saved_id.to_string()
+        );
+        assert_eq!(
+            smooth_record.workflow.unwrap().upstream[0].curve_id,
+            saved_id
+        );
+    }
+
+    #[test]
+    fn test_depth_parameters_convert_to_the_well_depth_unit() {
+        let engine = create_engine();
+        let gr = Arc::new(gr_curve());
+        let loader = MockCurveLoader::with_curves([gr.clone()]).with_depth_unit("m");
+        let mut workflow = create_chain(gr.curve_id);
+        // 0.5 m entered in centimetres
+        workflow.nodes[1].parameters.insert(
+            "new_step".to_string(),
+            ParameterValue::Quantity {
+                value: 50.0,
+                unit: "cm".to_string(),
+            },
+        );
+
+        let result = engine
+            .execute_workflow(
+                &workflow,
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                &loader,
+                &ExecutionOptions::new(),
+                |_| {},
+            )
+            .unwrap();
+
+        assert_eq!(result.count(ExecutionStatus::Completed), 3);
+        let record = &result.nodes[0].result.as_ref().unwrap().record;
+        assert_eq!(record.parameter_conversions[0].converted_value, 0.5);
+        assert_eq!(record.parameter_conversions[0].converted_unit, "m");
+    }
+
+    #[test]
+    fn test_failed_node_stops_downstream() {
+        let engine = create_engine();
+        let gr = Arc::new(gr_curve());
+        let loader = MockCurveLoader::with_curves([gr.clone()]);
+        let mut workflow = create_chain(gr.curve_id);
+        // An even window fails Moving Average's own check
+        workflow.nodes[2]
+            .parameters
+            .insert("window_size".to_string(), ParameterValue::Number(4.0));
+
+        let result = engine
+            .execute_workflow(
+                &workflow,
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                &loader,
+                &ExecutionOptions::new(),
+                |_| {},
+            )
+            .unwrap();
+
+        assert_eq!(result.count(ExecutionStatus::Completed), 1);
+        assert_eq!(result.nodes[1].status, ExecutionStatus::Failed);
+        assert!(result.nodes[1].result.is_some());
+
+        let skipped = &result.nodes[2];
+        assert_eq!(skipped.status, ExecutionStatus::Failed);
+        assert!(skipped.result.is_none());
+        assert!(skipped.error.as_ref().unwrap().contains("smooth.smoothed"));
+    }
+}
@@ -26,6 +26,7 @@ pub fn run() {
             commands::list_wells,
             commands::list_curves,
             commands::list_all_curves_for_workspace,
+            commands::get_well_markers,
             commands::get_curve_data,
             commands::get_curve_data_segmented,  // OSDU-inspired segment-based access
             // Legacy computations (to be deprecated)
@@ -35,11 +36,33 @@ pub fn run() {
             commands::list_udfs,
             commands::get_udf_parameters,
             commands::execute_udf,
+            commands::execute_udf_batch,
             commands::validate_udf_parameters,
+            // Workflows
+            commands::save_workflow,
+            commands::list_workflows,
+            commands::get_workflow,
+            commands::delete_workflow,
+            commands::execute_workflow,
+            // Result cache
+            commands::clear_result_cache,
+            // Mnemonic aliases
+            commands::get_mnemonic_dictionary,
+            commands::save_mnemonic_alias,
+            commands::delete_mnemonic_alias,
             // Save output
             commands::save_output_curve,
             // Provenance
             commands::get_curve_provenance,
+            commands::get_curve_lineage,
+            commands::export_provenance,
+            commands::verify_execution,
+            // Stale derived curves
+            commands::check_stale_curves,
+            commands::recompute_derived_curve,
+            // Execution history
+            commands::list_execution_history,
+            commands::get_execution_record,
             // Progress and cancellation
             commands::get_execution_progress,
             commands::cancel_execution,
@@ -8,12 +8,19 @@
 //! - Chart layout persistence per workspace
 //! - User preferences
 //! - Local execution history
+//! - Workflow definitions
+//! - Cached execution results
+//! - Mnemonic alias overrides per workspace
 //!
 //! The database is stored at: `<app_data_dir>/compute_local.db`
 
-use chrono::Utc;
+use crate::compute::cache::CachedResult;
+use crate::compute::mnemonics::{MatchKind, MnemonicRule};
+use crate::compute::types::{ExecutionRecord, UdfOutput};
+use crate::compute::workflow::WorkflowDefinition;
+use chrono::{DateTime, SecondsFormat, Utc};
 use log::info;
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -40,6 +47,46 @@ pub struct ChartLayout {
     pub updated_at: String,
 }
 
+/// Filter for querying the local execution history.
+///
+/// All fields are optional; unset fields do not constrain the query.
+/// Date bounds are RFC 3339 timestamps and are inclusive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionHistoryFilter {
+    pub udf_id: Option<String>,
+    pub well_id: Option<String>,
+    pub workspace_id: Option<String>,
+    pub status: Option<String>,
+    pub started_after: Option<String>,
+    pub started_before: Option<String>,
+    /// Maximum number of records to return (defaults to 50)
+    pub limit: Option<u32>,
+    /// Number of records to skip, for paging
+    pub offset: Option<u32>,
+}
+
+/// One page of execution history, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionHistoryPage {
+    pub records: Vec<ExecutionRecord>,
+    /// Total number of records matching the filter (ignoring paging)
+    pub total: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// Default page size for execution history queries
+const DEFAULT_HISTORY_PAGE_SIZE: u32 = 50;
+
+/// Format a timestamp for the execution history table.
+///
+/// Fixed precision UTC keeps lexical order equal to chronological order,
+/// which the date range filters rely on.
+fn history_timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
 impl LocalDatabase {
     /// Create a new local database instance
     pub fn new(app_data_dir: &PathBuf) -> anyhow::Result<Self> {
@@ -115,6 +162,114 @@ impl LocalDatabase {
             info!("✅ Migration 1 applied successfully");
         }
 
+        // Migration 2: Create execution_history table
+        if current_version < 2 {
+            info!("🔄 Applying migration 2: Create execution_history table");
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS execution_history (
+                    id TEXT PRIMARY KEY,
+                    udf_id TEXT NOT NULL,
+                    udf_version TEXT NOT NULL,
+                    well_id TEXT NOT NULL,
+                    workspace_id TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    started_at TEXT NOT NULL,
+                    completed_at TEXT,
+                    output_curve_id TEXT,
+                    error_message TEXT,
+                    record_json TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_udf
+                ON execution_history(udf_id);
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_well
+                ON execution_history(well_id);
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_workspace
+                ON execution_history(workspace_id);
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_status
+                ON execution_history(status);
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_started_at
+                ON execution_history(started_at);
+
+                CREATE INDEX IF NOT EXISTS idx_execution_history_output_curve
+                ON execution_history(output_curve_id);
+
+                INSERT INTO schema_migrations (version, applied_at) VALUES (2, datetime('now'));
+                "#,
+            )?;
+            info!("✅ Migration 2 applied successfully");
+        }
+
+        // Migration 3: Create workflows table
+        if current_version < 3 {
+            info!("🔄 Applying migration 3: Create workflows table");
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS workflows (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    definition_json TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_workflows_name
+                ON workflows(name);
+
+                INSERT INTO schema_migrations (version, applied_at) VALUES (3, datetime('now'));
+                "#,
+            )?;
+            info!("✅ Migration 3 applied successfully");
+        }
+
+        // Migration 4: Create result_cache table
+        if current_version < 4 {
+            info!("🔄 Applying migration 4: Create result_cache table");
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS result_cache (
+                    cache_key TEXT PRIMARY KEY,
+                    execution_id TEXT NOT NULL,
+                    udf_id TEXT NOT NULL,
+                    output_json TEXT NOT NULL,
+                    created_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_result_cache_udf
+                ON result_cache(udf_id);
+
+                INSERT INTO schema_migrations (version, applied_at) VALUES (4, datetime('now'));
+                "#,
+            )?;
+            info!("✅ Migration 4 applied successfully");
+        }
+
+        // Migration 5: Create mnemonic_aliases table
+        if current_version < 5 {
+            info!("🔄 Applying migration 5: Create mnemonic_aliases table");
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS mnemonic_aliases (
+                    workspace_id TEXT NOT NULL,
+                    kind TEXT NOT NULL,
+                    pattern TEXT NOT NULL,
+                    rule_json TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    PRIMARY KEY (workspace_id, kind, pattern)
+                );
+
+                INSERT INTO schema_migrations (version, applied_at) VALUES (5, datetime('now'));
+                "#,
+            )?;
+            info!("✅ Migration 5 applied successfully");
+        }
+
         info!("✅ All migrations applied");
         Ok(())
     }
@@ -208,6 +363,358 @@ impl LocalDatabase {
 
         layouts.collect::<Result<Vec<_>, _>>().map_err(Into::into)
     }
+
+    /// Save or update an execution record in the local history.
+    ///
+    /// Records are keyed by execution ID, so saving again after the output
+    /// has been committed updates the existing entry.
+    pub fn save_execution_record(&self, record: &ExecutionRecord) -> anyhow::Result<()> {
+        let record_json = serde_json::to_string(record)?;
+
+        self.conn.execute(
+            r#"
+            INSERT INTO execution_history (
+                id, udf_id, udf_version, well_id, workspace_id, status,
+                started_at, completed_at, output_curve_id, error_message,
+                record_json, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT(id) DO UPDATE SET
+                status = excluded.status,
+                completed_at = excluded.completed_at,
+                output_curve_id = excluded.output_curve_id,
+                error_message = excluded.error_message,
+                record_json = excluded.record_json,
+                updated_at = excluded.updated_at
+            "#,
+            params![
+                record.id.to_string(),
+                record.udf_id,
+                record.udf_version,
+                record.well_id.to_string(),
+                record.workspace_id.to_string(),
+                record.status.as_str(),
+                history_timestamp(&record.started_at),
+                record.completed_at.as_ref().map(history_timestamp),
+                record.output_curve_id.map(|id| id.to_string()),
+                record.error_message,
+                record_json,
+                history_timestamp(&Utc::now()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get an execution record by execution ID
+    pub fn get_execution_record(
+        &self,
+        execution_id: &str,
+    ) -> anyhow::Result<Option<ExecutionRecord>> {
+        let record_json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT record_json FROM execution_history WHERE id = ?1",
+                params![execution_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        record_json
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Get the execution record that produced a saved output curve
+    pub fn get_execution_record_for_curve(
+        &self,
+        curve_id: &str,
+    ) -> anyhow::Result<Option<ExecutionRecord>> {
+        let record_json: Option<String> = self
+            .conn
+            .query_row(
+                r#"
+                SELECT record_json FROM execution_history
+                WHERE output_curve_id = ?1
+                   OR EXISTS (
+                       SELECT 1 FROM json_each(record_json, '$.outputs')
+                       WHERE json_extract(value, '$.curve_id') = ?1
+                   )
+                ORDER BY started_at DESC
+                LIMIT 1
+                "#,
+                params![curve_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        record_json
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Query the execution history, newest first
+    pub fn list_execution_history(
+        &self,
+        filter: &ExecutionHistoryFilter,
+    ) -> anyhow::Result<ExecutionHistoryPage> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+
+        if let Some(udf_id) = &filter.udf_id {
+            conditions.push("udf_id = ?");
+            values.push(udf_id.clone());
+        }
+        if let Some(well_id) = &filter.well_id {
+            conditions.push("well_id = ?");
+            values.push(well_id.clone());
+        }
+        if let Some(workspace_id) = &filter.workspace_id {
+            conditions.push("workspace_id = ?");
+            values.push(workspace_id.clone());
+        }
+        if let Some(status) = &filter.status {
+            let status = crate::compute::types::ExecutionStatus::parse(status)
+                .ok_or_else(|| anyhow::anyhow!("Unknown execution status: {}", status))?;
+            conditions.push("status = ?");
+            values.push(status.as_str().to_string());
+        }
+        if let Some(after) = &filter.started_after {
+            conditions.push("started_at >= ?");
+            values.push(parse_history_bound(after)?);
+        }
+        if let Some(before) = &filter.started_before {
+            conditions.push("started_at <= ?");
+            values.push(parse_history_bound(before)?);
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let total: i64 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM execution_history {}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        let limit = filter.limit.unwrap_or(DEFAULT_HISTORY_PAGE_SIZE);
+        let offset = filter.offset.unwrap_or(0);
+
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT record_json FROM execution_history
+            {}
+            ORDER BY started_at DESC, id
+            LIMIT {} OFFSET {}
+            "#,
+            where_clause, limit, offset
+        ))?;
+
+        let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+            row.get::<_, String>(0)
+        })?;
+
+        let mut records = Vec::new();
+        for json in rows {
+            records.push(serde_json::from_str(&json?)?);
+        }
+
+        Ok(ExecutionHistoryPage {
+            records,
+            total: total as u64,
+            limit,
+            offset,
+        })
+    }
+
+    /// Save or update a workflow definition
+    pub fn save_workflow(&self, workflow: &WorkflowDefinition) -> anyhow::Result<()> {
+        let definition_json = serde_json::to_string(workflow)?;
+
+        self.conn.execute(
+            r#"
+            INSERT INTO workflows (id, name, definition_json, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                definition_json = excluded.definition_json,
+                updated_at = excluded.updated_at
+            "#,
+            params![
+                workflow.id.to_string(),
+                workflow.name,
+                definition_json,
+                history_timestamp(&workflow.created_at),
+                history_timestamp(&workflow.updated_at),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get a workflow definition by ID
+    pub fn get_workflow(&self, workflow_id: &str) -> anyhow::Result<Option<WorkflowDefinition>> {
+        let definition_json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT definition_json FROM workflows WHERE id = ?1",
+                params![workflow_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        definition_json
+            .map(|json| serde_json::from_str(&json).map_err(Into::into))
+            .transpose()
+    }
+
+    /// List all workflow definitions, by name
+    pub fn list_workflows(&self) -> anyhow::Result<Vec<WorkflowDefinition>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT definition_json FROM workflows ORDER BY name, id")?;
+
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut workflows = Vec::new();
+        for json in rows {
+            workflows.push(serde_json::from_str(&json?)?);
+        }
+        Ok(workflows)
+    }
+
+    /// Delete a workflow definition
+    pub fn delete_workflow(&self, workflow_id: &str) -> anyhow::Result<bool> {
+        let rows_affected = self
+            .conn
+            .execute("DELETE FROM workflows WHERE id = ?1", params![workflow_id])?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Store the output of an execution under its cache key.
+    ///
+    /// A later execution with the same key replaces the entry.
+    pub fn save_cached_result(
+        &self,
+        cache_key: &str,
+        record: &ExecutionRecord,
+        output: &UdfOutput,
+    ) -> anyhow::Result<()> {
+        let output_json = serde_json::to_string(output)?;
+
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO result_cache (cache_key, execution_id, udf_id, output_json, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![
+                cache_key,
+                record.id.to_string(),
+                record.udf_id,
+                output_json,
+                history_timestamp(&Utc::now()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get the output stored under a cache key
+    pub fn get_cached_result(&self, cache_key: &str) -> anyhow::Result<Option<CachedResult>> {
+        let row: Option<(String, String)> = self
+            .conn
+            .query_row(
+                "SELECT execution_id, output_json FROM result_cache WHERE cache_key = ?1",
+                params![cache_key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let Some((execution_id, output_json)) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(CachedResult {
+            execution_id: Uuid::parse_str(&execution_id)?,
+            output: serde_json::from_str(&output_json)?,
+        }))
+    }
+
+    /// Remove all cached results, returning how many were removed
+    pub fn clear_result_cache(&self) -> anyhow::Result<usize> {
+        Ok(self.conn.execute("DELETE FROM result_cache", [])?)
+    }
+
+    /// Save or update a workspace's mnemonic alias rule.
+    ///
+    /// Rules are keyed by kind and pattern, so saving a rule for the same
+    /// pattern replaces it.
+    pub fn save_mnemonic_alias(
+        &self,
+        workspace_id: &str,
+        rule: &MnemonicRule,
+    ) -> anyhow::Result<()> {
+        let rule_json = serde_json::to_string(rule)?;
+
+        self.conn.execute(
+            r#"
+            INSERT INTO mnemonic_aliases (workspace_id, kind, pattern, rule_json, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(workspace_id, kind, pattern) DO UPDATE SET
+                rule_json = excluded.rule_json,
+                updated_at = excluded.updated_at
+            "#,
+            params![
+                workspace_id,
+                rule.kind.as_str(),
+                rule.pattern,
+                rule_json,
+                history_timestamp(&Utc::now()),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// List a workspace's mnemonic alias rules
+    pub fn list_mnemonic_aliases(&self, workspace_id: &str) -> anyhow::Result<Vec<MnemonicRule>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rule_json FROM mnemonic_aliases WHERE workspace_id = ?1 ORDER BY kind, pattern",
+        )?;
+
+        let rows = stmt.query_map(params![workspace_id], |row| row.get::<_, String>(0))?;
+
+        let mut rules = Vec::new();
+        for json in rows {
+            rules.push(serde_json::from_str(&json?)?);
+        }
+        Ok(rules)
+    }
+
+    /// Delete a workspace's mnemonic alias rule
+    pub fn delete_mnemonic_alias(
+        &self,
+        workspace_id: &str,
+        kind: MatchKind,
+        pattern: &str,
+    ) -> anyhow::Result<bool> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM mnemonic_aliases WHERE workspace_id = ?1 AND kind = ?2 AND pattern = ?3",
+            params![workspace_id, kind.as_str(), pattern],
+        )?;
+
+        Ok(rows_affected > 0)
+    }
+}
+
+/// Normalize a user-supplied RFC 3339 date bound to the stored format
+fn parse_history_bound(value: &str) -> anyhow::Result<String> {
+    let time = DateTime::parse_from_rfc3339(value)
+        .map_err(|e| anyhow::anyhow!("Invalid timestamp '{}': {}", value, e))?;
+    Ok(history_timestamp(&time.with_timezone(&Utc)))
 }
 
 /// Thread-safe wrapper for LocalDatabase
@@ -222,3 +729,285 @@ impl LocalDbState {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::types::{CurveDataType, ExecutionStatus, OutputCurveData, OutputReference};
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+
+    fn create_db() -> LocalDatabase {
+        let mut db = LocalDatabase {
+            conn: Connection::open_in_memory().unwrap(),
+            db_path: PathBuf::from(":memory:"),
+        };
+        db.migrate().unwrap();
+        db
+    }
+
+    fn create_record(
+        udf_id: &str,
+        well_id: Uuid,
+        status: ExecutionStatus,
+        hour: u32,
+    ) -> ExecutionRecord {
+        let started_at = Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+        ExecutionRecord {
+            id: Uuid::new_v4(),
+            udf_id: udf_id.to_string(),
+            udf_version: "1.0.0".to_string(),
+            well_id,
+            workspace_id: Uuid::nil(),
+            inputs: vec![],
+            parameters: serde_json::json!({}),
+            parameter_conversions: vec![],
+            depth_intervals: BTreeMap::new(),
+            output_curve_id: None,
+            output_parquet_hash: None,
+            outputs: vec![],
+            started_at,
+            completed_at: Some(started_at),
+            compute_app_version: "0.1.0".to_string(),
+            status,
+            error_message: None,
+            workflow: None,
+            cache_key: None,
+            cached_from: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_update_execution_record() {
+        let db = create_db();
+        let mut record = create_record(
+            "core:moving_average",
+            Uuid::new_v4(),
+            ExecutionStatus::Completed,
+            1,
+        );
+        db.save_execution_record(&record).unwrap();
+
+        let curve_id = Uuid::new_v4();
+        record.output_curve_id = Some(curve_id);
+        db.save_execution_record(&record).unwrap();
+
+        let loaded = db
+            .get_execution_record(&record.id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.output_curve_id, Some(curve_id));
+
+        let by_curve = db
+            .get_execution_record_for_curve(&curve_id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_curve.id, record.id);
+
+        // Secondary outputs of a multi-output execution are found too
+        let flag_id = Uuid::new_v4();
+        record.outputs = vec![OutputReference {
+            name: "flag".to_string(),
+            curve_id: flag_id,
+            mnemonic: "VSH_FLAG".to_string(),
+            parquet_hash: "abc".to_string(),
+        }];
+        db.save_execution_record(&record).unwrap();
+        let by_flag = db
+            .get_execution_record_for_curve(&flag_id.to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(by_flag.id, record.id);
+
+        let page = db
+            .list_execution_history(&ExecutionHistoryFilter::default())
+            .unwrap();
+        assert_eq!(page.total, 1);
+    }
+
+    #[test]
+    fn test_list_execution_history_filters_and_pages() {
+        let db = create_db();
+        let well_a = Uuid::new_v4();
+        let well_b = Uuid::new_v4();
+        db.save_execution_record(&create_record(
+            "core:moving_average",
+            well_a,
+            ExecutionStatus::Completed,
+            1,
+        ))
+        .unwrap();
+        db.save_execution_record(&create_record(
+            "core:moving_average",
+            well_b,
+            ExecutionStatus::Failed,
+            2,
+        ))
+        .unwrap();
+        db.save_execution_record(&create_record(
+            "petro:vshale_linear",
+            well_a,
+            ExecutionStatus::Cancelled,
+            3,
+        ))
+        .unwrap();
+        db.save_execution_record(&create_record(
+            "petro:vshale_linear",
+            well_a,
+            ExecutionStatus::Completed,
+            4,
+        ))
+        .unwrap();
+
+        let by_well = db
+            .list_execution_history(&ExecutionHistoryFilter {
+                well_id: Some(well_a.to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_well.total, 3);
+        // Newest first
+        assert_eq!(
+            by_well.records[0].started_at.to_rfc3339(),
+            "2024-01-01T04:00:00+00:00"
+        );
+
+        let by_status = db
+            .list_execution_history(&ExecutionHistoryFilter {
+                udf_id: Some("core:moving_average".to_string()),
+                status: Some("failed".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_status.total, 1);
+        assert_eq!(by_status.records[0].well_id, well_b);
+
+        let by_date = db
+            .list_execution_history(&ExecutionHistoryFilter {
+                started_after: Some("2024-01-01T02:00:00Z".to_string()),
+                started_before: Some("2024-01-01T03:30:00+00:00".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_date.total, 2);
+
+        let second_page = db
+            .list_execution_history(&ExecutionHistoryFilter {
+                limit: Some(3),
+                offset: Some(3),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(second_page.total, 4);
+        assert_eq!(second_page.records.len(), 1);
+        assert_eq!(second_page.records[0].status, ExecutionStatus::Completed);
+        assert_eq!(second_page.records[0].udf_id, "core:moving_average");
+    }
+
+    #[test]
+    fn test_save_list_and_delete_workflows() {
+        let db = create_db();
+        let mut workflow = WorkflowDefinition {
+            id: Uuid::new_v4(),
+            name: "Smoothed VShale".to_string(),
+            description: String::new(),
+            nodes: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        db.save_workflow(&workflow).unwrap();
+
+        workflow.name = "Resampled VShale".to_string();
+        db.save_workflow(&workflow).unwrap();
+
+        let listed = db.list_workflows().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Resampled VShale");
+        assert!(db.get_workflow(&workflow.id.to_string()).unwrap().is_some());
+
+        assert!(db.delete_workflow(&workflow.id.to_string()).unwrap());
+        assert!(db.list_workflows().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_save_list_and_delete_mnemonic_aliases() {
+        let db = create_db();
+        let workspace_id = Uuid::new_v4().to_string();
+        let rule = MnemonicRule::exact("GRD", CurveDataType::GammaRay);
+        db.save_mnemonic_alias(&workspace_id, &rule).unwrap();
+        db.save_mnemonic_alias(&workspace_id, &rule.clone().with_priority(50))
+            .unwrap();
+        db.save_mnemonic_alias(
+            "other",
+            &MnemonicRule::regex("TEMP.*", CurveDataType::Unknown),
+        )
+        .unwrap();
+
+        let listed = db.list_mnemonic_aliases(&workspace_id).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].priority, 50);
+
+        assert!(db
+            .delete_mnemonic_alias(&workspace_id, MatchKind::Exact, "GRD")
+            .unwrap());
+        assert!(db.list_mnemonic_aliases(&workspace_id).unwrap().is_empty());
+        assert_eq!(db.list_mnemonic_aliases("other").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_replace_cached_result() {
+        let db = create_db();
+        assert!(db.get_cached_result("key").unwrap().is_none());
+
+        let first = create_record(
+            "petro:vshale_linear",
+            Uuid::new_v4(),
+            ExecutionStatus::Completed,
+            1,
+        );
+        let mut output = UdfOutput::new(
+            "vshale",
+            OutputCurveData {
+                mnemonic: "VSH".to_string(),
+                curve_type: CurveDataType::Computed,
+                unit: "v/v".to_string(),
+                depths: vec![100.0, 100.5],
+                values: vec![Some(0.25), None],
+                description: None,
+            },
+        );
+        db.save_cached_result("key", &first, &output).unwrap();
+
+        let cached = db.get_cached_result("key").unwrap().unwrap();
+        assert_eq!(cached.execution_id, first.id);
+        let vshale = cached.output.output("vshale").unwrap();
+        assert_eq!(vshale.values, vec![Some(0.25), None]);
+
+        // A forced recompute replaces the entry
+        let second = create_record(
+            "petro:vshale_linear",
+            Uuid::new_v4(),
+            ExecutionStatus::Completed,
+            2,
+        );
+        output.add_warning("recomputed");
+        db.save_cached_result("key", &second, &output).unwrap();
+
+        let cached = db.get_cached_result("key").unwrap().unwrap();
+        assert_eq!(cached.execution_id, second.id);
+        assert_eq!(cached.output.warnings, vec!["recomputed".to_string()]);
+
+        assert_eq!(db.clear_result_cache().unwrap(), 1);
+        assert!(db.get_cached_result("key").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_execution_history_rejects_unknown_status() {
+        let db = create_db();
+        let result = db.list_execution_history(&ExecutionHistoryFilter {
+            status: Some("running".to_string()),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+}
@@ -396,7 +396,7 @@ export const CURVE_TYPE_RESTRICTIONS: Record<string, string[] | null> = {
 
 	// Well log track types with restrictions
 	'track-gr': ['GR', 'SGR', 'CGR', 'GAMMA_RAY'],
-	'track-resistivity': ['RT', 'RILD', 'RILM', 'RSFL', 'RXO', 'AT90', 'AT60', 'AT30', 'AT10'],
+	'track-resistivity': ['RT', 'RD', 'RM', 'RS', 'RILD', 'RILM', 'RSFL', 'RXO', 'AT90', 'AT60', 'AT30', 'AT10'],
 	'track-density': ['RHOB', 'RHOZ', 'DENSITY'],
 	'track-neutron': ['NPHI', 'TNPH', 'NPOR', 'NEUTRON'],
 	'track-sonic': ['DT', 'DTC', 'DTS', 'DTCO', 'SONIC'],
@@ -3,17 +3,41 @@
  */
 import { writable, derived, get, type Readable } from 'svelte/store';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type {
 	DataForgeStatus,
 	WorkspaceInfo,
 	WellInfo,
+	WellMarker,
 	CurveInfo,
 	CurveInfoWithWell,
 	CurveData,
 	ProviderInfo,
 	UdfInfo,
 	ParameterDefinition,
+	ValidationError,
 	ExecuteUdfResult,
+	ExecuteUdfRequest,
+	ExecuteUdfOutput,
+	ExecutionProgress,
+	ExecutionHistoryFilter,
+	ExecutionHistoryPage,
+	ExecuteBatchRequest,
+	BatchWellResult,
+	BatchExecutionResult,
+	WorkflowDefinition,
+	SaveWorkflowRequest,
+	ExecuteWorkflowRequest,
+	WorkflowNodeResult,
+	WorkflowExecutionResult,
+	DerivedCurveStatus,
+	VerificationReport,
+	LineageDirection,
+	LineageGraph,
+	ProvExportScope,
+	MatchKind,
+	MnemonicRule,
+	MnemonicAliasInfo,
 	CurveDataPoint
 } from '$lib/types';
 import { workspaceManager } from '$lib/panes/workspace-manager';
@@ -24,6 +48,7 @@ export const status = writable<DataForgeStatus | null>(null);
 export const workspaces = writable<WorkspaceInfo[]>([]);
 export const wells = writable<WellInfo[]>([]);
 export const curves = writable<CurveInfo[]>([]);
+export const wellMarkers = writable<WellMarker[]>([]);
 export const allWorkspaceCurves = writable<CurveInfoWithWell[]>([]);
 export const curveData = writable<CurveData | null>(null);
 
@@ -49,11 +74,30 @@ export const selectedUdfId = writable<string | null>(null);
 export const udfParameters = writable<ParameterDefinition[]>([]);
 export const parameterValues = writable<Record<string, unknown>>({});
 
+// Execution event names (must match commands.rs)
+const EXECUTION_PROGRESS_EVENT = 'udf-execution-progress';
+const EXECUTION_COMPLETED_EVENT = 'udf-execution-completed';
+const BATCH_WELL_COMPLETED_EVENT = 'udf-batch-well-completed';
+const BATCH_COMPLETED_EVENT = 'udf-batch-completed';
+const WORKFLOW_NODE_COMPLETED_EVENT = 'udf-workflow-node-completed';
+const WORKFLOW_COMPLETED_EVENT = 'udf-workflow-completed';
+
 // Execution stores
 export const isExecuting = writable(false);
+export const activeExecutionId = writable<string | null>(null);
+export const executionProgress = writable<ExecutionProgress | null>(null);
 export const isSaving = writable(false);
 export const executionResult = writable<ExecuteUdfResult | null>(null);
 export const validationErrors = writable<Record<string, string>>({});
+export const executionHistory = writable<ExecutionHistoryPage | null>(null);
+export const batchWellResults = writable<BatchWellResult[]>([]);
+export const batchResult = writable<BatchExecutionResult | null>(null);
+export const workflows = writable<WorkflowDefinition[]>([]);
+export const workflowNodeResults = writable<WorkflowNodeResult[]>([]);
+export const workflowResult = writable<WorkflowExecutionResult | null>(null);
+export const staleCurves = writable<DerivedCurveStatus[]>([]);
+export const verificationReport = writable<VerificationReport | null>(null);
+export const lineageGraph = writable<LineageGraph | null>(null);
 
 // UI stores
 export const isLoading = writable(false);
@@ -211,6 +255,7 @@ export async function selectWell(id: string) {
 	selectedWellId.set(id);
 	selectedCurveId.set(null);
 	curves.set([]);
+	wellMarkers.set([]);
 	curveData.set(null);
 	executionResult.set(null);
 
@@ -220,6 +265,13 @@ export async function selectWell(id: string) {
 	} catch (e) {
 		error.set(e instanceof Error ? e.message : String(e));
 	}
+
+	// Well tops are only needed for zone parameters, so failing to load them is not an error
+	try {
+		wellMarkers.set(await invoke<WellMarker[]>('get_well_markers', { wellId: id }));
+	} catch (e) {
+		console.warn('Failed to load well tops:', e);
+	}
 }
 
 export async function selectCurve(id: string) {
@@ -274,7 +326,7 @@ export function setParameterValue(name: string, value: unknown) {
 	});
 }
 
-export async function executeUdf(saveResult = false) {
+export async function executeUdf(saveResult = false, forceRecompute = false) {
 	// Get current values using Svelte's get() helper
 	const currentWorkspaceId = get(selectedWorkspaceId);
 	const currentWellId = get(selectedWellId);
@@ -295,52 +347,503 @@ export async function executeUdf(saveResult = false) {
 	executionResult.set(null);
 
 	try {
-		const result = await invoke<ExecuteUdfResult>('execute_udf', {
-			request: {
-				udf_id: currentUdfId,
-				well_id: currentWellId,
-				workspace_id: currentWorkspaceId,
-				parameters: currentParams,
-				save_result: saveResult
+		// Preflight: catch wrong curve types, short or gappy curves and
+		// mismatched depth grids before starting the execution
+		if (!(await preflightParameters(currentUdfId, currentParams, currentWorkspaceId))) {
+			return;
+		}
+
+		const request: ExecuteUdfRequest = {
+			udf_id: currentUdfId,
+			well_id: currentWellId,
+			workspace_id: currentWorkspaceId,
+			parameters: currentParams,
+			save_result: saveResult,
+			force_recompute: forceRecompute
+		};
+		const result = await runExecution(() => invoke<string>('execute_udf', { request }));
+
+		executionResult.set(result);
+
+		if (!result.success && result.error) {
+			error.set(result.error);
+		} else if (result.success) {
+			// Create a table pane for each output curve
+			for (const output of result.outputs) {
+				createOutputTablePane(result.execution_id, output, udfName);
+			}
+		}
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+	} finally {
+		isExecuting.set(false);
+	}
+}
+
+/**
+ * Validate parameters and their curves without executing.
+ *
+ * Errors are shown on their fields; returns whether the parameters passed.
+ */
+async function preflightParameters(
+	udfId: string,
+	parameters: Record<string, unknown>,
+	workspaceId: string
+): Promise<boolean> {
+	const errors = await invoke<ValidationError[]>('validate_udf_parameters', {
+		udfId,
+		parameters,
+		workspaceId
+	});
+	if (errors.length === 0) {
+		return true;
+	}
+
+	const byField: Record<string, string> = {};
+	for (const e of errors) {
+		byField[e.field] = e.suggestion ? `${e.message}. ${e.suggestion}` : e.message;
+	}
+	validationErrors.set(byField);
+	error.set(errors.length === 1 ? errors[0].message : `${errors.length} parameters need attention`);
+	return false;
+}
+
+/**
+ * Start a background execution and wait for its completion event.
+ *
+ * `start` invokes a command that returns the execution ID immediately;
+ * progress and the final result arrive as Tauri events. Listeners are
+ * registered before invoking so a fast execution cannot complete before we
+ * are listening.
+ */
+async function runExecution(start: () => Promise<string>): Promise<ExecuteUdfResult> {
+	let executionId: string | null = null;
+	const earlyResults = new Map<string, ExecuteUdfResult>();
+	let resolveResult: (result: ExecuteUdfResult) => void = () => {};
+	const completed = new Promise<ExecuteUdfResult>((resolve) => (resolveResult = resolve));
+
+	const unlistenCompleted = await listen<ExecuteUdfResult>(EXECUTION_COMPLETED_EVENT, (event) => {
+		if (executionId === null) {
+			earlyResults.set(event.payload.execution_id, event.payload);
+		} else if (event.payload.execution_id === executionId) {
+			resolveResult(event.payload);
+		}
+	});
+	const unlistenProgress = await listen<ExecutionProgress>(EXECUTION_PROGRESS_EVENT, (event) => {
+		if (event.payload.execution_id === executionId) {
+			executionProgress.set(event.payload);
+		}
+	});
+
+	try {
+		executionId = await start();
+		activeExecutionId.set(executionId);
+
+		const earlyResult = earlyResults.get(executionId);
+		if (earlyResult) {
+			resolveResult(earlyResult);
+		}
+
+		return await completed;
+	} finally {
+		unlistenCompleted();
+		unlistenProgress();
+		activeExecutionId.set(null);
+		executionProgress.set(null);
+	}
+}
+
+/**
+ * Run a UDF over many wells and wait for the batch to finish.
+ *
+ * Per-well results are collected in batchWellResults as they arrive;
+ * aggregate progress is reported through executionProgress, and
+ * cancelExecution() cancels the batch.
+ */
+export async function executeBatch(request: ExecuteBatchRequest): Promise<BatchExecutionResult | null> {
+	let batchId: string | null = null;
+	const earlyWells: BatchWellResult[] = [];
+	const earlyResults = new Map<string, BatchExecutionResult>();
+	let resolveResult: (result: BatchExecutionResult) => void = () => {};
+	const completed = new Promise<BatchExecutionResult>((resolve) => (resolveResult = resolve));
+
+	isExecuting.set(true);
+	error.set(null);
+	batchWellResults.set([]);
+	batchResult.set(null);
+
+	const unlistenWell = await listen<BatchWellResult>(BATCH_WELL_COMPLETED_EVENT, (event) => {
+		if (batchId === null) {
+			earlyWells.push(event.payload);
+		} else if (event.payload.batch_id === batchId) {
+			batchWellResults.update((results) => [...results, event.payload]);
+		}
+	});
+	const unlistenCompleted = await listen<BatchExecutionResult>(BATCH_COMPLETED_EVENT, (event) => {
+		if (batchId === null) {
+			earlyResults.set(event.payload.batch_id, event.payload);
+		} else if (event.payload.batch_id === batchId) {
+			resolveResult(event.payload);
+		}
+	});
+	const unlistenProgress = await listen<ExecutionProgress>(EXECUTION_PROGRESS_EVENT, (event) => {
+		if (event.payload.execution_id === batchId) {
+			executionProgress.set(event.payload);
+		}
+	});
+
+	try {
+		batchId = await invoke<string>('execute_udf_batch', { request });
+		activeExecutionId.set(batchId);
+
+		batchWellResults.set(earlyWells.filter((w) => w.batch_id === batchId));
+		const earlyResult = earlyResults.get(batchId);
+		if (earlyResult) {
+			resolveResult(earlyResult);
+		}
+
+		const result = await completed;
+		batchResult.set(result);
+		if (!result.success && result.error) {
+			error.set(result.error);
+		}
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	} finally {
+		unlistenWell();
+		unlistenCompleted();
+		unlistenProgress();
+		activeExecutionId.set(null);
+		executionProgress.set(null);
+		isExecuting.set(false);
+	}
+}
+
+/**
+ * Load all saved workflow definitions
+ */
+export async function loadWorkflows() {
+	try {
+		const result = await invoke<WorkflowDefinition[]>('list_workflows');
+		workflows.set(result);
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return [];
+	}
+}
+
+/**
+ * Create or update a workflow definition
+ */
+export async function saveWorkflow(request: SaveWorkflowRequest): Promise<WorkflowDefinition | null> {
+	try {
+		const saved = await invoke<WorkflowDefinition>('save_workflow', { request });
+		workflows.update((list) => [...list.filter((w) => w.id !== saved.id), saved]);
+		return saved;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	}
+}
+
+/**
+ * Delete a saved workflow definition
+ */
+export async function deleteWorkflow(workflowId: string): Promise<boolean> {
+	try {
+		const deleted = await invoke<boolean>('delete_workflow', { workflowId });
+		workflows.update((list) => list.filter((w) => w.id !== workflowId));
+		return deleted;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return false;
+	}
+}
+
+/**
+ * Run a saved workflow against one well and wait for it to finish.
+ *
+ * Per-node results are collected in workflowNodeResults as they arrive;
+ * progress over all nodes is reported through executionProgress, and
+ * cancelExecution() cancels the run.
+ */
+export async function executeWorkflow(
+	request: ExecuteWorkflowRequest
+): Promise<WorkflowExecutionResult | null> {
+	let runId: string | null = null;
+	const earlyNodes: WorkflowNodeResult[] = [];
+	const earlyResults = new Map<string, WorkflowExecutionResult>();
+	let resolveResult: (result: WorkflowExecutionResult) => void = () => {};
+	const completed = new Promise<WorkflowExecutionResult>((resolve) => (resolveResult = resolve));
+
+	isExecuting.set(true);
+	error.set(null);
+	workflowNodeResults.set([]);
+	workflowResult.set(null);
+
+	const unlistenNode = await listen<WorkflowNodeResult>(WORKFLOW_NODE_COMPLETED_EVENT, (event) => {
+		if (runId === null) {
+			earlyNodes.push(event.payload);
+		} else if (event.payload.run_id === runId) {
+			workflowNodeResults.update((results) => [...results, event.payload]);
+		}
+	});
+	const unlistenCompleted = await listen<WorkflowExecutionResult>(
+		WORKFLOW_COMPLETED_EVENT,
+		(event) => {
+			if (runId === null) {
+				earlyResults.set(event.payload.run_id, event.payload);
+			} else if (event.payload.run_id === runId) {
+				resolveResult(event.payload);
 			}
+		}
+	);
+	const unlistenProgress = await listen<ExecutionProgress>(EXECUTION_PROGRESS_EVENT, (event) => {
+		if (event.payload.execution_id === runId) {
+			executionProgress.set(event.payload);
+		}
+	});
+
+	try {
+		runId = await invoke<string>('execute_workflow', { request });
+		activeExecutionId.set(runId);
+
+		workflowNodeResults.set(earlyNodes.filter((n) => n.run_id === runId));
+		const earlyResult = earlyResults.get(runId);
+		if (earlyResult) {
+			resolveResult(earlyResult);
+		}
+
+		const result = await completed;
+		workflowResult.set(result);
+		if (!result.success && result.error) {
+			error.set(result.error);
+		}
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	} finally {
+		unlistenNode();
+		unlistenCompleted();
+		unlistenProgress();
+		activeExecutionId.set(null);
+		executionProgress.set(null);
+		isExecuting.set(false);
+	}
+}
+
+/**
+ * Request cancellation of the currently running execution
+ */
+export async function cancelExecution(): Promise<boolean> {
+	const executionId = get(activeExecutionId);
+	if (!executionId) {
+		return false;
+	}
+
+	try {
+		return await invoke<boolean>('cancel_execution', { executionId });
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return false;
+	}
+}
+
+/**
+ * Remove all cached execution results, returning how many were removed
+ */
+export async function clearResultCache(): Promise<number> {
+	try {
+		return await invoke<number>('clear_result_cache');
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return 0;
+	}
+}
+
+/**
+ * Load a workspace's effective mnemonic dictionary, in match order
+ */
+export async function loadMnemonicDictionary(workspaceId: string): Promise<MnemonicAliasInfo[]> {
+	try {
+		return await invoke<MnemonicAliasInfo[]>('get_mnemonic_dictionary', { workspaceId });
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return [];
+	}
+}
+
+/**
+ * Create or update a workspace's mnemonic alias rule
+ */
+export async function saveMnemonicAlias(workspaceId: string, rule: MnemonicRule): Promise<boolean> {
+	try {
+		await invoke('save_mnemonic_alias', { workspaceId, rule });
+		return true;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return false;
+	}
+}
+
+/**
+ * Delete a workspace's mnemonic alias rule
+ */
+export async function deleteMnemonicAlias(
+	workspaceId: string,
+	kind: MatchKind,
+	pattern: string
+): Promise<boolean> {
+	try {
+		return await invoke<boolean>('delete_mnemonic_alias', { workspaceId, kind, pattern });
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return false;
+	}
+}
+
+/**
+ * Load the upstream or downstream lineage graph of a curve
+ */
+export async function loadCurveLineage(
+	curveId: string,
+	direction: LineageDirection = 'Upstream',
+	maxDepth?: number
+): Promise<LineageGraph | null> {
+	try {
+		const result = await invoke<LineageGraph>('get_curve_lineage', {
+			curveId,
+			direction,
+			maxDepth: maxDepth ?? null
 		});
+		lineageGraph.set(result);
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	}
+}
 
-		executionResult.set(result);
+/**
+ * Export provenance as a W3C PROV-JSON document
+ */
+export async function exportProvenance(
+	scope: ProvExportScope
+): Promise<Record<string, unknown> | null> {
+	try {
+		return await invoke<Record<string, unknown>>('export_provenance', { scope });
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	}
+}
+
+/**
+ * Re-run an execution on its recorded inputs and check the outputs match
+ */
+export async function verifyExecution(executionId: string): Promise<VerificationReport | null> {
+	isExecuting.set(true);
+	error.set(null);
+	verificationReport.set(null);
+
+	try {
+		const report = await invoke<VerificationReport>('verify_execution', { executionId });
+		verificationReport.set(report);
+		return report;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	} finally {
+		isExecuting.set(false);
+	}
+}
+
+/**
+ * Check derived curves of a workspace, or of one well, for changed inputs
+ */
+export async function checkStaleCurves(
+	workspaceId: string,
+	wellId?: string
+): Promise<DerivedCurveStatus[]> {
+	try {
+		const result = await invoke<DerivedCurveStatus[]>('check_stale_curves', {
+			workspaceId,
+			wellId: wellId ?? null
+		});
+		staleCurves.set(result);
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return [];
+	}
+}
+
+/**
+ * Recompute a stale derived curve from its recorded execution
+ *
+ * The outputs are saved as new versions of the existing derived curves.
+ * On success the curve is dropped from staleCurves.
+ */
+export async function recomputeDerivedCurve(curveId: string): Promise<ExecuteUdfResult | null> {
+	isExecuting.set(true);
+	error.set(null);
+
+	try {
+		const result = await runExecution(() =>
+			invoke<string>('recompute_derived_curve', { curveId })
+		);
 
 		if (!result.success && result.error) {
 			error.set(result.error);
-		} else if (result.success && result.output_data) {
-			// Create a table pane to display the output
-			createOutputTablePane(result, udfName);
+		} else if (result.success) {
+			staleCurves.update((statuses) => statuses.filter((s) => s.curve_id !== curveId));
 		}
+		return result;
 	} catch (e) {
 		error.set(e instanceof Error ? e.message : String(e));
+		return null;
 	} finally {
 		isExecuting.set(false);
 	}
 }
 
 /**
- * Create a table pane to display UDF output data
+ * Load a page of the local execution history
  */
-function createOutputTablePane(result: ExecuteUdfResult, udfName: string): void {
-	if (!result.output_data || result.output_data.length === 0) {
+export async function loadExecutionHistory(filter: ExecutionHistoryFilter = {}) {
+	try {
+		const result = await invoke<ExecutionHistoryPage>('list_execution_history', { filter });
+		executionHistory.set(result);
+		return result;
+	} catch (e) {
+		error.set(e instanceof Error ? e.message : String(e));
+		return null;
+	}
+}
+
+/**
+ * Create a table pane to display one UDF output curve
+ */
+function createOutputTablePane(executionId: string, output: ExecuteUdfOutput, udfName: string): void {
+	if (output.data.length === 0) {
 		return;
 	}
 
 	// Create the table pane with output data
-	const title = result.output_mnemonic
-		? `${udfName}: ${result.output_mnemonic}`
-		: `${udfName} Output`;
+	const title = `${udfName}: ${output.mnemonic}`;
 
 	workspaceManager.addPane(
 		PaneType.Table,
 		{
 			options: {
-				mnemonic: result.output_mnemonic,
-				executionId: result.execution_id,
+				mnemonic: output.mnemonic,
+				executionId,
 				udfName: udfName,
-				data: result.output_data
+				data: output.data
 			}
 		},
 		{
@@ -361,7 +864,7 @@ export async function saveOutputCurve() {
 	const currentWellId = get(selectedWellId);
 	const currentWorkspaceId = get(selectedWorkspaceId);
 
-	if (!currentResult || !currentResult.success || !currentResult.output_data) {
+	if (!currentResult || !currentResult.success || currentResult.outputs.length === 0) {
 		error.set('No successful execution result to save');
 		return false;
 	}
@@ -375,22 +878,40 @@ export async function saveOutputCurve() {
 	error.set(null);
 
 	try {
-		const saveResponse = await invoke<{ success: boolean; curve_id: string | null; error: string | null }>(
-			'save_output_curve',
-			{
-				request: {
-					execution_id: currentResult.execution_id,
-					well_id: currentWellId,
-					workspace_id: currentWorkspaceId,
-					mnemonic: currentResult.output_mnemonic,
-					output_data: currentResult.output_data
-				}
+		const saveResponse = await invoke<{
+			success: boolean;
+			curve_id: string | null;
+			curve_ids: string[];
+			error: string | null;
+		}>('save_output_curve', {
+			request: {
+				execution_id: currentResult.execution_id,
+				well_id: currentWellId,
+				workspace_id: currentWorkspaceId,
+				outputs: currentResult.outputs.map((output) => ({
+					name: output.name,
+					mnemonic: output.mnemonic,
+					unit: output.unit || null,
+					output_data: output.data
+				}))
 			}
-		);
+		});
 
 		if (saveResponse.success) {
 			// Update the execution result to show it's saved
-			executionResult.update((r) => (r ? { ...r, saved: true, output_curve_id: saveResponse.curve_id } : r));
+			executionResult.update((r) =>
+				r
+					? {
+							...r,
+							saved: true,
+							output_curve_id: saveResponse.curve_id,
+							outputs: r.outputs.map((output, i) => ({
+								...output,
+								curve_id: saveResponse.curve_ids[i] ?? null
+							}))
+						}
+					: r
+			);
 			// Refresh curves list to show the new curve
 			const updatedCurves = await invoke<CurveInfo[]>('list_curves', { wellId: currentWellId });
 			curves.set(updatedCurves);
@@ -33,11 +33,24 @@ export interface CurveInfo {
 	unit: string | null;
 	description: string | null;
 	main_curve_type: string | null;
+	/** DataForge curve_properties ID (e.g. 'deep_resistivity') */
+	property_id: string | null;
 	min_depth: number | null;
 	max_depth: number | null;
 	row_count: number;
 }
 
+/** Well top (formation marker) of a well */
+export interface WellMarker {
+	id: string;
+	name: string;
+	measured_depth: number;
+	tvd: number | null;
+	marker_type: string | null;
+	quality: string | null;
+	comments: string | null;
+}
+
 /** Curve info with associated well data - for curve selector dialogs */
 export interface CurveInfoWithWell extends CurveInfo {
 	well_id: string;
@@ -135,23 +148,83 @@ export interface UdfInfo {
 	description: string;
 	version: string;
 	tags: string[];
+	outputs: OutputDeclaration[];
+}
+
+/** A named output curve a UDF declares it will produce */
+export interface OutputDeclaration {
+	name: string;
+	label: string;
+	description: string;
+	/** null if the type depends on the inputs */
+	curve_type: string | null;
+	/** null if the unit depends on the inputs */
+	unit: string | null;
 }
 
 export interface ParameterDefinition {
 	name: string;
 	label: string;
 	description: string;
-	type: 'curve' | 'number' | 'boolean' | 'string';
+	type:
+		| 'curve'
+		| 'curve_list'
+		| 'number'
+		| 'integer'
+		| 'boolean'
+		| 'string'
+		| 'choice'
+		| 'depth_range'
+		| 'zone';
 	required: boolean;
 	default?: unknown;
 	min?: number;
 	max?: number;
 	unit?: string;
+	/** Family of the units a number may be entered in */
+	unit_family?: string | null;
+	/** Units a number may be entered in, base unit first */
+	units?: string[];
+	/** Unit a number is converted to before execution */
+	unit_target?: UnitTarget | null;
 	allowed_types?: string[];
+	/** Allowed types plus their compatible varieties (e.g. Deep Resistivity for Resistivity) */
+	accepted_types?: string[];
+	/** curve_properties IDs accepted in addition to the types */
+	allowed_properties?: string[];
+	unit_families?: string[];
 	min_length?: number;
 	allow_nulls?: boolean;
+	min_count?: number;
+	max_count?: number | null;
+	max_length?: number | null;
+	options?: ChoiceOption[];
+	constraints?: ParameterConstraint[];
 }
 
+/** Depth unit of the well, or unit of the curve bound to a curve parameter */
+export type UnitTarget = 'depth' | { curve: string };
+
+/** Number entered in an explicit unit */
+export interface Quantity {
+	value: number;
+	unit: string;
+}
+
+export interface ChoiceOption {
+	value: string;
+	label: string;
+}
+
+/** Cross-parameter rule, attached to the parameter it reports on */
+export type ParameterConstraint = {
+	description: string;
+} & (
+	| { type: 'greater_than' | 'greater_or_equal' | 'not_equal'; parameter: string; other: string }
+	| { type: 'mutually_exclusive'; parameters: string[] }
+	| { type: 'required_if'; parameter: string; other: string; equals?: unknown }
+);
+
 export interface ValidationError {
 	field: string;
 	message: string;
@@ -164,25 +237,201 @@ export interface ExecuteUdfRequest {
 	workspace_id: string;
 	parameters: Record<string, unknown>;
 	save_result?: boolean;
+	/** Run the UDF even if a cached result exists */
+	force_recompute?: boolean;
+}
+
+/** How a curve parameter is bound in each well of a batch */
+export type CurveBinding =
+	| { by: 'mnemonic'; value: string }
+	| { by: 'property_id'; value: string }
+	| { by: 'curve_type'; value: string };
+
+export interface ExecuteBatchRequest {
+	udf_id: string;
+	workspace_id: string;
+	/** Every well in the workspace when omitted */
+	well_ids?: string[];
+	/** Non-curve parameter values shared by all wells */
+	parameters: Record<string, unknown>;
+	bindings: Record<string, CurveBinding>;
+	save_results?: boolean;
+	force_recompute?: boolean;
+}
+
+/** Payload of the `udf-batch-well-completed` event */
+export interface BatchWellResult {
+	batch_id: string;
+	well_id: string;
+	status: ExecutionStatus;
+	execution_id: string | null;
+	bound_curves: Record<string, string>;
+	output_curve_ids: string[];
+	/** Execution whose cached output was reused, if the UDF did not run */
+	cached_from: string | null;
+	warnings: string[];
+	error: string | null;
+}
+
+/** Payload of the `udf-batch-completed` event */
+export interface BatchExecutionResult {
+	batch_id: string;
+	success: boolean;
+	wells: BatchWellResult[];
+	completed: number;
+	failed: number;
+	cancelled: number;
+	error: string | null;
+}
+
+// Workflow types
+
+/** A named output of an upstream workflow node */
+export interface NodeOutputRef {
+	node_id: string;
+	output: string;
+}
+
+/** One UDF invocation within a workflow */
+export interface WorkflowNode {
+	id: string;
+	udf_id: string;
+	/** Fixed parameter values, including curves bound to stored curves */
+	parameters: Record<string, unknown>;
+	/** Curve parameters fed from upstream node outputs */
+	inputs: Record<string, NodeOutputRef>;
+}
+
+export interface WorkflowDefinition {
+	id: string;
+	name: string;
+	description: string;
+	nodes: WorkflowNode[];
+	created_at: string;
+	updated_at: string;
+}
+
+export interface SaveWorkflowRequest {
+	/** Existing workflow to update; a new workflow is created when omitted */
+	id?: string;
+	name: string;
+	description?: string;
+	nodes: WorkflowNode[];
+}
+
+export interface ExecuteWorkflowRequest {
+	workflow_id: string;
+	well_id: string;
+	workspace_id: string;
+	save_results?: boolean;
+	force_recompute?: boolean;
+}
+
+/** Payload of the `udf-workflow-node-completed` event */
+export interface WorkflowNodeResult {
+	run_id: string;
+	node_id: string;
+	status: ExecutionStatus;
+	execution_id: string | null;
+	outputs: ExecuteUdfOutput[];
+	cached_from: string | null;
+	warnings: string[];
+	error: string | null;
+}
+
+/** Payload of the `udf-workflow-completed` event */
+export interface WorkflowExecutionResult {
+	run_id: string;
+	workflow_id: string;
+	success: boolean;
+	nodes: WorkflowNodeResult[];
+	completed: number;
+	failed: number;
+	cancelled: number;
+	error: string | null;
+}
+
+/** One named output curve of an execution */
+export interface ExecuteUdfOutput {
+	name: string;
+	mnemonic: string;
+	unit: string;
+	curve_type: string;
+	curve_id: string | null;
+	data: CurveDataPoint[];
 }
 
+/** output_* fields describe the first output; `outputs` lists all of them */
 export interface ExecuteUdfResult {
 	success: boolean;
 	execution_id: string;
 	output_mnemonic: string | null;
 	output_curve_id: string | null;
 	output_data: CurveDataPoint[] | null;
+	outputs: ExecuteUdfOutput[];
 	warnings: string[];
 	error: string | null;
 	saved: boolean;
+	/** Execution whose cached output was reused, if the UDF did not run */
+	cached_from: string | null;
+}
+
+/** Payload of the `udf-execution-progress` event */
+export interface ExecutionProgress {
+	execution_id: string;
+	progress: number;
+	message: string | null;
+	is_cancelled: boolean;
+}
+
+/** Payload of the `udf-execution-warning` event */
+export interface ExecutionWarning {
+	execution_id: string;
+	warning: string;
 }
 
 // Provenance types
+/** Conversion applied to an input curve's values before execution */
+export interface UnitConversion {
+	from_unit: string;
+	to_unit: string;
+	factor: number;
+}
+
+/** Resampling applied to an input curve on another depth grid */
+export interface DepthAlignment {
+	strategy: 'first' | 'reference' | 'grid';
+	reference?: string | null;
+	step?: number | null;
+	interpolation: 'linear' | 'nearest' | 'none';
+	source_samples: number;
+	samples: number;
+}
+
+/** Numeric parameter value converted to the unit the UDF works in */
+export interface ParameterConversion {
+	parameter: string;
+	value: number;
+	unit: string;
+	converted_value: number;
+	converted_unit: string;
+}
+
 export interface InputReference {
 	curve_id: string;
 	mnemonic: string;
 	parquet_hash: string;
 	version: number;
+	unit_conversion?: UnitConversion | null;
+	alignment?: DepthAlignment | null;
+}
+
+/** A saved output curve of an execution */
+export interface OutputReference {
+	name: string;
+	curve_id: string;
+	mnemonic: string;
+	parquet_hash: string;
 }
 
 export interface CurveProvenance {
@@ -191,11 +440,198 @@ export interface CurveProvenance {
 	udf_version: string;
 	inputs: InputReference[];
 	parameters: Record<string, unknown>;
+	parameter_conversions: ParameterConversion[];
 	output_curve_id: string | null;
 	output_parquet_hash: string | null;
+	outputs: OutputReference[];
 	started_at: string;
 	completed_at: string | null;
 	compute_app_version: string;
 	status: string;
 	error_message: string | null;
 }
+
+// Lineage types
+export type LineageDirection = 'Upstream' | 'Downstream';
+
+export interface LineageCurveNode {
+	curve_id: string;
+	/** Null if the curve no longer exists */
+	well_id: string | null;
+	mnemonic: string | null;
+	version: number | null;
+	is_derived: boolean;
+	deleted: boolean;
+}
+
+export interface LineageExecutionNode {
+	execution_id: string;
+	udf_id: string;
+	udf_version: string;
+	parameters: Record<string, unknown>;
+	started_at: string;
+	completed_at: string | null;
+	compute_app_version: string;
+}
+
+/** Used: curve (from) was read by execution (to); Generated: execution (from) produced curve (to) */
+export interface LineageEdge {
+	from: string;
+	to: string;
+	relation: 'Used' | 'Generated';
+	/** Parameter the input was bound to, or the output name */
+	name: string | null;
+	parquet_hash: string | null;
+	version: number | null;
+}
+
+export interface LineageGraph {
+	root: string;
+	direction: LineageDirection;
+	curves: LineageCurveNode[];
+	executions: LineageExecutionNode[];
+	edges: LineageEdge[];
+}
+
+/** What export_provenance covers */
+export type ProvExportScope =
+	| { type: 'curve'; curve_id: string }
+	| { type: 'lineage'; curve_id: string; direction: LineageDirection }
+	| { type: 'workspace'; workspace_id: string };
+
+// Reproducibility verification types
+/** Per-sample comparison of a recomputed output with the recorded one */
+export interface SampleDiff {
+	recorded_samples: number;
+	recomputed_samples: number;
+	compared_samples: number;
+	depth_mismatches: number;
+	differing_samples: number;
+	null_mismatches: number;
+	max_abs_difference: number | null;
+	mean_abs_difference: number | null;
+	first_difference_depth: number | null;
+}
+
+export interface OutputVerification {
+	name: string;
+	recorded_hash: string;
+	recomputed_hash: string | null;
+	matches: boolean;
+	diff: SampleDiff | null;
+	error: string | null;
+}
+
+export interface VerificationReport {
+	execution_id: string;
+	udf_id: string;
+	recorded_udf_version: string;
+	current_udf_version: string;
+	reproducible: boolean;
+	outputs: OutputVerification[];
+	error: string | null;
+}
+
+// Stale derived curve types
+export type StaleReason = 'Deleted' | 'DataChanged' | 'VersionChanged';
+
+/** An input curve that changed since a derived curve was computed */
+export interface StaleInput {
+	curve_id: string;
+	reason: StaleReason;
+	recorded_version: number;
+	recorded_hash: string;
+	current_version: number | null;
+	current_hash: string | null;
+}
+
+export interface DerivedCurveStatus {
+	curve_id: string;
+	well_id: string;
+	mnemonic: string;
+	execution_id: string | null;
+	udf_id: string | null;
+	is_stale: boolean;
+	stale_inputs: StaleInput[];
+	/** Set when the source execution could not be checked */
+	error: string | null;
+}
+
+// Execution history types
+export type ExecutionStatus = 'Completed' | 'Failed' | 'Cancelled';
+
+/** Execution record as stored in the local execution history */
+export interface ExecutionRecord {
+	id: string;
+	udf_id: string;
+	udf_version: string;
+	well_id: string;
+	workspace_id: string;
+	inputs: { curve_id: string; version: number; parquet_hash: string }[];
+	parameters: Record<string, unknown>;
+	/** Depths each depth range or zone parameter resolved to */
+	depth_intervals?: Record<string, { top: number; base: number }>;
+	output_curve_id: string | null;
+	output_parquet_hash: string | null;
+	outputs: OutputReference[];
+	started_at: string;
+	completed_at: string | null;
+	compute_app_version: string;
+	status: ExecutionStatus;
+	error_message: string | null;
+	/** Set when the execution ran as a workflow node */
+	workflow?: WorkflowStep;
+	/** Hash of UDF version, parameters and input content */
+	cache_key?: string;
+	/** Execution whose cached output was reused instead of running the UDF */
+	cached_from?: string;
+}
+
+/** Position of an execution within a workflow run */
+export interface WorkflowStep {
+	workflow_id: string;
+	run_id: string;
+	node_id: string;
+	upstream: { parameter: string; node_id: string; output: string; curve_id: string }[];
+}
+
+/** Filter for list_execution_history; date bounds are RFC 3339 and inclusive */
+export interface ExecutionHistoryFilter {
+	udf_id?: string;
+	well_id?: string;
+	workspace_id?: string;
+	status?: 'completed' | 'failed' | 'cancelled';
+	started_after?: string;
+	started_before?: string;
+	limit?: number;
+	offset?: number;
+}
+
+export interface ExecutionHistoryPage {
+	records: ExecutionRecord[];
+	total: number;
+	limit: number;
+	offset: number;
+}
+
+export type MatchKind = 'exact' | 'regex';
+
+export type MnemonicVendor = 'schlumberger' | 'halliburton' | 'baker_hughes';
+
+/** Rule mapping curve mnemonics to a curve type */
+export interface MnemonicRule {
+	/** Mnemonic, or a regex matched against the whole mnemonic (case-insensitive) */
+	pattern: string;
+	kind: MatchKind;
+	/** Curve type variant (e.g., "GammaRay", "DeepResistivity") */
+	curve_type: string;
+	vendor?: MnemonicVendor | null;
+	/** Rules with a higher priority win (defaults to above all built-in rules) */
+	priority?: number;
+}
+
+/** Rule of a workspace's effective mnemonic dictionary */
+export interface MnemonicAliasInfo extends MnemonicRule {
+	/** Whether the rule is a workspace override rather than a default */
+	workspace: boolean;
+}